@@ -0,0 +1,99 @@
+//! Ways to evaluate the leaf nodes of a search tree, see [Evaluator].
+use std::fmt::{Debug, Formatter};
+use std::marker::PhantomData;
+
+use cast_trait::Cast;
+use rand::Rng;
+
+use crate::ai::minimax::Heuristic;
+use crate::board::{Board, Outcome};
+use crate::pov::NonPov;
+use crate::wdl::WDL;
+
+/// The result of [Evaluator::evaluate].
+#[derive(Debug, Clone)]
+pub struct Evaluation {
+    /// The value of the board from the POV of `board.next_player()`.
+    pub wdl: WDL<f32>,
+    /// The prior probability of each available move, in the order of [available_moves](crate::board::BoardMoves::available_moves).
+    /// `None` means all moves are equally likely.
+    pub policy: Option<Vec<f32>>,
+}
+
+/// Estimates the value (and optionally the move priors) of boards that are not done yet.
+pub trait Evaluator<B: Board>: Debug + Sync {
+    /// Evaluate `board`, which is never done.
+    fn evaluate(&self, board: &B, rng: &mut impl Rng) -> Evaluation;
+}
+
+/// Play random moves until the game is done, the outcome is used as the value. Does not provide a policy.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct RandomRollout;
+
+impl<B: Board> Evaluator<B> for RandomRollout {
+    fn evaluate(&self, board: &B, rng: &mut impl Rng) -> Evaluation {
+        let outcome = random_playout(board.clone(), rng);
+        Evaluation {
+            wdl: outcome.pov(board.next_player()).to_wdl(),
+            policy: None,
+        }
+    }
+}
+
+pub fn random_playout<B: Board>(mut board: B, rng: &mut impl Rng) -> Outcome {
+    assert!(!board.is_done(), "should never start random playout on a done board");
+
+    loop {
+        board.play(board.random_available_move(rng));
+
+        if let Some(outcome) = board.outcome() {
+            return outcome;
+        }
+    }
+}
+
+/// Adapter that turns a [Heuristic] into an [Evaluator]. Does not provide a policy.
+///
+/// The heuristic value `v` is mapped to the scalar value `tanh(v / scale)`, which is then split into win and loss
+/// probabilities without any draw probability. The heuristic is always called with depth `0`.
+pub struct HeuristicEvaluator<B: Board, H: Heuristic<B>> {
+    heuristic: H,
+    scale: f32,
+    ph: PhantomData<B>,
+}
+
+impl<B: Board, H: Heuristic<B>> HeuristicEvaluator<B, H> {
+    pub fn new(heuristic: H, scale: f32) -> Self {
+        assert!(scale > 0.0, "scale must be positive, got {}", scale);
+        HeuristicEvaluator {
+            heuristic,
+            scale,
+            ph: PhantomData,
+        }
+    }
+}
+
+impl<B: Board, H: Heuristic<B> + Sync> Evaluator<B> for HeuristicEvaluator<B, H>
+where
+    H::V: Cast<f32>,
+{
+    fn evaluate(&self, board: &B, _: &mut impl Rng) -> Evaluation {
+        let value: f32 = self.heuristic.value(board, 0).cast();
+        let value = (value / self.scale).tanh();
+
+        Evaluation {
+            wdl: WDL::new((1.0 + value) / 2.0, 0.0, (1.0 - value) / 2.0),
+            policy: None,
+        }
+    }
+}
+
+impl<B: Board, H: Heuristic<B>> Debug for HeuristicEvaluator<B, H> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "HeuristicEvaluator {{ heuristic: {:?}, scale: {} }}",
+            self.heuristic, self.scale
+        )
+    }
+}
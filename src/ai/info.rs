@@ -0,0 +1,103 @@
+//! Structured information about a running or finished search, see [SearchInfo].
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
+
+use crate::wdl::WDL;
+
+/// A callback that receives [SearchInfo] while a bot is searching.
+pub type InfoCallback<M> = Box<dyn FnMut(&SearchInfo<M>) + Send>;
+
+/// Information about a search, reported by bots through an [InfoCallback].
+///
+/// All scores are from the POV of the player to move on the board the search started from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchInfo<M> {
+    /// The depth of the search. For minimax this is the last completed iteration,
+    /// for MCTS the length of the principal variation.
+    pub depth: u32,
+    /// The number of nodes visited so far. For MCTS this is the number of iterations.
+    pub nodes: u64,
+    /// The time since the start of the search.
+    pub time: Duration,
+    pub score: Score,
+    /// The expected line of play, starting with the best move. Can be shorter than `depth`.
+    pub pv: Vec<M>,
+    /// Statistics for each move available on the root board, empty if the search does not track them.
+    pub moves: Vec<MoveInfo<M>>,
+}
+
+/// The evaluation of a board or move.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Score {
+    /// The scalar value. For MCTS this is `wdl.value()`, for minimax the heuristic value.
+    pub value: f32,
+    /// The win, draw and loss probabilities, if the search estimates them.
+    pub wdl: Option<WDL<f32>>,
+}
+
+/// Search statistics for a single move available on the root board.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MoveInfo<M> {
+    pub mv: M,
+    /// The number of times the move was visited, if the search counts visits.
+    pub visits: Option<u64>,
+    /// The score of the resulting board, `None` if it was not evaluated.
+    pub score: Option<Score>,
+    /// The prior probability of the move, if the search uses priors.
+    pub prior: Option<f32>,
+}
+
+impl<M> SearchInfo<M> {
+    /// The number of nodes visited per second.
+    pub fn nps(&self) -> f32 {
+        let secs = self.time.as_secs_f32();
+        if secs == 0.0 {
+            0.0
+        } else {
+            self.nodes as f32 / secs
+        }
+    }
+
+    pub fn best_move(&self) -> Option<&M> {
+        self.pv.first()
+    }
+}
+
+impl Score {
+    pub fn from_value(value: f32) -> Self {
+        Score { value, wdl: None }
+    }
+
+    pub fn from_wdl(wdl: WDL<f32>) -> Self {
+        Score {
+            value: wdl.value(),
+            wdl: Some(wdl),
+        }
+    }
+}
+
+/// Formats as a single line in the style of an UCI info command,
+/// eg. `depth 3 nodes 1000 nps 20000 time 50 score 0.25 wdl 0.500 0.250 0.250 pv a1 b2 c3`.
+impl<M: Display> Display for SearchInfo<M> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "depth {} nodes {} nps {:.0} time {} score {}",
+            self.depth,
+            self.nodes,
+            self.nps(),
+            self.time.as_millis(),
+            self.score.value,
+        )?;
+        if let Some(wdl) = self.score.wdl {
+            write!(f, " wdl {:.3} {:.3} {:.3}", wdl.win, wdl.draw, wdl.loss)?;
+        }
+        if !self.pv.is_empty() {
+            write!(f, " pv")?;
+            for mv in &self.pv {
+                write!(f, " {}", mv)?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,152 @@
+//! Limits for a single search, see [SearchLimits].
+use std::cmp::min;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// The number of moves a game is assumed to still take if [Clock::moves_to_go] is not known.
+const DEFAULT_MOVES_TO_GO: u32 = 30;
+
+/// The limits for a single search, used by [Bot::select_move_limited](crate::ai::Bot::select_move_limited).
+///
+/// The search stops as soon as any of the limits is reached. If none of `move_time`, `clock`, `nodes` and `depth`
+/// are set the bot falls back to its own default settings, `stop` can still interrupt the search in that case.
+/// Bots ignore limits that don't make sense for them, eg. MCTS has no concept of depth.
+#[derive(Debug, Clone, Default)]
+pub struct SearchLimits {
+    /// The maximum time to spend on this move.
+    pub move_time: Option<Duration>,
+    /// The clock of the player to move, the time spent on this move is derived from it.
+    pub clock: Option<Clock>,
+    /// The clock of the opponent. Bots don't use it themselves, but it is passed on to external engines.
+    pub opponent_clock: Option<Clock>,
+    /// The maximum number of nodes to visit. For MCTS this is the number of iterations.
+    pub nodes: Option<u64>,
+    /// The maximum depth to search.
+    pub depth: Option<u32>,
+    /// A flag that can be set from another thread to stop the search as soon as possible.
+    pub stop: Option<Arc<AtomicBool>>,
+}
+
+/// The state of a chess-style clock.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Clock {
+    /// The time left on the clock.
+    pub remaining: Duration,
+    /// The time added to the clock after each move.
+    pub increment: Duration,
+    /// The number of moves until the next time control, if known.
+    pub moves_to_go: Option<u32>,
+}
+
+impl SearchLimits {
+    pub fn move_time(time: Duration) -> Self {
+        SearchLimits {
+            move_time: Some(time),
+            ..Default::default()
+        }
+    }
+
+    pub fn clock(remaining: Duration, increment: Duration) -> Self {
+        SearchLimits {
+            clock: Some(Clock {
+                remaining,
+                increment,
+                moves_to_go: None,
+            }),
+            ..Default::default()
+        }
+    }
+
+    pub fn nodes(nodes: u64) -> Self {
+        SearchLimits {
+            nodes: Some(nodes),
+            ..Default::default()
+        }
+    }
+
+    pub fn depth(depth: u32) -> Self {
+        SearchLimits {
+            depth: Some(depth),
+            ..Default::default()
+        }
+    }
+
+    /// Also pass the clock of the opponent, see [SearchLimits::opponent_clock].
+    pub fn with_opponent_clock(mut self, clock: Clock) -> Self {
+        self.opponent_clock = Some(clock);
+        self
+    }
+
+    /// Also stop the search when `stop` is set.
+    pub fn with_stop(mut self, stop: Arc<AtomicBool>) -> Self {
+        self.stop = Some(stop);
+        self
+    }
+
+    /// Whether any limit is set, not counting the stop flag.
+    pub fn is_limited(&self) -> bool {
+        self.move_time.is_some() || self.clock.is_some() || self.nodes.is_some() || self.depth.is_some()
+    }
+
+    /// The maximum time to spend on this move, the minimum of `move_time` and the time allocated from `clock`.
+    pub fn time_budget(&self) -> Option<Duration> {
+        let clock_time = self.clock.map(|clock| clock.allocate());
+        match (self.move_time, clock_time) {
+            (None, None) => None,
+            (Some(time), None) | (None, Some(time)) => Some(time),
+            (Some(move_time), Some(clock_time)) => Some(min(move_time, clock_time)),
+        }
+    }
+
+    /// Whether the stop flag has been set.
+    pub fn is_stopped(&self) -> bool {
+        self.stop.as_ref().is_some_and(|stop| stop.load(Ordering::Relaxed))
+    }
+
+    /// Start tracking a search against these limits.
+    pub fn start(&self) -> SearchTimer<'_> {
+        let start = Instant::now();
+        SearchTimer {
+            limits: self,
+            start,
+            deadline: self.time_budget().map(|time| start + time),
+        }
+    }
+}
+
+impl Clock {
+    /// The time to spend on a single move: an equal share of the remaining time over the remaining moves
+    /// plus half of the increment, but never more than half of the remaining time.
+    pub fn allocate(self) -> Duration {
+        let moves_to_go = self.moves_to_go.unwrap_or(DEFAULT_MOVES_TO_GO).max(1);
+        let time = self.remaining / moves_to_go + self.increment / 2;
+        min(time, self.remaining / 2)
+    }
+}
+
+/// Keeps track of a running search, see [SearchLimits::start].
+#[derive(Debug)]
+pub struct SearchTimer<'a> {
+    limits: &'a SearchLimits,
+    start: Instant,
+    deadline: Option<Instant>,
+}
+
+impl SearchTimer<'_> {
+    pub fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// Whether the search should stop after having visited `nodes` nodes.
+    /// This only checks the `nodes` limit and not the `depth` limit.
+    pub fn should_stop(&self, nodes: u64) -> bool {
+        self.limits.nodes.is_some_and(|max_nodes| nodes >= max_nodes)
+            || self.deadline.is_some_and(|deadline| Instant::now() >= deadline)
+            || self.limits.is_stopped()
+    }
+}
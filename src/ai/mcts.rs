@@ -1,14 +1,22 @@
+use std::any::Any;
 use std::fmt::{Debug, Formatter};
 use std::num::NonZeroUsize;
 use std::ops::{Index, IndexMut};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
 use decorum::N32;
 use internal_iterator::{InternalIterator, IteratorExt};
+use rand::rngs::SmallRng;
 use rand::seq::IteratorRandom;
-use rand::Rng;
+use rand::{Rng, SeedableRng};
+use rayon::iter::{IndexedParallelIterator, IntoParallelIterator, ParallelIterator};
 
+use crate::ai::evaluator::{Evaluation, Evaluator, RandomRollout};
+use crate::ai::info::{InfoCallback, MoveInfo, Score, SearchInfo};
+use crate::ai::limits::SearchLimits;
 use crate::ai::Bot;
-use crate::board::{AltBoard, Outcome};
+use crate::board::AltBoard;
 use crate::pov::{NonPov, Pov};
 use crate::wdl::{OutcomeWDL, WDL};
 
@@ -42,17 +50,23 @@ impl IntoIterator for IdxRange {
 /// Represents a node in the MCTS search tree.
 ///
 /// The outcome or wdl in this node are always from the POV of the player that just played `self.last_move`.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Node<M> {
     pub last_move: Option<M>,
     pub children: Option<IdxRange>,
     pub visits: i64,
+    /// The number of threads currently searching through this node, see [mcts_build_tree_shared].
+    pub virtual_loss: i64,
+    /// The prior probability of picking this node, set when the parent is expanded.
+    /// This comes from the policy of the [Evaluator], or is uniform over all children of the parent if there is none.
+    pub prior: f32,
     pub kind: SNodeKind,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Copy, Clone)]
 pub enum SNodeKind {
-    Estimate(WDL<i64>),
+    /// The sum of the results backpropagated through this node.
+    Estimate(WDL<f64>),
     Solved(OutcomeWDL),
 }
 
@@ -68,6 +82,8 @@ impl<M> Node<M> {
         Node {
             last_move,
             visits: 0,
+            virtual_loss: 0,
+            prior: 1.0,
             children: None,
             kind,
         }
@@ -75,7 +91,7 @@ impl<M> Node<M> {
 
     pub fn is_unvisited(&self) -> bool {
         match self.kind {
-            SNodeKind::Estimate(wdl) => wdl.sum() == 0,
+            SNodeKind::Estimate(_) => self.visits == 0 && self.virtual_loss == 0,
             SNodeKind::Solved(_) => false,
         }
     }
@@ -98,10 +114,15 @@ impl<M> Node<M> {
     }
 
     pub fn increment(&mut self, outcome: OutcomeWDL) {
+        self.increment_wdl(outcome.to_wdl());
+    }
+
+    /// Like [Self::increment], but for a possibly fractional result, for example from an [Evaluator].
+    pub fn increment_wdl(&mut self, value: WDL<f32>) {
         self.visits += 1;
         match &mut self.kind {
             SNodeKind::Estimate(wdl) => {
-                *wdl += outcome.to_wdl();
+                *wdl += value.cast::<f64>();
             }
             SNodeKind::Solved(_) => {
                 panic!("Cannot increment solved node")
@@ -109,22 +130,45 @@ impl<M> Node<M> {
         }
     }
 
+    /// Like [Self::increment], but if another thread has solved this node in the meantime only the visit is counted.
+    fn increment_shared(&mut self, value: WDL<f32>) {
+        match self.kind {
+            SNodeKind::Estimate(_) => self.increment_wdl(value),
+            SNodeKind::Solved(_) => self.visits += 1,
+        }
+    }
+
+    /// Add the statistics of `other`, which must represent the same board, to this node.
+    /// A solution from either node takes precedence over the estimates.
+    fn merge(&mut self, other: &Node<M>) {
+        self.visits += other.visits;
+        match other.kind {
+            SNodeKind::Solved(outcome) => {
+                if self.solution().is_none() {
+                    self.kind = SNodeKind::Solved(outcome);
+                }
+            }
+            SNodeKind::Estimate(other_wdl) => {
+                if let SNodeKind::Estimate(wdl) = &mut self.kind {
+                    *wdl += other_wdl;
+                }
+            }
+        }
+    }
+
     /// The value of this node from the POV of the player that just played `self.last_move`.
     pub fn wdl(&self) -> WDL<f32> {
         match self.kind {
-            SNodeKind::Estimate(wdl) => {
-                let visits = wdl.sum();
-                wdl.cast::<f32>() / visits as f32
-            }
+            SNodeKind::Estimate(wdl) => (wdl / wdl.sum()).cast::<f32>(),
             SNodeKind::Solved(outcome) => outcome.to_wdl(),
         }
     }
 
-    /// Return the uct value of this code.
+    /// Return the selection score of this node according to `policy`.
     ///
     /// For solved nodes this is just the unit value, with no exploration bonus. This is equivalent to
     /// a child node that's visited an infinite amount of times (together with the parent node).
-    fn uct(&self, parent_visits: i64, exploration_weight: f32) -> f32 {
+    fn selection_score(&self, parent_visits: i64, policy: &impl SelectionPolicy) -> f32 {
         //TODO continue investigating this, what uct value to use for solved (in practice lost and drawn) nodes?
         // if exploration_weight < 0.0 {
         //     let value_unit = (self.wdl().value() + 1.0) / 2.0;
@@ -135,19 +179,125 @@ impl<M> Node<M> {
 
         match self.kind {
             SNodeKind::Estimate(wdl) => {
-                let visits = wdl.sum() as f32;
-                let value = wdl.cast::<f32>().value() / visits;
-                let value_unit = (value + 1.0) / 2.0;
-
-                let explore = ((parent_visits as f32).ln() / visits).sqrt();
-
-                value_unit + exploration_weight * explore
+                // virtual losses count as visits lost by the player that played this move
+                let wdl = WDL {
+                    loss: wdl.loss + self.virtual_loss as f64,
+                    ..wdl
+                };
+                policy.score(parent_visits, wdl.cast::<f32>(), self.prior)
             }
             SNodeKind::Solved(outcome) => (outcome.sign::<f32>() + 1.0) / 2.0,
         }
     }
 }
 
+/// The policy that decides which child to explore next during the selection phase of MCTS.
+///
+/// Scores should be on the same scale as the unit value of a node, `0` for a loss and `1` for a win,
+/// since solved children are scored by their unit value directly.
+pub trait SelectionPolicy: Debug + Sync {
+    /// Whether unvisited children are explored first, in random order, before [Self::score] is used.
+    /// If this returns `false`, [Self::score] is also called for unvisited children.
+    fn unvisited_first(&self) -> bool {
+        true
+    }
+
+    /// The score of a child, the child with the highest score is explored next.
+    ///
+    /// * `parent_visits` is the number of visits of the parent, including virtual losses.
+    /// * `wdl` contains the results of the child from the POV of the player choosing between the children,
+    ///   with virtual losses counted as losses. Its sum is the number of visits of the child.
+    /// * `prior` is the prior probability of the child, see [Node::prior].
+    fn score(&self, parent_visits: i64, wdl: WDL<f32>, prior: f32) -> f32;
+}
+
+/// The standard UCT formula: `Q + c * sqrt(ln(N) / n)`, where `Q` is the average result mapped to `[0, 1]`.
+#[derive(Debug, Copy, Clone)]
+pub struct Uct {
+    pub exploration_weight: f32,
+}
+
+impl Uct {
+    pub fn new(exploration_weight: f32) -> Self {
+        Uct { exploration_weight }
+    }
+}
+
+impl SelectionPolicy for Uct {
+    fn score(&self, parent_visits: i64, wdl: WDL<f32>, _: f32) -> f32 {
+        let visits = wdl.sum();
+        let value = wdl.value() / visits;
+        let value_unit = (value + 1.0) / 2.0;
+
+        let explore = ((parent_visits as f32).ln() / visits).sqrt();
+
+        value_unit + self.exploration_weight * explore
+    }
+}
+
+/// UCB1-Tuned, which scales the UCT exploration term by an upper bound on the variance of the results of the child:
+/// `Q + c * sqrt(ln(N) / n * min(1/4, var + sqrt(2 * ln(N) / n)))`.
+#[derive(Debug, Copy, Clone)]
+pub struct Ucb1Tuned {
+    pub exploration_weight: f32,
+}
+
+impl Ucb1Tuned {
+    pub fn new(exploration_weight: f32) -> Self {
+        Ucb1Tuned { exploration_weight }
+    }
+}
+
+impl SelectionPolicy for Ucb1Tuned {
+    fn score(&self, parent_visits: i64, wdl: WDL<f32>, _: f32) -> f32 {
+        let visits = wdl.sum();
+        let wdl = wdl / visits;
+
+        // results are 1 for a win, 1/2 for a draw and 0 for a loss
+        let mean = wdl.win + 0.5 * wdl.draw;
+        let variance = (wdl.win + 0.25 * wdl.draw) - mean * mean;
+
+        let log_parent = (parent_visits as f32).ln();
+        let variance_bound = variance + (2.0 * log_parent / visits).sqrt();
+        let explore = (log_parent / visits * variance_bound.min(0.25)).sqrt();
+
+        mean + self.exploration_weight * explore
+    }
+}
+
+/// The PUCT formula as used by AlphaZero: `Q + c * P * sqrt(N) / (1 + n)`, where `P` is the prior of the child.
+///
+/// Unvisited children are not explored first, instead they get the value of a draw as `Q`.
+#[derive(Debug, Copy, Clone)]
+pub struct Puct {
+    pub exploration_weight: f32,
+}
+
+impl Puct {
+    pub fn new(exploration_weight: f32) -> Self {
+        Puct { exploration_weight }
+    }
+}
+
+impl SelectionPolicy for Puct {
+    fn unvisited_first(&self) -> bool {
+        false
+    }
+
+    fn score(&self, parent_visits: i64, wdl: WDL<f32>, prior: f32) -> f32 {
+        let visits = wdl.sum();
+        let value_unit = if visits == 0.0 {
+            0.5
+        } else {
+            (wdl.value() / visits + 1.0) / 2.0
+        };
+
+        let explore = prior * (parent_visits as f32).sqrt() / (1.0 + visits);
+
+        value_unit + self.exploration_weight * explore
+    }
+}
+
 // TODO extend mcts to non-alternating games
 
 /// A small wrapper type for `Vec<SNode>` that uses u64 for indexing instead.
@@ -166,18 +316,76 @@ impl<B: AltBoard> Tree<B> {
     }
 
     pub fn best_child(&self) -> usize {
-        let children = self[0].children.expect("Root node must have children");
+        self.best_child_of(0).expect("Root node must have children")
+    }
+
+    fn best_child_of(&self, node: usize) -> Option<usize> {
+        let children = self[node].children?;
 
         //pick the winning child if any
         // there should only be at most one, so we're not biasing towards earlier moves here
         let won_child = children.iter().find(|&c| self[c].solution() == Some(OutcomeWDL::Win));
         if let Some(win_child) = won_child {
-            return win_child;
+            return Some(win_child);
         }
 
         // pick the most visited child
         //TODO filter out lost children
-        children.iter().max_by_key(|&c| self[c].visits).unwrap()
+        children.iter().max_by_key(|&c| self[c].visits)
+    }
+
+    /// The principal variation, the sequence of moves both players are expected to play starting from `root_board`.
+    ///
+    /// This follows the same choice as [Self::best_child] at every node: a winning child if there is one,
+    /// otherwise the most visited child. It stops at nodes that have not been expanded yet
+    /// and at unsolved nodes that have not been visited yet.
+    pub fn principal_variation(&self) -> Vec<B::Move> {
+        let mut pv = vec![];
+        let mut node = 0;
+        while let Some(child) = self.best_child_of(node) {
+            if self[child].visits == 0 && self[child].solution().is_none() {
+                break;
+            }
+            pv.push(self[child].last_move.unwrap());
+            node = child;
+        }
+        pv
+    }
+
+    /// Summarize the state of this tree, `nodes` and `time` are the statistics of the search that built it.
+    pub fn search_info(&self, nodes: u64, time: Duration) -> SearchInfo<B::Move> {
+        let pv = self.principal_variation();
+
+        let moves = match self[0].children {
+            None => vec![],
+            Some(children) => children
+                .iter()
+                .map(|c| {
+                    let child = &self[c];
+                    // the child values are from the POV of the player that played the move, which is the root player
+                    let score = if child.visits > 0 {
+                        Some(Score::from_wdl(child.wdl()))
+                    } else {
+                        None
+                    };
+                    MoveInfo {
+                        mv: child.last_move.unwrap(),
+                        visits: Some(child.visits as u64),
+                        score,
+                        prior: Some(child.prior),
+                    }
+                })
+                .collect(),
+        };
+
+        SearchInfo {
+            depth: pv.len() as u32,
+            nodes,
+            time,
+            score: Score::from_wdl(self.wdl()),
+            pv,
+            moves,
+        }
     }
 
     pub fn best_move(&self) -> B::Move {
@@ -192,6 +400,50 @@ impl<B: AltBoard> Tree<B> {
         self[0].wdl().flip()
     }
 
+    /// Find the node reached by playing `moves` starting from `root_board`,
+    /// walking only the children that match the next move.
+    /// Returns `None` if any node on the way has not been expanded yet.
+    pub fn find_moves(&self, moves: &[B::Move]) -> Option<usize> {
+        if self.nodes.is_empty() {
+            return None;
+        }
+
+        moves.iter().try_fold(0, |node, &mv| {
+            self[node]
+                .children?
+                .iter()
+                .find(|&child| self[child].last_move == Some(mv))
+        })
+    }
+
+    /// Build a new tree rooted at `node`, which must correspond to `board`.
+    /// The statistics of all nodes in the subtree are kept.
+    pub fn subtree(&self, node: usize, board: B) -> Tree<B> {
+        let mut root = self[node].clone();
+        root.last_move = None;
+
+        let mut nodes = vec![root];
+
+        // copy nodes breadth-first so the children of each node stay contiguous
+        let mut next = 0;
+        while next < nodes.len() {
+            if let Some(children) = nodes[next].children {
+                let start = NonZeroUsize::new(nodes.len()).unwrap();
+                nodes.extend(children.iter().map(|c| self[c].clone()));
+                nodes[next].children = Some(IdxRange {
+                    start,
+                    length: children.length,
+                });
+            }
+            next += 1;
+        }
+
+        Tree {
+            root_board: board,
+            nodes,
+        }
+    }
+
     pub fn print(&self, depth: u64) {
         println!("move: visits, value <- W,D,L");
         self.print_impl(0, 0, depth);
@@ -246,16 +498,70 @@ impl<B: AltBoard> IndexMut<usize> for Tree<B> {
     }
 }
 
-fn random_playout<B: AltBoard>(mut board: B, rng: &mut impl Rng) -> Outcome {
-    assert!(!board.is_done(), "should never start random playout on a done board");
+/// Create the children of `node`, which corresponds to `board`.
+fn expand_node<B: AltBoard>(tree: &mut Tree<B>, node: usize, board: &mut B) -> IdxRange {
+    let start = NonZeroUsize::new(tree.nodes.len()).unwrap();
 
-    loop {
-        board.play(board.random_available_move(rng));
+    let player = board.next_player();
+    let moves: Vec<B::Move> = board.available_moves().collect();
+    for mv in moves {
+        let outcome = board.play_then_undo(mv, |next_board| next_board.outcome()).pov(player);
+        tree.nodes.push(Node::new(Some(mv), outcome));
+    }
+
+    let length = tree.nodes.len() - start.get();
+    let children = IdxRange { start, length };
+    tree[node].children = Some(children);
+
+    for child in children {
+        tree[child].prior = 1.0 / length as f32;
+    }
+
+    children
+}
+
+/// Replace the uniform priors of freshly expanded `children` with the `policy` of an evaluation, if any.
+fn set_priors<B: AltBoard>(tree: &mut Tree<B>, children: IdxRange, policy: Option<Vec<f32>>) {
+    if let Some(policy) = policy {
+        assert_eq!(
+            children.length,
+            policy.len(),
+            "policy length must match the number of available moves"
+        );
+        for (child, prior) in children.iter().zip(policy) {
+            tree[child].prior = prior;
+        }
+    }
+}
 
-        if let Some(outcome) = board.outcome() {
-            return outcome;
+/// Pick the child of `node` to explore next.
+fn select_child<B: AltBoard>(
+    tree: &Tree<B>,
+    node: usize,
+    children: IdxRange,
+    policy: &impl SelectionPolicy,
+    rng: &mut impl Rng,
+) -> usize {
+    if policy.unvisited_first() {
+        let unvisited = children.iter().filter(|&c| tree[c].is_unvisited());
+        if let Some(picked) = unvisited.choose(rng) {
+            return picked;
         }
     }
+
+    //TODO we're including lost and drawn nodes here, is there nothing better we can do?
+    // at least this is what the paper seems to suggest
+    let parent_visits = tree[node].visits + tree[node].virtual_loss;
+    children
+        .iter()
+        .max_by_key(|&c| N32::from(tree[c].selection_score(parent_visits, policy)))
+        .unwrap()
+}
+
+/// The solution of a node (from the POV of the player that played its last move) that follows from
+/// the solutions of its `children`, if any.
+fn solution_from_children<B: AltBoard>(tree: &Tree<B>, children: IdxRange) -> Option<OutcomeWDL> {
+    OutcomeWDL::best_maybe(children.iter().map(|c| tree[c].solution()).into_internal()).flip()
 }
 
 /// Run a single MCTS step.
@@ -268,151 +574,710 @@ fn random_playout<B: AltBoard>(mut board: B, rng: &mut impl Rng) -> Outcome {
 fn mcts_solver_step<B: AltBoard>(
     tree: &mut Tree<B>,
     curr_node: usize,
-    curr_board: &B,
-    exploration_weight: f32,
+    curr_board: &mut B,
+    policy: &impl SelectionPolicy,
+    evaluator: &impl Evaluator<B>,
     rng: &mut impl Rng,
-) -> (OutcomeWDL, bool) {
+) -> (WDL<f32>, bool) {
     //TODO should we decrement visit count? -> meh, then we're pulling search time towards partially solved branches
     //TODO should we backprop all previous backpropped losses and draws as wins now? -> meh, then we're overestimating this entire branch
 
     if let Some(outcome) = tree[curr_node].solution() {
-        return (outcome, true);
+        return (outcome.to_wdl(), true);
     }
 
-    // initialize children
     let children = match tree[curr_node].children {
         Some(children) => children,
         None => {
-            let start = NonZeroUsize::new(tree.nodes.len()).unwrap();
-
-            curr_board.available_moves().for_each(|mv: B::Move| {
-                let next_board = curr_board.clone_and_play(mv);
-                let outcome = next_board.outcome().pov(curr_board.next_player());
-                let node = Node::new(Some(mv), outcome);
-                tree.nodes.push(node);
-            });
-
-            let length = tree.nodes.len() - start.get();
-            let children = IdxRange { start, length };
-            tree[curr_node].children = Some(children);
+            // this is a leaf node, initialize the children and evaluate it
+            let children = expand_node(tree, curr_node, curr_board);
 
             //TODO maybe do this even earlier, and immediately stop pushing nodes -> but then children are inconsistent :(
             //  so what? who care about children somewhere deep in the tree!
-            let outcome = OutcomeWDL::best_maybe(children.iter().map(|c| tree[c].solution()).into_internal());
-            if let Some(outcome) = outcome.flip() {
+            if let Some(outcome) = solution_from_children(tree, children) {
                 tree[curr_node].mark_solved(outcome);
-                return (outcome, true);
-            } else {
-                children
+                return (outcome.to_wdl(), true);
             }
-        }
-    };
-
-    // check if there are unvisited children
-    let unvisited = children.iter().filter(|&c| tree[c].is_unvisited());
-    let picked_unvisited = unvisited.choose(rng);
-
-    // result is from the POV of curr_board.next_player
-    let (result, proven) = if let Some(picked_child) = picked_unvisited {
-        let picked_mv = tree[picked_child].last_move.unwrap();
-        let next_board = curr_board.clone_and_play(picked_mv);
 
-        let outcome = random_playout(next_board, rng).pov(curr_board.next_player().other());
-        tree[picked_child].increment(outcome);
+            let evaluation = evaluator.evaluate(curr_board, rng);
+            set_priors(tree, children, evaluation.policy);
 
-        (outcome.flip(), false)
-    } else {
-        //pick the max-uct child
-        //TODO we're including lost and drawn nodes here, is there nothing better we can do?
-        // at least this is what the paper seems to suggest
-        let parent_visits = tree[curr_node].visits;
-
-        let picked = children
-            .iter()
-            .max_by_key(|&c| N32::from(tree[c].uct(parent_visits, exploration_weight)))
-            .unwrap();
-
-        //continue recursing
-        let picked_mv = tree[picked].last_move.unwrap();
-        let next_board = curr_board.clone_and_play(picked_mv);
-
-        mcts_solver_step(tree, picked, &next_board, exploration_weight, rng)
+            // the evaluation is from the POV of curr_board.next_player
+            let result = evaluation.wdl.flip();
+            tree[curr_node].increment_wdl(result);
+            return (result, false);
+        }
     };
 
+    //continue recursing
+    let picked = select_child(tree, curr_node, children, policy, rng);
+    let (result, proven) = curr_board.play_then_undo(tree[picked].last_move.unwrap(), |next_board| {
+        mcts_solver_step(tree, picked, next_board, policy, evaluator, rng)
+    });
+
     let result = result.flip();
 
     if proven {
         //check if we can prove the current node as well
-        let outcome = OutcomeWDL::best_maybe(children.iter().map(|c| tree[c].solution()).into_internal());
-        if let Some(outcome) = outcome.flip() {
+        if let Some(outcome) = solution_from_children(tree, children) {
             tree[curr_node].mark_solved(outcome);
-            return (outcome, true);
+            return (outcome.to_wdl(), true);
         }
     }
 
-    tree[curr_node].increment(result);
+    tree[curr_node].increment_wdl(result);
     (result, false)
 }
 
+/// Build a tree using the [Uct] selection policy with the given `exploration_weight`.
 pub fn mcts_build_tree<B: AltBoard>(
     root_board: &B,
     iterations: u64,
     exploration_weight: f32,
     rng: &mut impl Rng,
+) -> Tree<B> {
+    mcts_build_tree_with_policy(root_board, iterations, &Uct::new(exploration_weight), rng)
+}
+
+/// Build a tree using the given selection `policy` and [RandomRollout] to evaluate leaf nodes.
+pub fn mcts_build_tree_with_policy<B: AltBoard>(
+    root_board: &B,
+    iterations: u64,
+    policy: &impl SelectionPolicy,
+    rng: &mut impl Rng,
+) -> Tree<B> {
+    mcts_build_tree_with_evaluator(root_board, iterations, policy, &RandomRollout, rng)
+}
+
+pub fn mcts_build_tree_with_evaluator<B: AltBoard>(
+    root_board: &B,
+    iterations: u64,
+    policy: &impl SelectionPolicy,
+    evaluator: &impl Evaluator<B>,
+    rng: &mut impl Rng,
 ) -> Tree<B> {
     assert!(iterations > 0);
 
+    let mut tree = root_tree(root_board);
+    mcts_extend_tree(&mut tree, iterations, policy, evaluator, rng);
+    tree
+}
+
+/// Create a tree that only contains the root node.
+fn root_tree<B: AltBoard>(root_board: &B) -> Tree<B> {
     let mut tree = Tree::new(root_board.clone());
 
     let root_outcome = root_board.outcome().map(|o| o.pov(root_board.next_player().other()));
     tree.nodes.push(Node::new(None, root_outcome));
 
-    for _ in 0..iterations {
+    tree
+}
+
+/// Run `iterations` additional MCTS steps on an existing tree, for example one returned by [Tree::subtree].
+pub fn mcts_extend_tree<B: AltBoard>(
+    tree: &mut Tree<B>,
+    iterations: u64,
+    policy: &impl SelectionPolicy,
+    evaluator: &impl Evaluator<B>,
+    rng: &mut impl Rng,
+) {
+    mcts_extend_tree_until(tree, policy, evaluator, rng, |_, i| i >= iterations);
+}
+
+/// Run MCTS steps on an existing tree until `stop(tree, iterations_done)` returns `true` or the root is solved.
+fn mcts_extend_tree_until<B: AltBoard>(
+    tree: &mut Tree<B>,
+    policy: &impl SelectionPolicy,
+    evaluator: &impl Evaluator<B>,
+    rng: &mut impl Rng,
+    mut stop: impl FnMut(&Tree<B>, u64) -> bool,
+) {
+    assert!(!tree.nodes.is_empty(), "tree must have a root node");
+    let mut root_board = tree.root_board.clone();
+
+    let mut iterations = 0;
+    while !stop(tree, iterations) {
         //we've solved the root node, so we're done
         if tree[0].solution().is_some() {
             break;
         }
 
-        mcts_solver_step(&mut tree, 0, root_board, exploration_weight, rng);
+        mcts_solver_step(tree, 0, &mut root_board, policy, evaluator, rng);
+        iterations += 1;
     }
+}
 
-    tree
+/// Build a tree with root parallelism: `threads` independent trees are built in parallel
+/// and the statistics of the root and its children are merged into a single tree.
+///
+/// The `iterations` are spread over the threads. The returned tree only contains the root and its children.
+pub fn mcts_build_tree_root_parallel<B: AltBoard>(
+    root_board: &B,
+    iterations: u64,
+    threads: usize,
+    policy: &impl SelectionPolicy,
+    evaluator: &impl Evaluator<B>,
+    rng: &mut impl Rng,
+) -> Tree<B> {
+    root_parallel_until(root_board, iterations, threads, policy, evaluator, rng, || false)
 }
 
-pub struct MCTSBot<R: Rng> {
+/// Implementation of [mcts_build_tree_root_parallel] that also stops early once `stop` returns `true`.
+/// Each thread always runs at least one iteration.
+fn root_parallel_until<B: AltBoard>(
+    root_board: &B,
     iterations: u64,
-    exploration_weight: f32,
+    threads: usize,
+    policy: &impl SelectionPolicy,
+    evaluator: &impl Evaluator<B>,
+    rng: &mut impl Rng,
+    stop: impl Fn() -> bool + Sync,
+) -> Tree<B> {
+    assert!(iterations > 0);
+    assert!(threads > 0);
+
+    let seeds: Vec<u64> = (0..threads).map(|_| rng.gen()).collect();
+    let threads = threads as u64;
+
+    let trees: Vec<Tree<B>> = seeds
+        .into_par_iter()
+        .enumerate()
+        .map(|(i, seed)| {
+            let thread_iterations = iterations / threads + (((i as u64) < iterations % threads) as u64);
+            let mut rng = SmallRng::seed_from_u64(seed);
+
+            let mut tree = root_tree(root_board);
+            let thread_stop = |_: &Tree<B>, i: u64| i >= 1 && (i >= thread_iterations || stop());
+            mcts_extend_tree_until(&mut tree, policy, evaluator, &mut rng, thread_stop);
+            tree
+        })
+        .collect();
+
+    merge_root_trees(trees)
+}
+
+fn merge_root_trees<B: AltBoard>(trees: Vec<Tree<B>>) -> Tree<B> {
+    let mut trees = trees.into_iter();
+    let first = trees.next().unwrap();
+
+    let first_children = match first[0].children {
+        None => return first,
+        Some(children) => children,
+    };
+
+    let mut root = first[0].clone();
+    root.children = Some(IdxRange {
+        start: NonZeroUsize::new(1).unwrap(),
+        length: first_children.length,
+    });
+
+    let mut nodes = vec![root];
+    nodes.extend(first_children.iter().map(|c| Node {
+        children: None,
+        ..first[c].clone()
+    }));
+
+    for tree in trees {
+        assert_eq!(first.root_board, tree.root_board);
+        nodes[0].merge(&tree[0]);
+
+        if let Some(children) = tree[0].children {
+            assert_eq!(first_children.length, children.length);
+            for (i, c) in children.iter().enumerate() {
+                debug_assert_eq!(nodes[1 + i].last_move, tree[c].last_move);
+                nodes[1 + i].merge(&tree[c]);
+            }
+        }
+    }
+
+    Tree {
+        root_board: first.root_board,
+        nodes,
+    }
+}
+
+/// Build a tree with tree parallelism: `threads` threads run MCTS steps on a single shared tree.
+///
+/// The tree is locked during selection and backpropagation but not while evaluating leaf nodes.
+/// Nodes that are being searched by a thread get a virtual loss so other threads prefer different branches.
+pub fn mcts_build_tree_shared<B: AltBoard>(
+    root_board: &B,
+    iterations: u64,
+    threads: usize,
+    policy: &impl SelectionPolicy,
+    evaluator: &impl Evaluator<B>,
+    rng: &mut impl Rng,
+) -> Tree<B> {
+    shared_until(root_board, iterations, threads, policy, evaluator, rng, || false)
+}
+
+/// Implementation of [mcts_build_tree_shared] that also stops early once `stop` returns `true`.
+/// At least one iteration is always run.
+fn shared_until<B: AltBoard>(
+    root_board: &B,
+    iterations: u64,
+    threads: usize,
+    policy: &impl SelectionPolicy,
+    evaluator: &impl Evaluator<B>,
+    rng: &mut impl Rng,
+    stop: impl Fn() -> bool + Sync,
+) -> Tree<B> {
+    assert!(iterations > 0);
+    assert!(threads > 0);
+
+    // the tree and the number of started iterations
+    let state = Mutex::new((root_tree(root_board), 0));
+    let seeds: Vec<u64> = (0..threads).map(|_| rng.gen()).collect();
+
+    seeds.into_par_iter().for_each(|seed| {
+        let mut rng = SmallRng::seed_from_u64(seed);
+
+        loop {
+            let (path, leaf) = {
+                let mut guard = state.lock().unwrap();
+                let (tree, started) = &mut *guard;
+
+                //we've solved the root node, all iterations have been started or we're told to stop, so we're done
+                if *started >= iterations || tree[0].solution().is_some() || (*started >= 1 && stop()) {
+                    break;
+                }
+                *started += 1;
+
+                shared_select(tree, policy, &mut rng)
+            };
+
+            let evaluation = match &leaf {
+                SharedLeaf::Evaluate(board) => Some(evaluator.evaluate(board, &mut rng)),
+                SharedLeaf::Proven(_) => None,
+            };
+
+            let mut guard = state.lock().unwrap();
+            shared_backprop(&mut guard.0, &path, leaf, evaluation);
+        }
+    });
+
+    state.into_inner().unwrap().0
+}
+
+enum SharedLeaf<B> {
+    /// A leaf node with its board, which still needs to be evaluated.
+    Evaluate(B),
+    /// The node is solved, its outcome is from the POV of the player that just played its last move.
+    Proven(OutcomeWDL),
+}
+
+/// The selection phase of a [mcts_build_tree_shared] step.
+/// Returns the path of nodes that got a virtual loss.
+fn shared_select<B: AltBoard>(
+    tree: &mut Tree<B>,
+    policy: &impl SelectionPolicy,
+    rng: &mut impl Rng,
+) -> (Vec<usize>, SharedLeaf<B>) {
+    let mut path = vec![0];
+    tree[0].virtual_loss += 1;
+    let mut curr_board = tree.root_board.clone();
+
+    loop {
+        let curr_node = *path.last().unwrap();
+
+        if let Some(outcome) = tree[curr_node].solution() {
+            return (path, SharedLeaf::Proven(outcome));
+        }
+
+        let children = match tree[curr_node].children {
+            Some(children) => children,
+            None => return (path, SharedLeaf::Evaluate(curr_board)),
+        };
+
+        let picked = select_child(tree, curr_node, children, policy, rng);
+        tree[picked].virtual_loss += 1;
+        path.push(picked);
+        curr_board.play(tree[picked].last_move.unwrap());
+    }
+}
+
+/// The expansion and backpropagation phase of a [mcts_build_tree_shared] step,
+/// also removes the virtual losses added by [shared_select].
+///
+/// Other threads may have expanded or even solved the leaf node while it was being evaluated.
+fn shared_backprop<B: AltBoard>(
+    tree: &mut Tree<B>,
+    path: &[usize],
+    leaf: SharedLeaf<B>,
+    evaluation: Option<Evaluation>,
+) {
+    for &node in path {
+        tree[node].virtual_loss -= 1;
+    }
+
+    let (&leaf_node, parents) = path.split_last().unwrap();
+
+    // result is from the POV of the player that just played the last move of the current node
+    let (mut result, mut proven) = match (leaf, tree[leaf_node].solution()) {
+        (SharedLeaf::Proven(outcome), _) | (_, Some(outcome)) => (outcome.to_wdl(), true),
+        (SharedLeaf::Evaluate(mut board), None) => {
+            // the evaluation is from the POV of board.next_player
+            let evaluation = evaluation.unwrap();
+            let result = evaluation.wdl.flip();
+
+            match tree[leaf_node].children {
+                Some(_) => {
+                    tree[leaf_node].increment_wdl(result);
+                    (result, false)
+                }
+                None => {
+                    let children = expand_node(tree, leaf_node, &mut board);
+
+                    if let Some(outcome) = solution_from_children(tree, children) {
+                        tree[leaf_node].mark_solved(outcome);
+                        (outcome.to_wdl(), true)
+                    } else {
+                        set_priors(tree, children, evaluation.policy);
+                        tree[leaf_node].increment_wdl(result);
+                        (result, false)
+                    }
+                }
+            }
+        }
+    };
+
+    for &node in parents.iter().rev() {
+        result = result.flip();
+
+        if proven {
+            //check if we can prove the current node as well
+            let children = tree[node].children.unwrap();
+            if let Some(outcome) = solution_from_children(tree, children) {
+                if tree[node].solution().is_none() {
+                    tree[node].mark_solved(outcome);
+                }
+                result = outcome.to_wdl();
+                continue;
+            }
+            proven = false;
+        }
+
+        tree[node].increment_shared(result);
+    }
+}
+
+/// The interval at which [MCTSBot] reports [SearchInfo] during a search.
+pub const INFO_INTERVAL: Duration = Duration::from_secs(1);
+
+/// MCTS bot that keeps its tree between moves.
+///
+/// On each call the previous tree is re-rooted at the node reached by the moves played since:
+/// the move the bot selected itself followed by at most one opponent move.
+/// Only when the board can't be reached that way a new tree is built.
+pub struct MCTSBot<R: Rng, P: SelectionPolicy = Uct, E = RandomRollout> {
+    iterations: u64,
+    policy: P,
+    evaluator: E,
     rng: R,
+    /// The [KeptTree] of the last search, type-erased because the bot is not tied to a single board type.
+    kept: Option<Box<dyn Any + Send>>,
+    /// The [InfoCallback], type-erased for the same reason.
+    info_callback: Option<Box<dyn Any + Send>>,
 }
 
-impl<R: Rng> Debug for MCTSBot<R> {
+/// The tree kept by [MCTSBot] between searches.
+struct KeptTree<B: AltBoard> {
+    tree: Tree<B>,
+    /// The move selected on the root of `tree`, if any.
+    selected_move: Option<B::Move>,
+}
+
+impl<R: Rng, P: SelectionPolicy, E: Debug> Debug for MCTSBot<R, P, E> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "MCTSBot {{ iterations: {}, exploration_weight: {} }}",
-            self.iterations, self.exploration_weight
+            "MCTSBot {{ iterations: {}, policy: {:?}, evaluator: {:?} }}",
+            self.iterations, self.policy, self.evaluator
         )
     }
 }
 
-impl<R: Rng> MCTSBot<R> {
+impl<R: Rng> MCTSBot<R, Uct, RandomRollout> {
     pub fn new(iterations: u64, exploration_weight: f32, rng: R) -> Self {
+        Self::new_with_policy(iterations, Uct::new(exploration_weight), rng)
+    }
+}
+
+impl<R: Rng, P: SelectionPolicy> MCTSBot<R, P, RandomRollout> {
+    pub fn new_with_policy(iterations: u64, policy: P, rng: R) -> Self {
+        Self::new_with_evaluator(iterations, policy, RandomRollout, rng)
+    }
+}
+
+impl<R: Rng, P: SelectionPolicy, E> MCTSBot<R, P, E> {
+    pub fn new_with_evaluator(iterations: u64, policy: P, evaluator: E, rng: R) -> Self {
         assert!(iterations > 0);
         MCTSBot {
             iterations,
-            exploration_weight,
+            policy,
+            evaluator,
             rng,
+            kept: None,
+            info_callback: None,
+        }
+    }
+
+    /// Report [SearchInfo] to `callback` every [INFO_INTERVAL] during each search and once at the end of it.
+    /// The callback is only called for searches on boards with moves of type `M`.
+    pub fn with_info_callback<M: 'static>(mut self, callback: impl FnMut(&SearchInfo<M>) + Send + 'static) -> Self {
+        let callback: InfoCallback<M> = Box::new(callback);
+        self.info_callback = Some(Box::new(callback));
+        self
+    }
+
+    /// Build a new tree for `board`.
+    pub fn build_tree<B: AltBoard>(&mut self, board: &B) -> Tree<B>
+    where
+        E: Evaluator<B>,
+    {
+        self.build_tree_limited(board, &SearchLimits::default())
+    }
+
+    /// Like [Self::build_tree], but runs iterations until one of `limits` is reached.
+    /// If `limits` does not limit the time or the number of nodes, `iterations` is used as the node limit.
+    /// At least one iteration is run unless the root is already solved.
+    pub fn build_tree_limited<B: AltBoard>(&mut self, board: &B, limits: &SearchLimits) -> Tree<B>
+    where
+        E: Evaluator<B>,
+    {
+        let mut tree = root_tree(board);
+        self.search(&mut tree, limits);
+        tree
+    }
+
+    /// Like [Self::build_tree], but starts from the tree kept from the previous search if `board` can be reached
+    /// from it, and keeps the resulting tree for the next search.
+    pub fn extend_tree<B: AltBoard>(&mut self, board: &B) -> &Tree<B>
+    where
+        E: Evaluator<B>,
+    {
+        self.extend_tree_limited(board, &SearchLimits::default())
+    }
+
+    /// Like [Self::extend_tree], but runs iterations until one of `limits` is reached, see [Self::build_tree_limited].
+    /// No iterations are run if the root of the reused tree is already solved.
+    pub fn extend_tree_limited<B: AltBoard>(&mut self, board: &B, limits: &SearchLimits) -> &Tree<B>
+    where
+        E: Evaluator<B>,
+    {
+        let mut tree = self
+            .kept
+            .take()
+            .and_then(|kept| kept.downcast::<KeptTree<B>>().ok())
+            .and_then(|kept| {
+                let moves = moves_since(&kept.tree.root_board, kept.selected_move, board)?;
+                let node = kept.tree.find_moves(&moves)?;
+                Some(kept.tree.subtree(node, board.clone()))
+            })
+            .unwrap_or_else(|| root_tree(board));
+
+        self.search(&mut tree, limits);
+
+        let kept = KeptTree {
+            tree,
+            selected_move: None,
+        };
+        self.kept = Some(Box::new(kept));
+        self.tree().unwrap()
+    }
+
+    /// The tree kept from the last call to [Self::extend_tree] or [Bot::select_move],
+    /// if that search was for a board of type `B`.
+    pub fn tree<B: AltBoard>(&self) -> Option<&Tree<B>> {
+        let kept = self.kept.as_ref()?.downcast_ref::<KeptTree<B>>()?;
+        Some(&kept.tree)
+    }
+
+    /// Throw away the kept tree, the next search will start from scratch.
+    pub fn clear_tree(&mut self) {
+        self.kept = None;
+    }
+
+    /// Run iterations on `tree` until one of `limits` is reached, reporting to the info callback if any.
+    fn search<B: AltBoard>(&mut self, tree: &mut Tree<B>, limits: &SearchLimits)
+    where
+        E: Evaluator<B>,
+    {
+        let max_iterations = match (limits.nodes, limits.time_budget()) {
+            (Some(nodes), _) => nodes,
+            (None, Some(_)) => u64::MAX,
+            (None, None) => self.iterations,
+        };
+        let timer = limits.start();
+        let mut info_callback = self
+            .info_callback
+            .as_mut()
+            .and_then(|callback| callback.downcast_mut::<InfoCallback<B::Move>>());
+        let mut last_info = Instant::now();
+        let mut iterations = 0;
+
+        let stop = |tree: &Tree<B>, i: u64| {
+            iterations = i;
+            if let Some(callback) = &mut info_callback {
+                if last_info.elapsed() >= INFO_INTERVAL {
+                    callback(&tree.search_info(i, timer.elapsed()));
+                    last_info = Instant::now();
+                }
+            }
+            i >= 1 && (i >= max_iterations || timer.should_stop(i))
+        };
+
+        mcts_extend_tree_until(tree, &self.policy, &self.evaluator, &mut self.rng, stop);
+
+        if let Some(callback) = info_callback {
+            callback(&tree.search_info(iterations, timer.elapsed()));
         }
     }
+}
 
-    pub fn build_tree<B: AltBoard>(&mut self, board: &B) -> Tree<B> {
-        mcts_build_tree(board, self.iterations, self.exploration_weight, &mut self.rng)
+/// The moves leading from `prev` to `board`: `selected_move` (if any) followed by at most one other move.
+fn moves_since<B: AltBoard>(prev: &B, selected_move: Option<B::Move>, board: &B) -> Option<Vec<B::Move>> {
+    if prev == board {
+        return Some(vec![]);
     }
+
+    let mut curr = prev.clone();
+    let mut moves = vec![];
+    if let Some(mv) = selected_move {
+        curr.play(mv);
+        moves.push(mv);
+        if &curr == board {
+            return Some(moves);
+        }
+    }
+
+    if curr.is_done() {
+        return None;
+    }
+    let mv = curr.available_moves().find(|&mv| &curr.clone_and_play(mv) == board)?;
+    moves.push(mv);
+    Some(moves)
 }
 
-impl<R: Rng, B: AltBoard> Bot<B> for MCTSBot<R> {
+impl<R: Rng, P: SelectionPolicy, E: Evaluator<B>, B: AltBoard> Bot<B> for MCTSBot<R, P, E> {
+    fn select_move(&mut self, board: &B) -> B::Move {
+        self.select_move_limited(board, &SearchLimits::default())
+    }
+
+    fn select_move_limited(&mut self, board: &B, limits: &SearchLimits) -> B::Move {
+        assert!(!board.is_done());
+        let mv = self.extend_tree_limited(board, limits).best_move();
+        let kept = self
+            .kept
+            .as_mut()
+            .and_then(|kept| kept.downcast_mut::<KeptTree<B>>())
+            .unwrap();
+        kept.selected_move = Some(mv);
+        mv
+    }
+}
+
+/// The way [ParallelMCTSBot] spreads its search over multiple threads.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Parallelism {
+    /// Each thread builds its own tree, see [mcts_build_tree_root_parallel].
+    Root,
+    /// All threads share a single tree using virtual loss, see [mcts_build_tree_shared].
+    SharedTree,
+}
+
+pub struct ParallelMCTSBot<R: Rng, P: SelectionPolicy = Uct, E = RandomRollout> {
+    iterations: u64,
+    threads: usize,
+    parallelism: Parallelism,
+    policy: P,
+    evaluator: E,
+    rng: R,
+}
+
+impl<R: Rng, P: SelectionPolicy, E: Debug> Debug for ParallelMCTSBot<R, P, E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "ParallelMCTSBot {{ iterations: {}, threads: {}, parallelism: {:?}, policy: {:?}, evaluator: {:?} }}",
+            self.iterations, self.threads, self.parallelism, self.policy, self.evaluator
+        )
+    }
+}
+
+impl<R: Rng> ParallelMCTSBot<R, Uct, RandomRollout> {
+    pub fn new(iterations: u64, exploration_weight: f32, threads: usize, parallelism: Parallelism, rng: R) -> Self {
+        Self::new_with_policy(iterations, threads, parallelism, Uct::new(exploration_weight), rng)
+    }
+}
+
+impl<R: Rng, P: SelectionPolicy> ParallelMCTSBot<R, P, RandomRollout> {
+    pub fn new_with_policy(iterations: u64, threads: usize, parallelism: Parallelism, policy: P, rng: R) -> Self {
+        Self::new_with_evaluator(iterations, threads, parallelism, policy, RandomRollout, rng)
+    }
+}
+
+impl<R: Rng, P: SelectionPolicy, E> ParallelMCTSBot<R, P, E> {
+    pub fn new_with_evaluator(
+        iterations: u64,
+        threads: usize,
+        parallelism: Parallelism,
+        policy: P,
+        evaluator: E,
+        rng: R,
+    ) -> Self {
+        assert!(iterations > 0);
+        assert!(threads > 0);
+        ParallelMCTSBot {
+            iterations,
+            threads,
+            parallelism,
+            policy,
+            evaluator,
+            rng,
+        }
+    }
+
+    pub fn build_tree<B: AltBoard>(&mut self, board: &B) -> Tree<B>
+    where
+        E: Evaluator<B>,
+    {
+        self.build_tree_limited(board, &SearchLimits::default())
+    }
+
+    /// Like [Self::build_tree], but runs iterations until one of `limits` is reached.
+    /// If `limits` does not limit the time or the number of nodes, `iterations` is used as the node limit.
+    /// At least one iteration is always run.
+    pub fn build_tree_limited<B: AltBoard>(&mut self, board: &B, limits: &SearchLimits) -> Tree<B>
+    where
+        E: Evaluator<B>,
+    {
+        let iterations = match (limits.nodes, limits.time_budget()) {
+            (Some(nodes), _) => nodes,
+            (None, Some(_)) => u64::MAX,
+            (None, None) => self.iterations,
+        };
+        let threads = self.threads;
+
+        let timer = limits.start();
+        let stop = || timer.deadline().is_some_and(|deadline| Instant::now() >= deadline) || limits.is_stopped();
+
+        let (policy, evaluator, rng) = (&self.policy, &self.evaluator, &mut self.rng);
+        match self.parallelism {
+            Parallelism::Root => root_parallel_until(board, iterations, threads, policy, evaluator, rng, stop),
+            Parallelism::SharedTree => shared_until(board, iterations, threads, policy, evaluator, rng, stop),
+        }
+    }
+}
+
+impl<R: Rng, P: SelectionPolicy, E: Evaluator<B>, B: AltBoard> Bot<B> for ParallelMCTSBot<R, P, E> {
     fn select_move(&mut self, board: &B) -> B::Move {
         assert!(!board.is_done());
         self.build_tree(board).best_move()
     }
+
+    fn select_move_limited(&mut self, board: &B, limits: &SearchLimits) -> B::Move {
+        assert!(!board.is_done());
+        self.build_tree_limited(board, limits).best_move()
+    }
 }
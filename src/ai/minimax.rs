@@ -1,11 +1,20 @@
-use std::cmp::Ordering;
+use std::cmp::{Ordering, Reverse};
+use std::collections::HashMap;
 use std::fmt::{Debug, Formatter};
+use std::hash::Hash;
 use std::marker::PhantomData;
 use std::ops::{ControlFlow, Neg};
+use std::sync::atomic::{self, AtomicBool};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
+use cast_trait::Cast;
 use internal_iterator::InternalIterator;
 use rand::Rng;
 
+use crate::ai::info::{Score, SearchInfo};
+use crate::ai::limits::SearchLimits;
+use crate::ai::transposition::{Bound, TTEntry, TranspositionTable};
 use crate::ai::Bot;
 use crate::board::Board;
 
@@ -33,6 +42,27 @@ pub trait Heuristic<B: Board>: Debug {
         self.value(child, board_length + 1)
     }
 
+    /// Whether [Self::value_update] is the same as calling [Self::value] on the child, as it is by default.
+    /// If `true`, searches call [Self::value] on the child directly, which allows them to visit children with
+    /// [Board::play_then_undo] instead of cloning the board. Leave this `false` when overriding [Self::value_update].
+    const VALUE_FROM_CHILD: bool = false;
+
+    /// Return a score used to order the moves of `board` before they are searched, higher scores are searched first.
+    /// Better move ordering leads to more alpha-beta cutoffs, it does not change the value found by the search.
+    ///
+    /// Moves with the same score are further ordered by the killer move and history heuristics.
+    /// The default implementation returns the same score for every move.
+    /// Only used if [Heuristic::ORDER_MOVES] is `true`.
+    #[allow(unused_variables)]
+    fn move_order_score(&self, board: &B, mv: B::Move) -> i32 {
+        0
+    }
+
+    /// Whether the search should order moves using [Heuristic::move_order_score] and the killer move and history
+    /// heuristics. Ordering has a cost at every node, so it is disabled by default.
+    /// Even without ordering the best move of a previous iteration or of the transposition table is searched first.
+    const ORDER_MOVES: bool = false;
+
     /// Merge old and new into a new value, and compare their values.
     /// For standard minimax searches this can simply be implemented as: `(max(old, new), new.cmp(old))`
     fn merge(old: Self::V, new: Self::V) -> (Self::V, Ordering);
@@ -47,6 +77,17 @@ pub struct MinimaxResult<V, R> {
     pub best_move: Option<R>,
 }
 
+/// The result of [minimax_pv].
+#[derive(Debug, Clone)]
+pub struct MinimaxPvResult<V, M> {
+    /// The value of this board.
+    pub value: V,
+
+    /// The principal variation, the sequence of moves both players are expected to play, starting with the best move.
+    /// Empty if the board is done or the depth was zero.
+    pub pv: Vec<M>,
+}
+
 // TODO extend minimax to non-alternating games
 
 /// Evaluate the board using minimax with the given heuristic up to the given depth.
@@ -58,16 +99,7 @@ pub fn minimax<B: Board, H: Heuristic<B>>(
     depth: u32,
     rng: &mut impl Rng,
 ) -> MinimaxResult<H::V, B::Move> {
-    let result = negamax_recurse(
-        heuristic,
-        board,
-        heuristic.value(board, 0),
-        0,
-        depth,
-        None,
-        None,
-        RandomMoveSelector::new(rng),
-    );
+    let result = Negamax::new(heuristic, None).root(board, depth, RandomMoveSelector::new(rng));
 
     if result.best_move.is_none() {
         assert!(board.is_done() || depth == 0, "Implementation error in negamax");
@@ -82,16 +114,7 @@ pub fn minimax_all_moves<B: Board, H: Heuristic<B>>(
     heuristic: &H,
     depth: u32,
 ) -> MinimaxResult<H::V, Vec<B::Move>> {
-    let result = negamax_recurse(
-        heuristic,
-        board,
-        heuristic.value(board, 0),
-        0,
-        depth,
-        None,
-        None,
-        AllMoveSelector::new(),
-    );
+    let result = Negamax::new(heuristic, None).root(board, depth, AllMoveSelector::new());
 
     if result.best_move.is_none() {
         assert!(board.is_done() || depth == 0, "Implementation error in negamax");
@@ -100,26 +123,264 @@ pub fn minimax_all_moves<B: Board, H: Heuristic<B>>(
     result
 }
 
+/// Variant of [minimax] that returns the principal variation instead of only the best move.
+/// If multiple moves have the same value the first one searched is picked, so the result is deterministic.
+///
+/// The principal variation ends early at boards where the search was cut off, so it can be shorter than `depth`
+/// even if the game does not end.
+pub fn minimax_pv<B: Board, H: Heuristic<B>>(board: &B, heuristic: &H, depth: u32) -> MinimaxPvResult<H::V, B::Move> {
+    let mut search = Negamax::new(heuristic, None);
+    search.track_pv = true;
+
+    let value = search.root(board, depth, NoMoveSelector).value;
+    let pv = search.take_pv();
+
+    if pv.is_empty() {
+        assert!(board.is_done() || depth == 0, "Implementation error in negamax");
+    }
+
+    MinimaxPvResult { value, pv }
+}
+
 /// Variant of [minimax] that only returns the value and not the best move.
 /// The advantage is that no rng is necessary to break ties between best moves.
 pub fn minimax_value<B: Board, H: Heuristic<B>>(board: &B, heuristic: &H, depth: u32) -> H::V {
-    negamax_recurse(
-        heuristic,
-        board,
-        heuristic.value(board, 0),
-        0,
-        depth,
-        None,
-        None,
-        NoMoveSelector,
-    )
-    .value
-}
-
-/// The selection procedure for selecting the best move to be returned by [negamax_recurse].
+    Negamax::new(heuristic, None).root(board, depth, NoMoveSelector).value
+}
+
+/// Variant of [minimax] that uses and fills the transposition table `tt`.
+///
+/// The table can be reused between searches, even if they start from different boards,
+/// but it must only ever be used with the same heuristic.
+/// Entries found by deeper searches are used as-is, so the result can be more accurate than [minimax] would be.
+pub fn minimax_with_tt<B: Board, H: Heuristic<B>>(
+    board: &B,
+    heuristic: &H,
+    depth: u32,
+    tt: &mut TranspositionTable<B, H::V>,
+    rng: &mut impl Rng,
+) -> MinimaxResult<H::V, B::Move> {
+    let result = Negamax::new(heuristic, Some(tt)).root(board, depth, RandomMoveSelector::new(rng));
+
+    if result.best_move.is_none() {
+        assert!(board.is_done() || depth == 0, "Implementation error in negamax");
+    }
+
+    result
+}
+
+/// Variant of [minimax_all_moves] that uses and fills the transposition table `tt`, see [minimax_with_tt].
+pub fn minimax_all_moves_with_tt<B: Board, H: Heuristic<B>>(
+    board: &B,
+    heuristic: &H,
+    depth: u32,
+    tt: &mut TranspositionTable<B, H::V>,
+) -> MinimaxResult<H::V, Vec<B::Move>> {
+    let result = Negamax::new(heuristic, Some(tt)).root(board, depth, AllMoveSelector::new());
+
+    if result.best_move.is_none() {
+        assert!(board.is_done() || depth == 0, "Implementation error in negamax");
+    }
+
+    result
+}
+
+/// Variant of [minimax_value] that uses and fills the transposition table `tt`, see [minimax_with_tt].
+pub fn minimax_value_with_tt<B: Board, H: Heuristic<B>>(
+    board: &B,
+    heuristic: &H,
+    depth: u32,
+    tt: &mut TranspositionTable<B, H::V>,
+) -> H::V {
+    Negamax::new(heuristic, Some(tt))
+        .root(board, depth, NoMoveSelector)
+        .value
+}
+
+/// The limits for [minimax_iterative]. The search stops as soon as any of them is reached.
+#[derive(Debug, Clone)]
+pub struct MinimaxBudget {
+    /// The maximum depth to search.
+    pub max_depth: u32,
+    /// The maximum wall-clock time to spend.
+    pub time: Option<Duration>,
+    /// The maximum number of nodes to visit.
+    pub nodes: Option<u64>,
+    /// A flag that stops the search when set from another thread.
+    pub stop: Option<Arc<AtomicBool>>,
+}
+
+impl MinimaxBudget {
+    pub fn depth(max_depth: u32) -> Self {
+        MinimaxBudget {
+            max_depth,
+            time: None,
+            nodes: None,
+            stop: None,
+        }
+    }
+
+    pub fn time(time: Duration) -> Self {
+        MinimaxBudget {
+            max_depth: u32::MAX,
+            time: Some(time),
+            nodes: None,
+            stop: None,
+        }
+    }
+
+    pub fn nodes(nodes: u64) -> Self {
+        MinimaxBudget {
+            max_depth: u32::MAX,
+            time: None,
+            nodes: Some(nodes),
+            stop: None,
+        }
+    }
+
+    /// Convert search limits into a budget. `default_depth` is used as the maximum depth if `limits`
+    /// does not limit the depth, time or nodes.
+    pub fn from_limits(limits: &SearchLimits, default_depth: u32) -> Self {
+        let time = limits.time_budget();
+        let max_depth = match limits.depth {
+            Some(depth) => depth,
+            None if time.is_some() || limits.nodes.is_some() => u32::MAX,
+            None => default_depth,
+        };
+
+        MinimaxBudget {
+            max_depth,
+            time,
+            nodes: limits.nodes,
+            stop: limits.stop.clone(),
+        }
+    }
+
+    fn is_stopped(&self) -> bool {
+        self.stop
+            .as_ref()
+            .is_some_and(|stop| stop.load(atomic::Ordering::Relaxed))
+    }
+}
+
+/// The result of a single completed iteration of [minimax_iterative].
+#[derive(Debug, Clone)]
+pub struct MinimaxDepthResult<V, M> {
+    pub depth: u32,
+    pub value: V,
+    pub best_move: M,
+    /// The principal variation found by this iteration, starting with `best_move`.
+    /// This can be shorter than `depth` if the search was cut off by the transposition table.
+    pub pv: Vec<M>,
+
+    /// The number of nodes visited during this iteration.
+    pub nodes: u64,
+    /// The number of nodes visited since the start of the search, including this iteration.
+    pub total_nodes: u64,
+    /// The time since the start of the search at the end of this iteration.
+    pub elapsed: Duration,
+}
+
+/// Iterative deepening variant of [minimax], searching at depth `1, 2, 3, ...` until `budget` runs out.
+///
+/// Returns the results of all completed iterations, the last one is the result of the deepest search.
+/// The first iteration is always completed, even if that takes longer than the budget allows.
+/// Each iteration searches the best move of the previous iteration first, and `tt` (if any) is shared between them.
+/// If an iteration reached the end of the game everywhere deeper iterations are skipped.
+pub fn minimax_iterative<B: Board, H: Heuristic<B>>(
+    board: &B,
+    heuristic: &H,
+    budget: MinimaxBudget,
+    tt: Option<&mut TranspositionTable<B, H::V>>,
+    rng: &mut impl Rng,
+) -> Vec<MinimaxDepthResult<H::V, B::Move>> {
+    minimax_iterative_with_callback(board, heuristic, budget, tt, rng, |_| {})
+}
+
+/// Same as [minimax_iterative], but `callback` is called with the result of each iteration as soon as it completes.
+pub fn minimax_iterative_with_callback<B: Board, H: Heuristic<B>>(
+    board: &B,
+    heuristic: &H,
+    budget: MinimaxBudget,
+    mut tt: Option<&mut TranspositionTable<B, H::V>>,
+    rng: &mut impl Rng,
+    mut callback: impl FnMut(&MinimaxDepthResult<H::V, B::Move>),
+) -> Vec<MinimaxDepthResult<H::V, B::Move>> {
+    assert!(!board.is_done(), "Cannot search done board {:?}", board);
+    assert!(budget.max_depth > 0, "requires max_depth>0 to find the best move");
+    assert!(
+        budget.max_depth != u32::MAX || budget.time.is_some() || budget.nodes.is_some(),
+        "The budget must be limited"
+    );
+
+    let start = Instant::now();
+    let deadline = budget.time.map(|time| start + time);
+    let mut total_nodes = 0;
+    let mut results: Vec<MinimaxDepthResult<H::V, B::Move>> = vec![];
+    let mut ordering = MoveOrdering::default();
+
+    for depth in 1..=budget.max_depth {
+        let mut search = Negamax::new(heuristic, tt.as_deref_mut());
+        search.root_move = results.last().map(|r| r.best_move);
+        search.ordering = ordering;
+        search.track_pv = true;
+
+        // only start limiting after the first iteration, so we always have a move
+        if depth > 1 {
+            search.deadline = deadline;
+            search.max_nodes = budget.nodes.map(|nodes| nodes.saturating_sub(total_nodes));
+            search.stop = budget.stop.as_deref();
+        }
+
+        let result = search.root(board, depth, RandomMoveSelector::new(&mut *rng));
+        total_nodes += search.nodes;
+        ordering = std::mem::take(&mut search.ordering);
+
+        if search.aborted {
+            break;
+        }
+
+        // ties at the root are broken randomly, in which case the principal variation may start with another move
+        let best_move = result.best_move.unwrap();
+        let mut pv = search.take_pv();
+        if pv.first() != Some(&best_move) {
+            pv = vec![best_move];
+        }
+
+        results.push(MinimaxDepthResult {
+            depth,
+            value: result.value,
+            best_move,
+            pv,
+            nodes: search.nodes,
+            total_nodes,
+            elapsed: start.elapsed(),
+        });
+        callback(results.last().unwrap());
+
+        let out_of_time = deadline.is_some_and(|deadline| Instant::now() >= deadline);
+        let out_of_nodes = budget.nodes.is_some_and(|nodes| total_nodes >= nodes);
+        if !search.depth_limited || out_of_time || out_of_nodes || budget.is_stopped() {
+            break;
+        }
+    }
+
+    results
+}
+
+/// The selection procedure for selecting the best move to be returned by [Negamax].
 trait MoveSelector<M> {
     type Result;
 
+    /// Whether moves that tie with the best move are accepted. A child searched with the alpha bound that looks
+    /// like a tie may only have been proven to be at most as good as the best move, so it is searched again
+    /// without the bound to check.
+    const TIES: bool = true;
+
+    /// Whether every move that ties with the best move is needed. If so all children are searched without the
+    /// alpha bound up front, since most of them would have to be searched again anyway.
+    const ALL_TIES: bool = false;
+
     fn reset(&mut self);
 
     fn accept(&mut self, mv: M);
@@ -134,6 +395,8 @@ struct NoMoveSelector;
 impl<M> MoveSelector<M> for NoMoveSelector {
     type Result = ();
 
+    const TIES: bool = false;
+
     fn reset(&mut self) {}
 
     fn accept(&mut self, _: M) {}
@@ -196,6 +459,8 @@ impl<M> AllMoveSelector<M> {
 impl<M> MoveSelector<M> for AllMoveSelector<M> {
     type Result = Vec<M>;
 
+    const ALL_TIES: bool = true;
+
     fn reset(&mut self) {
         self.moves.clear();
     }
@@ -209,93 +474,391 @@ impl<M> MoveSelector<M> for AllMoveSelector<M> {
     }
 }
 
-/// The core minimax implementation.
-/// Alpha-Beta Negamax, implementation based on
-/// <https://en.wikipedia.org/wiki/Negamax#Negamax_with_alpha_beta_pruning>
-fn negamax_recurse<B: Board, H: Heuristic<B>, S: MoveSelector<B::Move>>(
-    heuristic: &H,
-    board: &B,
-    board_heuristic: H::V,
-    length: u32,
-    depth_left: u32,
-    alpha: Option<H::V>,
-    beta: Option<H::V>,
-    mut move_selector: S,
-) -> MinimaxResult<H::V, S::Result> {
-    if depth_left == 0 || board.is_done() {
-        return MinimaxResult {
-            value: board_heuristic,
-            best_move: None,
-        };
+/// Return whether `a >= b` according to [Heuristic::merge].
+fn value_ge<B: Board, H: Heuristic<B>>(a: H::V, b: H::V) -> bool {
+    H::merge(b, a).1.is_ge()
+}
+
+/// The killer move and history heuristics, used to order moves independently of the board type.
+#[derive(Debug)]
+struct MoveOrdering<M> {
+    /// For each length the two most recent moves that caused a beta cutoff.
+    killers: Vec<[Option<M>; 2]>,
+    /// For each move the sum of `depth_left^2` over all beta cutoffs it caused.
+    history: HashMap<M, u64>,
+}
+
+impl<M> Default for MoveOrdering<M> {
+    fn default() -> Self {
+        MoveOrdering {
+            killers: vec![],
+            history: HashMap::default(),
+        }
+    }
+}
+
+impl<M: Copy + Eq + Hash> MoveOrdering<M> {
+    fn is_killer(&self, length: u32, mv: M) -> bool {
+        self.killers
+            .get(length as usize)
+            .is_some_and(|killers| killers.contains(&Some(mv)))
     }
 
-    let mut best_value = None;
-    let mut alpha = alpha;
+    fn history(&self, mv: M) -> u64 {
+        self.history.get(&mv).copied().unwrap_or(0)
+    }
 
-    let early = board.available_moves().try_for_each(|mv: B::Move| {
-        let child = board.clone_and_play(mv);
-        let child_heuristic = heuristic.value_update(board, board_heuristic, length, mv, &child);
+    fn record_cutoff(&mut self, length: u32, depth_left: u32, mv: M) {
+        let length = length as usize;
+        if self.killers.len() <= length {
+            self.killers.resize(length + 1, [None; 2]);
+        }
 
-        let maybe_neg = |v: H::V| {
-            if child.next_player() != board.next_player() {
-                -v
-            } else {
-                v
-            }
-        };
+        let killers = &mut self.killers[length];
+        if killers[0] != Some(mv) {
+            killers[1] = killers[0];
+            killers[0] = Some(mv);
+        }
+
+        *self.history.entry(mv).or_default() += depth_left as u64 * depth_left as u64;
+    }
+}
+
+/// The state of a single negamax search.
+struct Negamax<'a, B: Board, H: Heuristic<B>> {
+    heuristic: &'a H,
+    tt: Option<&'a mut TranspositionTable<B, H::V>>,
+    /// The move to search first at the root, typically the best move of the previous iteration.
+    root_move: Option<B::Move>,
+    ordering: MoveOrdering<B::Move>,
+
+    deadline: Option<Instant>,
+    max_nodes: Option<u64>,
+    stop: Option<&'a AtomicBool>,
+
+    /// Whether to keep track of the principal variation, which has some overhead.
+    track_pv: bool,
+    /// The principal variation for each length, of the board currently being searched at that length.
+    pv: Vec<Vec<B::Move>>,
+
+    nodes: u64,
+    /// Whether the search was stopped because `deadline` or `max_nodes` was reached or `stop` was set.
+    /// The result of the search is meaningless in that case.
+    aborted: bool,
+    /// Whether the search was stopped anywhere by the depth limit instead of the end of the game.
+    depth_limited: bool,
+}
+
+impl<'a, B: Board, H: Heuristic<B>> Negamax<'a, B, H> {
+    fn new(heuristic: &'a H, tt: Option<&'a mut TranspositionTable<B, H::V>>) -> Self {
+        Negamax {
+            heuristic,
+            tt,
+            root_move: None,
+            ordering: MoveOrdering::default(),
+            deadline: None,
+            max_nodes: None,
+            stop: None,
+            track_pv: false,
+            pv: vec![],
+            nodes: 0,
+            aborted: false,
+            depth_limited: false,
+        }
+    }
+
+    fn root<S: MoveSelector<B::Move>>(
+        &mut self,
+        board: &B,
+        depth: u32,
+        move_selector: S,
+    ) -> MinimaxResult<H::V, S::Result> {
+        let heuristic = self.heuristic;
+        let mut board = board.clone();
+        let board_heuristic = heuristic.value(&board, 0);
+        self.recurse(&mut board, board_heuristic, 0, depth, None, None, move_selector)
+    }
+
+    /// Take the principal variation of the last search, starting at the root.
+    fn take_pv(&mut self) -> Vec<B::Move> {
+        self.pv.first_mut().map(std::mem::take).unwrap_or_default()
+    }
 
-        let child_value = maybe_neg(
-            negamax_recurse(
-                heuristic,
-                &child,
+    /// Start a new node at `length`, clearing its principal variation.
+    fn clear_pv(&mut self, length: u32) {
+        let length = length as usize;
+        if self.pv.len() <= length {
+            self.pv.resize_with(length + 1, Vec::new);
+        }
+        self.pv[length].clear();
+    }
+
+    /// The move `mv` is the new best move at `length`, the principal variation of its child was just computed.
+    fn update_pv(&mut self, length: u32, mv: B::Move) {
+        let length = length as usize;
+        let (curr, rest) = self.pv.split_at_mut(length + 1);
+        let curr = &mut curr[length];
+        curr.clear();
+        curr.push(mv);
+        if let Some(child) = rest.first() {
+            curr.extend_from_slice(child);
+        }
+    }
+
+    /// Play `mv` on `board` and search the resulting child.
+    /// The returned value, `alpha` and `beta` are from the POV of `board.next_player()`.
+    #[allow(clippy::too_many_arguments)]
+    fn recurse_child(
+        &mut self,
+        board: &mut B,
+        board_heuristic: H::V,
+        mv: B::Move,
+        length: u32,
+        depth_left: u32,
+        alpha: Option<H::V>,
+        beta: Option<H::V>,
+    ) -> H::V {
+        let player = board.next_player();
+        let search = |this: &mut Self, child: &mut B, child_heuristic: H::V| {
+            let flip = child.next_player() != player;
+            let maybe_neg = |v: H::V| if flip { -v } else { v };
+
+            let result = this.recurse(
+                child,
                 child_heuristic,
                 length + 1,
                 depth_left - 1,
                 beta.map(maybe_neg),
                 alpha.map(maybe_neg),
                 NoMoveSelector,
-            )
-            .value,
-        );
+            );
+            maybe_neg(result.value)
+        };
 
-        let (new_best_value, ordering) = best_value.map_or((child_value, Ordering::Greater), |best_value| {
-            H::merge(best_value, child_value)
-        });
-        let new_alpha = alpha.map_or(new_best_value, |alpha| H::merge(alpha, new_best_value).0);
+        let heuristic = self.heuristic;
+        if H::VALUE_FROM_CHILD {
+            board.play_then_undo(mv, |child| {
+                let child_heuristic = heuristic.value(child, length + 1);
+                search(self, child, child_heuristic)
+            })
+        } else {
+            // the heuristic needs both the parent and the child
+            let mut child = board.clone_and_play(mv);
+            let child_heuristic = heuristic.value_update(board, board_heuristic, length, mv, &child);
+            search(self, &mut child, child_heuristic)
+        }
+    }
 
-        best_value = Some(new_best_value);
+    fn check_abort(&mut self) -> bool {
+        if !self.aborted {
+            let out_of_nodes = self.max_nodes.is_some_and(|max_nodes| self.nodes >= max_nodes);
+            // only check the time and the stop flag every once in a while, getting them is relatively slow
+            let check = self.nodes.is_multiple_of(1024);
+            let out_of_time = check && self.deadline.is_some_and(|d| Instant::now() >= d);
+            let stopped = check && self.stop.is_some_and(|stop| stop.load(atomic::Ordering::Relaxed));
+            self.aborted = out_of_nodes || out_of_time || stopped;
+        }
+        self.aborted
+    }
 
-        if ordering.is_gt() {
-            move_selector.reset();
+    /// The core minimax implementation.
+    /// Alpha-Beta Negamax, implementation based on
+    /// <https://en.wikipedia.org/wiki/Negamax#Negamax_with_alpha_beta_pruning>
+    ///
+    /// If a transposition table is given it is used for cutoffs (except at the root, where we need a move)
+    /// and to search the previous best move first.
+    fn recurse<S: MoveSelector<B::Move>>(
+        &mut self,
+        board: &mut B,
+        board_heuristic: H::V,
+        length: u32,
+        depth_left: u32,
+        alpha: Option<H::V>,
+        beta: Option<H::V>,
+        mut move_selector: S,
+    ) -> MinimaxResult<H::V, S::Result> {
+        self.nodes += 1;
+        if self.track_pv {
+            self.clear_pv(length);
         }
-        if ordering.is_ge() {
-            move_selector.accept(mv);
+
+        if depth_left == 0 || board.is_done() {
+            if !board.is_done() {
+                self.depth_limited = true;
+            }
+
+            return MinimaxResult {
+                value: board_heuristic,
+                best_move: None,
+            };
         }
-        alpha = Some(new_alpha);
 
-        if beta.map_or(false, |beta| H::merge(beta, new_alpha).1.is_ge()) {
-            ControlFlow::Break(MinimaxResult {
-                value: new_best_value,
+        if self.check_abort() {
+            return MinimaxResult {
+                value: board_heuristic,
                 best_move: None,
-            })
+            };
+        }
+
+        // probe the transposition table
+        let tt_key = self.tt.as_ref().map(|tt| tt.key(board));
+        let mut tt_move = None;
+
+        if let (Some(tt), Some(tt_key)) = (&self.tt, tt_key) {
+            if let Some(entry) = tt.get(board, tt_key) {
+                tt_move = entry.best_move.filter(|&mv| board.is_available_move(mv));
+
+                if length != 0 && entry.length == length && entry.depth >= depth_left {
+                    let cutoff = match entry.bound {
+                        Bound::Exact => true,
+                        Bound::Lower => beta.is_some_and(|beta| value_ge::<B, H>(entry.value, beta)),
+                        Bound::Upper => alpha.is_some_and(|alpha| value_ge::<B, H>(alpha, entry.value)),
+                    };
+
+                    if cutoff {
+                        self.depth_limited |= !entry.complete;
+
+                        return MinimaxResult {
+                            value: entry.value,
+                            best_move: None,
+                        };
+                    }
+                }
+            }
+        }
+
+        // the root or transposition table move first, then optionally by heuristic score, killers and history
+        let first_move = if length == 0 {
+            self.root_move.filter(|&mv| board.is_available_move(mv)).or(tt_move)
         } else {
-            ControlFlow::Continue(())
+            tt_move
+        };
+
+        // the board is modified while searching the children, so the moves are collected first
+        let heuristic = self.heuristic;
+        let mut moves: Vec<B::Move> = vec![];
+        if H::ORDER_MOVES {
+            let mut keyed_moves = vec![];
+            board.available_moves().for_each(|mv: B::Move| {
+                let key = (
+                    Some(mv) == first_move,
+                    heuristic.move_order_score(board, mv),
+                    self.ordering.is_killer(length, mv),
+                    self.ordering.history(mv),
+                );
+                keyed_moves.push((key, mv));
+            });
+            keyed_moves.sort_by_key(|&(key, _)| Reverse(key));
+            moves.extend(keyed_moves.into_iter().map(|(_, mv)| mv));
+        } else {
+            moves.extend(first_move);
+            board
+                .available_moves()
+                .filter(|&mv| Some(mv) != first_move)
+                .for_each(|mv| moves.push(mv));
         }
-    });
 
-    match early {
-        ControlFlow::Break(early) => early,
-        ControlFlow::Continue(()) => MinimaxResult {
-            value: best_value.unwrap(),
-            best_move: Some(move_selector.finish()),
-        },
+        // track whether this subtree is complete separately
+        let outer_depth_limited = std::mem::replace(&mut self.depth_limited, false);
+
+        let original_alpha = alpha;
+        let mut best_value = None;
+        let mut best_tt_move = None;
+        let mut alpha = alpha;
+
+        let early = moves.into_iter().try_for_each(|mv| {
+            let child_alpha = if S::ALL_TIES { None } else { alpha };
+            let mut child_value = self.recurse_child(board, board_heuristic, mv, length, depth_left, child_alpha, beta);
+
+            let looks_tied = best_value.is_some_and(|best_value| H::merge(best_value, child_value).1.is_eq());
+            if S::TIES && child_alpha.is_some() && looks_tied && !self.aborted {
+                child_value = self.recurse_child(board, board_heuristic, mv, length, depth_left, None, beta);
+            }
+
+            if self.aborted {
+                return ControlFlow::Break(child_value);
+            }
+
+            let (new_best_value, ordering) = best_value.map_or((child_value, Ordering::Greater), |best_value| {
+                H::merge(best_value, child_value)
+            });
+            let new_alpha = alpha.map_or(new_best_value, |alpha| H::merge(alpha, new_best_value).0);
+
+            best_value = Some(new_best_value);
+
+            if ordering.is_gt() {
+                move_selector.reset();
+                best_tt_move = Some(mv);
+                if self.track_pv {
+                    self.update_pv(length, mv);
+                }
+            }
+            if ordering.is_ge() {
+                move_selector.accept(mv);
+            }
+            alpha = Some(new_alpha);
+
+            if beta.is_some_and(|beta| H::merge(beta, new_alpha).1.is_ge()) {
+                if H::ORDER_MOVES {
+                    self.ordering.record_cutoff(length, depth_left, mv);
+                }
+                ControlFlow::Break(new_best_value)
+            } else {
+                ControlFlow::Continue(())
+            }
+        });
+
+        if self.aborted {
+            return MinimaxResult {
+                value: board_heuristic,
+                best_move: None,
+            };
+        }
+
+        let complete = !self.depth_limited;
+        self.depth_limited |= outer_depth_limited;
+
+        let (result, bound) = match early {
+            ControlFlow::Break(value) => (MinimaxResult { value, best_move: None }, Bound::Lower),
+            ControlFlow::Continue(()) => {
+                let value = best_value.unwrap();
+                let bound = match original_alpha {
+                    Some(original_alpha) if value_ge::<B, H>(original_alpha, value) => Bound::Upper,
+                    _ => Bound::Exact,
+                };
+                let result = MinimaxResult {
+                    value,
+                    best_move: Some(move_selector.finish()),
+                };
+                (result, bound)
+            }
+        };
+
+        if let (Some(tt), Some(tt_key)) = (&mut self.tt, tt_key) {
+            let entry = TTEntry {
+                length,
+                depth: depth_left,
+                complete,
+                bound,
+                value: result.value,
+                best_move: best_tt_move,
+            };
+            tt.insert(board, tt_key, entry);
+        }
+
+        result
     }
 }
 
+type DepthCallback<V, M> = Box<dyn FnMut(&MinimaxDepthResult<V, M>) + Send>;
+
 pub struct MiniMaxBot<B: Board, H: Heuristic<B>, R: Rng> {
     depth: u32,
+    budget: Option<MinimaxBudget>,
     heuristic: H,
+    tt: Option<TranspositionTable<B, H::V>>,
     rng: R,
+    info_callback: Option<DepthCallback<H::V, B::Move>>,
     ph: PhantomData<B>,
 }
 
@@ -303,8 +866,8 @@ impl<B: Board, H: Heuristic<B>, R: Rng> Debug for MiniMaxBot<B, H, R> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "MiniMaxBot {{ depth: {}, heuristic: {:?} }}",
-            self.depth, self.heuristic
+            "MiniMaxBot {{ depth: {}, budget: {:?}, heuristic: {:?}, tt: {:?} }}",
+            self.depth, self.budget, self.heuristic, self.tt
         )
     }
 }
@@ -314,16 +877,84 @@ impl<B: Board, H: Heuristic<B>, R: Rng> MiniMaxBot<B, H, R> {
         assert!(depth > 0, "requires depth>0 to find the best move");
         MiniMaxBot {
             depth,
+            budget: None,
             heuristic,
+            tt: None,
             rng,
+            info_callback: None,
             ph: PhantomData,
         }
     }
+
+    /// Construct a bot that uses [minimax_iterative] with the given budget instead of a fixed depth.
+    pub fn new_iterative(budget: MinimaxBudget, heuristic: H, rng: R) -> Self {
+        let mut bot = Self::new(budget.max_depth, heuristic, rng);
+        bot.budget = Some(budget);
+        bot
+    }
+
+    /// Use the given transposition table for all future searches.
+    /// The table is kept between moves, so later searches can reuse the work of earlier ones.
+    pub fn with_tt(mut self, tt: TranspositionTable<B, H::V>) -> Self {
+        self.tt = Some(tt);
+        self
+    }
+
+    /// Report [SearchInfo] to `callback` after each completed iteration of [minimax_iterative].
+    /// Fixed depth searches also use iterative deepening once a callback is set.
+    pub fn with_info_callback(mut self, mut callback: impl FnMut(&SearchInfo<B::Move>) + Send + 'static) -> Self
+    where
+        H::V: Cast<f32>,
+    {
+        self.info_callback = Some(Box::new(move |result: &MinimaxDepthResult<H::V, B::Move>| {
+            callback(&SearchInfo {
+                depth: result.depth,
+                nodes: result.total_nodes,
+                time: result.elapsed,
+                score: Score::from_value(result.value.cast()),
+                pv: result.pv.clone(),
+                moves: vec![],
+            })
+        }));
+        self
+    }
+
+    fn search_iterative(&mut self, board: &B, budget: MinimaxBudget) -> B::Move {
+        let info_callback = &mut self.info_callback;
+        let callback = |result: &MinimaxDepthResult<H::V, B::Move>| {
+            if let Some(info_callback) = info_callback.as_mut() {
+                info_callback(result)
+            }
+        };
+        let results = minimax_iterative_with_callback(
+            board,
+            &self.heuristic,
+            budget,
+            self.tt.as_mut(),
+            &mut self.rng,
+            callback,
+        );
+        // the first iteration is always completed
+        results.last().unwrap().best_move
+    }
 }
 
 impl<B: Board, H: Heuristic<B> + Debug, R: Rng> Bot<B> for MiniMaxBot<B, H, R> {
     fn select_move(&mut self, board: &B) -> B::Move {
         assert!(!board.is_done());
+
+        if let Some(budget) = &self.budget {
+            return self.search_iterative(board, budget.clone());
+        }
+        if self.info_callback.is_some() {
+            return self.search_iterative(board, MinimaxBudget::depth(self.depth));
+        }
+
+        let result = match &mut self.tt {
+            None => minimax(board, &self.heuristic, self.depth, &mut self.rng),
+            Some(tt) => minimax_with_tt(board, &self.heuristic, self.depth, tt, &mut self.rng),
+        };
+
         // SAFETY: unwrap is safe because:
         // * depth > 0 (see [`MiniMaxBot::new`])
         // * the board is not done (see assert)
@@ -332,8 +963,24 @@ impl<B: Board, H: Heuristic<B> + Debug, R: Rng> Bot<B> for MiniMaxBot<B, H, R> {
         //   by contraposition, we have
         //     !board.is_done() && depth > 0 => best_move.is_some()
         // hence best_move.is_some()
-        minimax(board, &self.heuristic, self.depth, &mut self.rng)
-            .best_move
-            .unwrap()
+        result.best_move.unwrap()
+    }
+
+    fn select_move_limited(&mut self, board: &B, limits: &SearchLimits) -> B::Move {
+        if !limits.is_limited() && limits.stop.is_none() {
+            return self.select_move(board);
+        }
+        assert!(!board.is_done());
+
+        let budget = if limits.is_limited() {
+            MinimaxBudget::from_limits(limits, self.depth)
+        } else {
+            // only the stop flag is set, otherwise keep the settings of this bot
+            let mut budget = self.budget.clone().unwrap_or_else(|| MinimaxBudget::depth(self.depth));
+            budget.stop = limits.stop.clone();
+            budget
+        };
+
+        self.search_iterative(board, budget)
     }
 }
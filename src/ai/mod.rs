@@ -1,18 +1,32 @@
 use std::fmt::Debug;
 
+use crate::ai::limits::SearchLimits;
 use crate::board::Board;
 
+pub mod evaluator;
+pub mod info;
+pub mod limits;
 pub mod mcts;
 pub mod minimax;
 pub mod simple;
 pub mod solver;
+pub mod transposition;
 
 pub trait Bot<B: Board>: Debug {
     /// Pick a move to play. Panics if the board is done.
     ///
-    /// `self` is mutable to allow for random state, this method is not supposed to
-    /// modify `self` in any other significant way.
+    /// `self` is mutable to allow for random state and for state kept between moves, for example a search tree
+    /// that is reused. Such state must only affect the time the search takes or the quality of the move,
+    /// and implementations should recover when called with an unrelated board.
     fn select_move(&mut self, board: &B) -> B::Move;
+
+    /// Pick a move to play while respecting `limits`. Panics if the board is done.
+    ///
+    /// The default implementation ignores the limits and calls [Bot::select_move].
+    #[allow(unused_variables)]
+    fn select_move_limited(&mut self, board: &B, limits: &SearchLimits) -> B::Move {
+        self.select_move(board)
+    }
 }
 
 impl<B: Board, F: FnMut(&B) -> B::Move + Debug> Bot<B> for F {
@@ -20,3 +34,14 @@ impl<B: Board, F: FnMut(&B) -> B::Move + Debug> Bot<B> for F {
         self(board)
     }
 }
+
+/// Allows picking bots at runtime, for example from the command line.
+impl<B: Board> Bot<B> for Box<dyn Bot<B> + Send> {
+    fn select_move(&mut self, board: &B) -> B::Move {
+        (**self).select_move(board)
+    }
+
+    fn select_move_limited(&mut self, board: &B, limits: &SearchLimits) -> B::Move {
+        (**self).select_move_limited(board, limits)
+    }
+}
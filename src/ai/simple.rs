@@ -4,6 +4,7 @@ use std::fmt::{Debug, Formatter};
 use internal_iterator::InternalIterator;
 use rand::Rng;
 
+use crate::ai::limits::SearchLimits;
 use crate::ai::Bot;
 use crate::board::Board;
 use crate::pov::NonPov;
@@ -35,6 +36,8 @@ impl<B: Board, R: Rng> Bot<B> for RandomBot<R> {
 ///
 /// The same number of simulations `rollouts / nb_moves` is done for
 /// each move, and the move resulting in the best average score is selected.
+/// When searching with [SearchLimits] the simulations are spread evenly over the moves until a limit is reached,
+/// `nodes` then limits the total number of simulations.
 pub struct RolloutBot<R: Rng> {
     rollouts: u32,
     rng: R,
@@ -62,17 +65,57 @@ impl<B: Board, R: Rng> Bot<B> for RolloutBot<R> {
                 let child = board.clone_and_play(mv);
 
                 let score: i64 = (0..rollouts_per_move)
-                    .map(|_| {
-                        let mut copy = child.clone();
-                        while !copy.is_done() {
-                            copy.play(copy.random_available_move(&mut self.rng))
-                        }
-                        copy.outcome().unwrap().pov(board.next_player()).sign::<i64>()
-                    })
+                    .map(|_| rollout_score(board, &child, &mut self.rng))
                     .sum();
 
                 score
             })
             .unwrap()
     }
+
+    fn select_move_limited(&mut self, board: &B, limits: &SearchLimits) -> B::Move {
+        if !limits.is_limited() && limits.stop.is_none() {
+            return self.select_move(board);
+        }
+        assert!(!board.is_done());
+
+        let max_rollouts = limits.nodes.unwrap_or_else(|| {
+            if limits.time_budget().is_some() {
+                u64::MAX
+            } else {
+                self.rollouts as u64
+            }
+        });
+
+        let timer = limits.start();
+        let mut scores: Vec<(B::Move, B, i64)> = board
+            .available_moves()
+            .map(|mv| (mv, board.clone_and_play(mv), 0))
+            .collect();
+
+        // do rollouts in rounds, one for each move, so all moves are always simulated equally often
+        // at least one round is always completed
+        let mut rollouts = 0;
+        loop {
+            if rollouts > 0 && (rollouts + scores.len() as u64 > max_rollouts || timer.should_stop(rollouts)) {
+                break;
+            }
+
+            for (_, child, score) in &mut scores {
+                *score += rollout_score(board, child, &mut self.rng);
+            }
+            rollouts += scores.len() as u64;
+        }
+
+        scores.iter().max_by_key(|&&(_, _, score)| score).unwrap().0
+    }
+}
+
+/// Simulate a random game starting from `child` and return the score from the POV of the player to move on `board`.
+fn rollout_score<B: Board>(board: &B, child: &B, rng: &mut impl Rng) -> i64 {
+    let mut copy = child.clone();
+    while !copy.is_done() {
+        copy.play(copy.random_available_move(rng))
+    }
+    copy.outcome().unwrap().pov(board.next_player()).sign::<i64>()
 }
@@ -5,7 +5,12 @@ use std::ops::Neg;
 use internal_iterator::InternalIterator;
 use rand::Rng;
 
-use crate::ai::minimax::{minimax, minimax_all_moves, minimax_value, Heuristic, MinimaxResult};
+use crate::ai::limits::SearchLimits;
+use crate::ai::minimax::{
+    minimax, minimax_all_moves, minimax_all_moves_with_tt, minimax_iterative, minimax_pv, minimax_value,
+    minimax_value_with_tt, minimax_with_tt, Heuristic, MinimaxBudget, MinimaxPvResult, MinimaxResult,
+};
+use crate::ai::transposition::TranspositionTable;
 use crate::ai::Bot;
 use crate::board::{Board, Outcome};
 use crate::pov::NonPov;
@@ -38,6 +43,8 @@ impl<B: Board> Heuristic<B> for SolverHeuristic {
             })
     }
 
+    const VALUE_FROM_CHILD: bool = true;
+
     fn merge(old: SolverValue, new: SolverValue) -> (SolverValue, Ordering) {
         SolverValue::merge(old, new)
     }
@@ -118,10 +125,44 @@ pub fn solve_all_moves<B: Board>(board: &B, depth: u32) -> MinimaxResult<SolverV
     minimax_all_moves(board, &SolverHeuristic, depth)
 }
 
+/// Variant of [solve] that returns the principal variation, see [minimax_pv].
+/// For a won or lost board this is the fastest win against the longest defence.
+pub fn solve_pv<B: Board>(board: &B, depth: u32) -> MinimaxPvResult<SolverValue, B::Move> {
+    minimax_pv(board, &SolverHeuristic, depth)
+}
+
 pub fn solve_value<B: Board>(board: &B, depth: u32) -> SolverValue {
     minimax_value(board, &SolverHeuristic, depth)
 }
 
+/// Variant of [solve] that uses and fills the transposition table `tt`, see [minimax_with_tt].
+pub fn solve_with_tt<B: Board>(
+    board: &B,
+    depth: u32,
+    tt: &mut TranspositionTable<B, SolverValue>,
+    rng: &mut impl Rng,
+) -> MinimaxResult<SolverValue, B::Move> {
+    minimax_with_tt(board, &SolverHeuristic, depth, tt, rng)
+}
+
+/// Variant of [solve_all_moves] that uses and fills the transposition table `tt`, see [minimax_all_moves_with_tt].
+pub fn solve_all_moves_with_tt<B: Board>(
+    board: &B,
+    depth: u32,
+    tt: &mut TranspositionTable<B, SolverValue>,
+) -> MinimaxResult<SolverValue, Vec<B::Move>> {
+    minimax_all_moves_with_tt(board, &SolverHeuristic, depth, tt)
+}
+
+/// Variant of [solve_value] that uses and fills the transposition table `tt`, see [minimax_value_with_tt].
+pub fn solve_value_with_tt<B: Board>(
+    board: &B,
+    depth: u32,
+    tt: &mut TranspositionTable<B, SolverValue>,
+) -> SolverValue {
+    minimax_value_with_tt(board, &SolverHeuristic, depth, tt)
+}
+
 /// Return whether this board is a double forced draw, ie. no matter what either player does the game can only end in a draw.
 /// Returns `None` if the result is unknown.
 pub fn is_double_forced_draw(board: &impl Board, depth: u32) -> Option<bool> {
@@ -175,4 +216,15 @@ impl<B: Board, R: Rng> Bot<B> for SolverBot<R> {
             .best_move
             .unwrap()
     }
+
+    fn select_move_limited(&mut self, board: &B, limits: &SearchLimits) -> B::Move {
+        if !limits.is_limited() && limits.stop.is_none() {
+            return self.select_move(board);
+        }
+
+        let budget = MinimaxBudget::from_limits(limits, self.depth);
+        let results = minimax_iterative(board, &SolverHeuristic, budget, None, &mut self.rng);
+        // the first iteration is always completed
+        results.last().unwrap().best_move
+    }
 }
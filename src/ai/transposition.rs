@@ -0,0 +1,191 @@
+//! A size-bounded transposition table that can be shared between minimax searches.
+use std::collections::hash_map::DefaultHasher;
+use std::fmt::{Debug, Formatter};
+use std::hash::Hasher;
+
+use crate::board::{Board, ZobristHash};
+use crate::symmetry::Symmetry;
+
+/// The kind of value stored in a [TTEntry].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Bound {
+    /// The value is the exact minimax value.
+    Exact,
+    /// The real value is at least the stored value, the search was cut off by beta.
+    Lower,
+    /// The real value is at most the stored value, no move improved alpha.
+    Upper,
+}
+
+/// A single entry in a [TranspositionTable].
+#[derive(Debug, Copy, Clone)]
+pub struct TTEntry<M, V> {
+    /// The number of moves played since the root of the search that stored this entry.
+    /// Heuristics can depend on this value, so it is checked when probing: entries stored at a different length
+    /// are not used for cutoffs. It is not part of the hash, so such entries still share a slot.
+    pub length: u32,
+    /// The remaining search depth for which `value` was computed.
+    pub depth: u32,
+    /// Whether the search that produced this entry reached the end of the game everywhere,
+    /// in which case searching deeper would not change the result.
+    pub complete: bool,
+    pub bound: Bound,
+    pub value: V,
+    /// The move that was best (or that caused a cutoff), if any.
+    pub best_move: Option<M>,
+}
+
+/// The key of a board in a [TranspositionTable], see [TranspositionTable::key].
+#[derive(Debug, Copy, Clone)]
+pub struct TTKey<S> {
+    hash: u64,
+    sym: S,
+}
+
+#[derive(Debug, Copy, Clone)]
+struct Slot<M, V> {
+    hash: u64,
+    entry: TTEntry<M, V>,
+}
+
+/// A transposition table with a fixed number of slots, indexed by the [Hash] of the board.
+/// Boards that implement [ZobristHash] can use their much cheaper zobrist key instead, see [Self::with_zobrist].
+///
+/// When `canonicalize` is set boards are first mapped to their canonical symmetry
+/// (see [BoardSymmetry::canonicalize](crate::board::BoardSymmetry::canonicalize)), so symmetric positions share an entry.
+/// Stored moves are mapped back to the symmetry of the probing board.
+///
+/// Only the 64-bit hash of a board is stored, not the board itself, so hash collisions are possible but unlikely.
+/// Moves retrieved from the table are always checked for availability by the search before being used.
+pub struct TranspositionTable<B: Board, V> {
+    slots: Vec<Option<Slot<B::Move, V>>>,
+    canonicalize: bool,
+    filled: usize,
+    hash: fn(&B) -> u64,
+}
+
+impl<B: Board, V: Copy> TranspositionTable<B, V> {
+    pub fn new(capacity: usize, canonicalize: bool) -> Self {
+        assert!(capacity > 0, "transposition table needs at least one slot");
+        TranspositionTable {
+            slots: vec![None; capacity],
+            canonicalize,
+            filled: 0,
+            hash: hash_board::<B>,
+        }
+    }
+
+    /// Use [ZobristHash::zobrist] instead of [Hash] to compute the keys of boards.
+    ///
+    /// Zobrist keys leave out the counters for draw rules (eg. `moves_since_last_copy` for Ataxx),
+    /// so boards that only differ in those share entries. Close to such a move limit this can make
+    /// the search, and the solver in particular, return a wrong result.
+    pub fn with_zobrist(mut self) -> Self
+    where
+        B: ZobristHash,
+    {
+        self.hash = B::zobrist;
+        self
+    }
+
+    /// The maximum number of entries this table can hold.
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// The number of slots that currently hold an entry.
+    pub fn len(&self) -> usize {
+        self.filled
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.filled == 0
+    }
+
+    pub fn canonicalize(&self) -> bool {
+        self.canonicalize
+    }
+
+    pub fn clear(&mut self) {
+        self.slots.iter_mut().for_each(|slot| *slot = None);
+        self.filled = 0;
+    }
+
+    /// Compute the key for `board`, which can then be used for both [Self::get] and [Self::insert].
+    pub fn key(&self, board: &B) -> TTKey<B::Symmetry> {
+        if self.canonicalize {
+            let (sym, canonical) = B::Symmetry::all()
+                .iter()
+                .map(|&sym| (sym, board.map(sym)))
+                .min_by_key(|(_, cand)| cand.canonical_key())
+                .unwrap();
+            TTKey {
+                hash: (self.hash)(&canonical),
+                sym,
+            }
+        } else {
+            TTKey {
+                hash: (self.hash)(board),
+                sym: B::Symmetry::default(),
+            }
+        }
+    }
+
+    /// Get the entry for `board`, with the best move mapped back to the symmetry of `board`.
+    pub fn get(&self, board: &B, key: TTKey<B::Symmetry>) -> Option<TTEntry<B::Move, V>> {
+        let slot = self.slots[self.index(key)].as_ref()?;
+        if slot.hash != key.hash {
+            return None;
+        }
+
+        let mut entry = slot.entry;
+        if self.canonicalize {
+            // the stored move belongs to the canonical board, so it has to be mapped back from there
+            let canonical = board.map(key.sym);
+            entry.best_move = entry.best_move.map(|mv| canonical.map_move(key.sym.inverse(), mv));
+        }
+        Some(entry)
+    }
+
+    /// Store `entry` for `board`. An existing entry for a different board is always replaced,
+    /// an existing entry for the same board is only replaced if the new one was searched at least as deep.
+    pub fn insert(&mut self, board: &B, key: TTKey<B::Symmetry>, mut entry: TTEntry<B::Move, V>) {
+        entry.best_move = entry.best_move.map(|mv| board.map_move(key.sym, mv));
+
+        let index = self.index(key);
+        let slot = &mut self.slots[index];
+
+        match slot {
+            None => self.filled += 1,
+            Some(prev) => {
+                if prev.hash == key.hash && prev.entry.depth > entry.depth {
+                    return;
+                }
+            }
+        }
+
+        *slot = Some(Slot { hash: key.hash, entry });
+    }
+
+    fn index(&self, key: TTKey<B::Symmetry>) -> usize {
+        (key.hash % self.slots.len() as u64) as usize
+    }
+}
+
+fn hash_board<B: Board>(board: &B) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    board.hash(&mut hasher);
+    hasher.finish()
+}
+
+impl<B: Board, V> Debug for TranspositionTable<B, V> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "TranspositionTable {{ capacity: {}, filled: {}, canonicalize: {} }}",
+            self.slots.len(),
+            self.filled,
+            self.canonicalize
+        )
+    }
+}
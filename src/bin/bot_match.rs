@@ -0,0 +1,343 @@
+//! Run a match between two bots from the command line, see [USAGE].
+
+use std::convert::TryFrom;
+use std::fmt::Debug;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+use itertools::Itertools;
+use rand::rngs::SmallRng;
+use rand::SeedableRng;
+
+use board_game::ai::mcts::MCTSBot;
+use board_game::ai::minimax::{Heuristic, MiniMaxBot};
+use board_game::ai::simple::{RandomBot, RolloutBot};
+use board_game::ai::solver::{SolverBot, SolverHeuristic};
+use board_game::ai::Bot;
+use board_game::board::AltBoard;
+use board_game::games::ataxx::AtaxxBoard;
+use board_game::games::chess::ChessBoard;
+use board_game::games::connect4::Connect4;
+use board_game::games::oware::OwareBoard;
+use board_game::games::sttt::STTTBoard;
+use board_game::games::ttt::TTTBoard;
+use board_game::heuristic::ataxx::AtaxxTileHeuristic;
+use board_game::heuristic::chess::ChessPieceValueHeuristic;
+use board_game::heuristic::sttt::STTTTileHeuristic;
+use board_game::util::bot_game;
+use board_game::util::bot_game::{BotGameResult, Replay, TimeControl};
+use board_game::util::opening::{parse_ataxx_opening, parse_chess_opening, OpeningBook};
+use board_game::util::sprt::Sprt;
+
+const USAGE: &str = "\
+Usage: bot_match <game> <bot_l> <bot_r> [--games <n>] [--both-sides] [--replays <path>]
+                 [--sprt <elo0,elo1,alpha,beta>] [--pentanomial] [--openings <path>]
+
+  <game>           one of ataxx, sttt, connect4, chess, oware, ttt
+  <bot_l> <bot_r>  bot specs of the form name[:key=value,...], one of
+                     random
+                     rollout:rollouts=100
+                     mcts:iters=1000,c=2.0
+                     minimax:depth=4
+                     solver:depth=6
+                   games without a specific heuristic use the solver heuristic for minimax
+  --games <n>      the number of games per side, default 10
+  --both-sides     play each game twice with the bots switching sides
+  --replays <path> write the moves of all games to a file
+  --sprt <elo0,elo1,alpha,beta>
+                   stop as soon as a sequential probability ratio test resolves,
+                   --games is then the maximum number of games per side
+  --pentanomial    use statistics of game pairs for the test, requires --both-sides
+  --openings <path> start from the positions in a file, one FEN or move sequence per line,
+                   only supported for ataxx and chess";
+
+#[derive(Debug)]
+struct Args {
+    game: String,
+    bot_l: BotSpec,
+    bot_r: BotSpec,
+    games_per_side: u32,
+    both_sides: bool,
+    replays: Option<String>,
+    sprt: Option<Sprt>,
+    openings: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+enum BotSpec {
+    Random,
+    Rollout { rollouts: u32 },
+    MCTS { iterations: u64, exploration_weight: f32 },
+    MiniMax { depth: u32 },
+    Solver { depth: u32 },
+}
+
+fn main() {
+    let args = match Args::parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("Error: {}\n\n{}", e, USAGE);
+            std::process::exit(1);
+        }
+    };
+
+    let result = match (args.game.as_str(), &args.openings) {
+        ("ataxx", Some(path)) => read_openings(path, parse_ataxx_opening)
+            .and_then(|book| run_game(&args, book.sequential(), AtaxxTileHeuristic::default)),
+        ("chess", Some(path)) => read_openings(path, parse_chess_opening)
+            .and_then(|book| run_game(&args, book.sequential(), || ChessPieceValueHeuristic)),
+        (game, Some(_)) => Err(format!("openings are not supported for game '{}'", game)),
+        _ => run_default_game(&args),
+    };
+
+    if let Err(e) = result {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+}
+
+fn run_default_game(args: &Args) -> Result<(), String> {
+    match args.game.as_str() {
+        "ataxx" => run_game(args, AtaxxBoard::default, AtaxxTileHeuristic::default),
+        "sttt" => run_game(args, STTTBoard::default, STTTTileHeuristic::default),
+        "connect4" => run_game(args, Connect4::default, || SolverHeuristic),
+        "chess" => run_game(args, ChessBoard::default, || ChessPieceValueHeuristic),
+        "oware" => run_game(args, OwareBoard::<6>::default, || SolverHeuristic),
+        "ttt" => run_game(args, TTTBoard::default, || SolverHeuristic),
+        game => Err(format!("unknown game '{}'", game)),
+    }
+}
+
+fn read_openings<B: AltBoard>(path: &str, parse: impl Fn(&str) -> Result<B, String>) -> Result<OpeningBook<B>, String> {
+    let book = OpeningBook::read_file(path, parse).map_err(|e| format!("failed to read openings '{}': {}", path, e))?;
+    println!("Read {} openings from '{}'", book.len(), path);
+    Ok(book)
+}
+
+fn run_game<B: AltBoard, H: Heuristic<B> + Debug + Send + 'static>(
+    args: &Args,
+    start: impl Fn() -> B + Sync,
+    heuristic: impl Fn() -> H + Sync,
+) -> Result<(), String>
+where
+    H::V: Send,
+{
+    println!("Running {} games of {}", args.game_count(), args.game);
+    println!("  left:  {:?}", args.bot_l);
+    println!("  right: {:?}", args.bot_r);
+
+    let bot_l = || args.bot_l.build(heuristic());
+    let bot_r = || args.bot_r.build(heuristic());
+
+    let result: BotGameResult<B> = match args.sprt {
+        None => {
+            let result = bot_game::run(start, bot_l, bot_r, args.games_per_side, args.both_sides, |wdl, _| {
+                println!("{:?}", wdl)
+            });
+            println!("{:?}", result);
+            result
+        }
+        Some(sprt) => {
+            let (lower, upper) = sprt.bounds();
+            println!("  sprt:  {:?}, bounds ({:.3}, {:.3})", sprt, lower, upper);
+
+            let result = bot_game::run_sprt(
+                start,
+                bot_l,
+                bot_r,
+                args.games_per_side,
+                args.both_sides,
+                TimeControl::Unlimited,
+                sprt,
+                |wdl, llr, _| println!("{:?} llr {:.3}", wdl, llr),
+            );
+            println!("{:?}", result);
+            result.result
+        }
+    };
+
+    if let Some(path) = &args.replays {
+        write_replays(path, &result.replays).map_err(|e| format!("failed to write replays to '{}': {}", path, e))?;
+        println!("Wrote {} replays to '{}'", result.replays.len(), path);
+    }
+
+    Ok(())
+}
+
+fn write_replays<B: AltBoard>(path: &str, replays: &[Replay<B>]) -> std::io::Result<()> {
+    let mut f = BufWriter::new(File::create(path)?);
+
+    for (i, replay) in replays.iter().enumerate() {
+        writeln!(
+            f,
+            "game {}: left is {:?}, outcome {:?}, lost on time {:?}",
+            i, replay.player_l, replay.outcome, replay.lost_on_time
+        )?;
+        writeln!(f, "start:\n{}", replay.start)?;
+        writeln!(f, "moves: {}", replay.moves.iter().join(" "))?;
+        writeln!(f)?;
+    }
+
+    f.flush()
+}
+
+impl Args {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Args, String> {
+        let mut positional = vec![];
+        let mut games_per_side = 10;
+        let mut both_sides = false;
+        let mut replays = None;
+        let mut sprt = None;
+        let mut pentanomial = false;
+        let mut openings = None;
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--games" => {
+                    let value = args.next().ok_or("missing value for --games")?;
+                    games_per_side = value.parse().map_err(|_| format!("invalid game count '{}'", value))?;
+                }
+                "--both-sides" => both_sides = true,
+                "--replays" => replays = Some(args.next().ok_or("missing value for --replays")?),
+                "--sprt" => sprt = Some(parse_sprt(&args.next().ok_or("missing value for --sprt")?)?),
+                "--pentanomial" => pentanomial = true,
+                "--openings" => openings = Some(args.next().ok_or("missing value for --openings")?),
+                "-h" | "--help" => return Err("help requested".to_owned()),
+                _ if arg.starts_with("--") => return Err(format!("unknown flag '{}'", arg)),
+                _ => positional.push(arg),
+            }
+        }
+
+        let (game, bot_l, bot_r) = match <[String; 3]>::try_from(positional) {
+            Ok([game, bot_l, bot_r]) => (game, bot_l, bot_r),
+            Err(positional) => return Err(format!("expected 3 positional arguments, got {}", positional.len())),
+        };
+
+        let sprt = match sprt {
+            None if pentanomial => return Err("--pentanomial requires --sprt".to_owned()),
+            Some(_) if pentanomial && !both_sides => return Err("--pentanomial requires --both-sides".to_owned()),
+            sprt => sprt.map(|sprt: Sprt| sprt.with_pentanomial(pentanomial)),
+        };
+
+        Ok(Args {
+            game,
+            bot_l: BotSpec::parse(&bot_l)?,
+            bot_r: BotSpec::parse(&bot_r)?,
+            games_per_side,
+            both_sides,
+            replays,
+            sprt,
+            openings,
+        })
+    }
+
+    fn game_count(&self) -> u32 {
+        if self.both_sides {
+            2 * self.games_per_side
+        } else {
+            self.games_per_side
+        }
+    }
+}
+
+impl BotSpec {
+    fn parse(spec: &str) -> Result<BotSpec, String> {
+        let (name, params) = spec.split_once(':').unwrap_or((spec, ""));
+
+        let mut params = params
+            .split(',')
+            .filter(|p| !p.is_empty())
+            .map(|p| {
+                p.split_once('=')
+                    .ok_or_else(|| format!("invalid parameter '{}' in '{}'", p, spec))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut take = |key: &str| params.iter().position(|&(k, _)| k == key).map(|i| params.remove(i).1);
+
+        let result = match name {
+            "random" => BotSpec::Random,
+            "rollout" => BotSpec::Rollout {
+                rollouts: parse_param(spec, "rollouts", take("rollouts"), 100)?,
+            },
+            "mcts" => BotSpec::MCTS {
+                iterations: parse_positive(spec, "iters", take("iters"), 1000)?,
+                exploration_weight: parse_param(spec, "c", take("c"), 2.0)?,
+            },
+            "minimax" => BotSpec::MiniMax {
+                depth: parse_positive(spec, "depth", take("depth"), 4)?,
+            },
+            "solver" => BotSpec::Solver {
+                depth: parse_positive(spec, "depth", take("depth"), 6)?,
+            },
+            _ => return Err(format!("unknown bot '{}'", name)),
+        };
+
+        if let Some((key, _)) = params.first() {
+            return Err(format!("unknown parameter '{}' for bot '{}'", key, name));
+        }
+        Ok(result)
+    }
+
+    fn build<B: AltBoard, H: Heuristic<B> + Debug + Send + 'static>(&self, heuristic: H) -> Box<dyn Bot<B> + Send>
+    where
+        H::V: Send,
+    {
+        let rng = SmallRng::from_entropy();
+        match *self {
+            BotSpec::Random => Box::new(RandomBot::new(rng)),
+            BotSpec::Rollout { rollouts } => Box::new(RolloutBot::new(rollouts, rng)),
+            BotSpec::MCTS {
+                iterations,
+                exploration_weight,
+            } => Box::new(MCTSBot::new(iterations, exploration_weight, rng)),
+            BotSpec::MiniMax { depth } => Box::new(MiniMaxBot::new(depth, heuristic, rng)),
+            BotSpec::Solver { depth } => Box::new(SolverBot::new(depth, rng)),
+        }
+    }
+}
+
+fn parse_sprt(value: &str) -> Result<Sprt, String> {
+    let error = || format!("invalid sprt settings '{}', expected elo0,elo1,alpha,beta", value);
+
+    let values = value
+        .split(',')
+        .map(|v| v.trim().parse::<f64>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| error())?;
+
+    match *values.as_slice() {
+        [elo0, elo1, alpha, beta] => {
+            let valid_rate = |r: f64| 0.0 < r && r < 1.0;
+            if elo0 < elo1 && valid_rate(alpha) && valid_rate(beta) {
+                Ok(Sprt::new(elo0, elo1, alpha, beta))
+            } else {
+                Err(error())
+            }
+        }
+        _ => Err(error()),
+    }
+}
+
+fn parse_param<T: std::str::FromStr>(spec: &str, key: &str, value: Option<&str>, default: T) -> Result<T, String> {
+    match value {
+        None => Ok(default),
+        Some(value) => value
+            .parse()
+            .map_err(|_| format!("invalid value '{}' for '{}' in '{}'", value, key, spec)),
+    }
+}
+
+/// Like [parse_param], but also rejects zero.
+fn parse_positive<T: std::str::FromStr + Default + PartialEq>(
+    spec: &str,
+    key: &str,
+    value: Option<&str>,
+    default: T,
+) -> Result<T, String> {
+    let result = parse_param(spec, key, value, default)?;
+    if result == T::default() {
+        return Err(format!("'{}' must be positive in '{}'", key, spec));
+    }
+    Ok(result)
+}
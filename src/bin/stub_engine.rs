@@ -0,0 +1,33 @@
+//! A minimal engine that plays random moves, used to test
+//! [ProcessBot](board_game::interface::engine::process::ProcessBot).
+//!
+//! Usage: `stub_engine (uai | uci)`, speaks the given protocol on stdin and stdout.
+
+use rand::rngs::SmallRng;
+use rand::SeedableRng;
+
+use board_game::ai::simple::RandomBot;
+use board_game::interface::engine::server::EngineServer;
+use board_game::interface::uai::codec::UaiCodec;
+use board_game::interface::uci::codec::UciCodec;
+
+fn main() -> std::io::Result<()> {
+    let protocol = std::env::args().nth(1).unwrap_or_else(|| "uai".to_owned());
+    let bot = RandomBot::new(SmallRng::seed_from_u64(0));
+
+    match protocol.as_str() {
+        "uai" => EngineServer::new(UaiCodec, "stub", "board-game").run(
+            bot,
+            std::io::stdin(),
+            std::io::stdout(),
+            std::io::sink(),
+        ),
+        "uci" => EngineServer::new(UciCodec::default(), "stub", "board-game").run(
+            bot,
+            std::io::stdin(),
+            std::io::stdout(),
+            std::io::sink(),
+        ),
+        _ => panic!("unknown protocol {:?}, expected uai or uci", protocol),
+    }
+}
@@ -62,6 +62,32 @@ where
         next
     }
 
+    /// Play `mv`, call `f` on the resulting board and then restore this board to its original state.
+    /// Panics if this board is done or if the move is not available or valid for this board.
+    ///
+    /// Searches use this to visit children. The default implementation plays the move on a clone,
+    /// boards that implement [UndoBoard] more cheaply can override it with [play_then_undo_in_place].
+    fn play_then_undo<R>(&mut self, mv: Self::Move, f: impl FnOnce(&mut Self) -> R) -> R {
+        let mut child = self.clone_and_play(mv);
+        f(&mut child)
+    }
+
+    /// Play `mv` on the board `get(outer)`, call `f` on `outer` and then undo the move again.
+    /// This allows boards that wrap this board to forward [Board::play_then_undo].
+    ///
+    /// Returns `f` without calling it if this board cannot undo moves in place, which is the default.
+    /// Boards that override [Board::play_then_undo] with [play_then_undo_in_place] should override this
+    /// with [play_then_undo_wrapped_in_place].
+    #[allow(unused_variables)]
+    fn play_then_undo_wrapped<W, R, F: FnOnce(&mut W) -> R>(
+        outer: &mut W,
+        get: fn(&mut W) -> &mut Self,
+        mv: Self::Move,
+        f: F,
+    ) -> Result<R, F> {
+        Err(f)
+    }
+
     /// The outcome of this board, is `None` when this games is not done yet.
     fn outcome(&self) -> Option<Outcome>;
 
@@ -79,6 +105,86 @@ where
 /// A marker trait for boards which guarantee that [Board::next_player] flips after a move is played.
 pub trait Alternating {}
 
+/// A board that can undo moves, which can be cheaper than cloning the board before playing a move.
+pub trait UndoBoard: Board {
+    /// The information needed to undo a move.
+    type Undo;
+
+    /// Play the move `mv` like [Board::play], returning a token that can be passed to [UndoBoard::undo].
+    fn play_undoable(&mut self, mv: Self::Move) -> Self::Undo;
+
+    /// Undo the move that returned `undo`, which must be the last move played on this board that was not undone yet.
+    fn undo(&mut self, undo: Self::Undo);
+}
+
+/// An implementation of [Board::play_then_undo] that plays and undoes the move in place.
+/// If `f` panics the board is left in the state after the move.
+pub fn play_then_undo_in_place<B: UndoBoard, R>(board: &mut B, mv: B::Move, f: impl FnOnce(&mut B) -> R) -> R {
+    let undo = board.play_undoable(mv);
+    let result = f(board);
+    board.undo(undo);
+    result
+}
+
+/// An implementation of [Board::play_then_undo_wrapped] that plays and undoes the move in place.
+/// If `f` panics the board is left in the state after the move.
+pub fn play_then_undo_wrapped_in_place<B: UndoBoard, W, R, F: FnOnce(&mut W) -> R>(
+    outer: &mut W,
+    get: fn(&mut W) -> &mut B,
+    mv: B::Move,
+    f: F,
+) -> Result<R, F> {
+    let undo = get(outer).play_undoable(mv);
+    let result = f(outer);
+    get(outer).undo(undo);
+    Ok(result)
+}
+
+/// A board that keeps track of a [Zobrist hash](https://en.wikipedia.org/wiki/Zobrist_hashing) of its state.
+/// The key is updated incrementally when moves are played, so getting it is much cheaper than using [Hash].
+///
+/// Equal boards must have equal keys, and different boards should have different keys with high probability.
+/// Counters that only matter for draw rules, like the number of moves since the last capture, are not included.
+pub trait ZobristHash: Board {
+    fn zobrist(&self) -> u64;
+}
+
+/// A text notation for the positions and moves of a board.
+/// [NotationCodec](crate::interface::engine::NotationCodec) turns it into a codec for game records and engine servers.
+///
+/// Formatting and then parsing a position or move should give back an equivalent value,
+/// although information that is not part of the notation (eg. the history of a chess board) can be lost.
+pub trait BoardNotation: Board {
+    type PositionError: Debug;
+    type MoveError: Debug;
+
+    fn parse_position(position: &str) -> Result<Self, Self::PositionError>;
+
+    fn format_position(&self) -> String;
+
+    /// Parse `mv` as a move for this board.
+    /// This only checks whether the move is available if the notation itself depends on the available moves.
+    fn parse_move(&self, mv: &str) -> Result<Self::Move, Self::MoveError>;
+
+    fn format_move(&self, mv: Self::Move) -> String;
+}
+
+/// A generic error for [BoardNotation] implementations without a more specific error type.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct InvalidNotation {
+    pub text: String,
+    pub reason: String,
+}
+
+impl InvalidNotation {
+    pub fn new(text: &str, reason: impl Into<String>) -> Self {
+        InvalidNotation {
+            text: text.to_owned(),
+            reason: reason.into(),
+        }
+    }
+}
+
 /// Auto trait for [Board]s that also implement [Alternating].
 pub trait AltBoard: Board + Alternating {}
 
@@ -126,6 +232,26 @@ macro_rules! impl_unit_symmetry_board {
     };
 }
 
+/// Utility macro to implement [UndoBoard] for boards that are cheap to clone, by using the previous board as the undo token.
+#[macro_export]
+macro_rules! impl_clone_undo_board {
+    ($B:ty) => {
+        impl $crate::board::UndoBoard for $B {
+            type Undo = $B;
+
+            fn play_undoable(&mut self, mv: <$B as $crate::board::Board>::Move) -> Self::Undo {
+                let prev = self.clone();
+                $crate::board::Board::play(self, mv);
+                prev
+            }
+
+            fn undo(&mut self, undo: Self::Undo) {
+                *self = undo;
+            }
+        }
+    };
+}
+
 /// A helper trait that describes the ways in which a board is symmetric.
 /// For boards without any symmetry, the macro [impl_unit_symmetry_board] can be used to reduce boilerplate.
 /// This is a separate trait specifically to allow this trick to work.
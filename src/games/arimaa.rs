@@ -7,8 +7,13 @@ use arimaa_engine_step::{Action, Direction, GameState, Piece, Square, Terminal};
 use internal_iterator::InternalIterator;
 use once_cell::sync::OnceCell;
 
-use crate::board::{AllMovesIterator, AvailableMovesIterator, Board, BoardMoves, Outcome, Player};
+use crate::board::{
+    play_then_undo_in_place, play_then_undo_wrapped_in_place, AllMovesIterator, AvailableMovesIterator, Board,
+    BoardMoves, BoardNotation, Outcome, Player, UndoBoard,
+};
 use crate::impl_unit_symmetry_board;
+use crate::interface::aei::notation;
+use crate::interface::aei::notation::{InvalidAeiMove, InvalidAeiPosition};
 use crate::util::bitboard::BitBoard8;
 
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -19,6 +24,13 @@ pub struct ArimaaBoard {
     available_moves_cache: OnceCell<Vec<Action>>,
 }
 
+/// The undo token for [ArimaaBoard], the previous state including the cached available moves.
+#[derive(Debug, Clone)]
+pub struct ArimaaUndo {
+    state: GameState,
+    available_moves_cache: OnceCell<Vec<Action>>,
+}
+
 impl Default for ArimaaBoard {
     fn default() -> Self {
         ArimaaBoard::from_state(GameState::initial())
@@ -88,6 +100,19 @@ impl Board for ArimaaBoard {
         self.available_moves_cache = OnceCell::new();
     }
 
+    fn play_then_undo<R>(&mut self, mv: Action, f: impl FnOnce(&mut Self) -> R) -> R {
+        play_then_undo_in_place(self, mv, f)
+    }
+
+    fn play_then_undo_wrapped<W, R, F: FnOnce(&mut W) -> R>(
+        outer: &mut W,
+        get: fn(&mut W) -> &mut Self,
+        mv: Action,
+        f: F,
+    ) -> Result<R, F> {
+        play_then_undo_wrapped_in_place(outer, get, mv, f)
+    }
+
     fn outcome(&self) -> Option<Outcome> {
         self.state.is_terminal().map(|t| match t {
             Terminal::GoldWin => Outcome::WonBy(Player::A),
@@ -100,6 +125,26 @@ impl Board for ArimaaBoard {
     }
 }
 
+impl UndoBoard for ArimaaBoard {
+    type Undo = ArimaaUndo;
+
+    fn play_undoable(&mut self, mv: Action) -> ArimaaUndo {
+        assert!(!self.is_done());
+        assert!(self.is_available_move(mv));
+
+        let next = self.state.take_action(&mv);
+        ArimaaUndo {
+            state: std::mem::replace(&mut self.state, next),
+            available_moves_cache: std::mem::take(&mut self.available_moves_cache),
+        }
+    }
+
+    fn undo(&mut self, undo: ArimaaUndo) {
+        self.state = undo.state;
+        self.available_moves_cache = undo.available_moves_cache;
+    }
+}
+
 pub fn player_from_bool(player_bool: bool) -> Player {
     match player_bool {
         true => Player::A,
@@ -169,6 +214,28 @@ impl Hash for ArimaaBoard {
 
 impl_unit_symmetry_board!(ArimaaBoard);
 
+/// Positions and single actions in AEI notation, see [crate::interface::aei::notation].
+impl BoardNotation for ArimaaBoard {
+    type PositionError = InvalidAeiPosition;
+    type MoveError = InvalidAeiMove;
+
+    fn parse_position(position: &str) -> Result<Self, InvalidAeiPosition> {
+        notation::parse_position(position)
+    }
+
+    fn format_position(&self) -> String {
+        notation::format_position(self)
+    }
+
+    fn parse_move(&self, mv: &str) -> Result<Action, InvalidAeiMove> {
+        notation::parse_action(self, mv)
+    }
+
+    fn format_move(&self, mv: Action) -> String {
+        notation::format_action(self, mv)
+    }
+}
+
 impl Display for ArimaaBoard {
     fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
         Display::fmt(&self.state, f)
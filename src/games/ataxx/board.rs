@@ -6,13 +6,22 @@ use rand::Rng;
 
 use crate::board::{
     AllMovesIterator, Alternating, AvailableMovesIterator, Board, BoardMoves, BoardSymmetry, Outcome, Player,
+    ZobristHash,
 };
+use crate::impl_clone_undo_board;
 use crate::symmetry::D4Symmetry;
 use crate::util::bitboard::BitBoard8;
 use crate::util::coord::Coord8;
+use crate::util::zobrist::{zobrist_key, zobrist_table};
 
 pub const MAX_MOVES_SINCE_LAST_COPY: u8 = 100;
 
+// zobrist keys, the tiles are indexed by `64 * player + coord`
+const ZOBRIST_TILES: [u64; 128] = zobrist_table(0);
+const ZOBRIST_GAPS: [u64; 64] = zobrist_table(128);
+const ZOBRIST_SIZE: [u64; 9] = zobrist_table(192);
+const ZOBRIST_NEXT_B: u64 = zobrist_key(201);
+
 #[derive(Clone, Eq, PartialEq, Hash)]
 pub struct AtaxxBoard {
     pub(super) size: u8,
@@ -22,6 +31,7 @@ pub struct AtaxxBoard {
     pub(super) moves_since_last_copy: u8,
     pub(super) next_player: Player,
     pub(super) outcome: Option<Outcome>,
+    pub(super) zobrist: u64,
 }
 
 #[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
@@ -56,8 +66,10 @@ impl AtaxxBoard {
             moves_since_last_copy,
             next_player,
             outcome: None,
+            zobrist: 0,
         };
         result.update_outcome();
+        result.zobrist = result.compute_zobrist();
         result.assert_valid();
         result
     }
@@ -70,7 +82,7 @@ impl AtaxxBoard {
         let tiles_a = BitBoard8::coord(Coord8::from_xy(0, corner)) | BitBoard8::coord(Coord8::from_xy(corner, 0));
         let tiles_b = BitBoard8::coord(Coord8::from_xy(0, 0)) | BitBoard8::coord(Coord8::from_xy(corner, corner));
 
-        AtaxxBoard {
+        let mut result = AtaxxBoard {
             size,
             tiles_a,
             tiles_b,
@@ -78,7 +90,10 @@ impl AtaxxBoard {
             moves_since_last_copy: 0,
             next_player: Player::A,
             outcome: if size == 2 { Some(Outcome::Draw) } else { None },
-        }
+            zobrist: 0,
+        };
+        result.zobrist = result.compute_zobrist();
+        result
     }
 
     pub fn empty(size: u8) -> Self {
@@ -91,6 +106,7 @@ impl AtaxxBoard {
             moves_since_last_copy: 0,
             next_player: Player::A,
             outcome: Some(Outcome::Draw),
+            zobrist: ZOBRIST_SIZE[size as usize],
         }
     }
 
@@ -206,6 +222,25 @@ impl AtaxxBoard {
         let mut clone = self.clone();
         clone.update_outcome();
         assert_eq!(self.outcome, clone.outcome);
+        assert_eq!(self.zobrist, self.compute_zobrist());
+    }
+
+    /// Compute the zobrist key from scratch, the move counter is not included.
+    pub(super) fn compute_zobrist(&self) -> u64 {
+        let mut result = ZOBRIST_SIZE[self.size as usize];
+        for coord in self.tiles_a {
+            result ^= zobrist_tile(Player::A, coord);
+        }
+        for coord in self.tiles_b {
+            result ^= zobrist_tile(Player::B, coord);
+        }
+        for coord in self.gaps {
+            result ^= ZOBRIST_GAPS[coord.index() as usize];
+        }
+        if self.next_player == Player::B {
+            result ^= ZOBRIST_NEXT_B;
+        }
+        result
     }
 
     pub fn map_coord(&self, coord: Coord8, sym: D4Symmetry) -> Coord8 {
@@ -223,6 +258,10 @@ impl AtaxxBoard {
     }
 }
 
+fn zobrist_tile(player: Player, coord: Coord8) -> u64 {
+    ZOBRIST_TILES[64 * player.index() as usize + coord.index() as usize]
+}
+
 impl Move {
     pub fn valid_for_size(self, size: u8) -> bool {
         match self {
@@ -235,6 +274,8 @@ impl Move {
     }
 }
 
+impl_clone_undo_board!(AtaxxBoard);
+
 impl Board for AtaxxBoard {
     type Move = Move;
 
@@ -302,6 +343,7 @@ impl Board for AtaxxBoard {
     fn play(&mut self, mv: Self::Move) {
         assert!(self.is_available_move(mv), "{:?} is not available on {:?}", mv, self);
 
+        let player = self.next_player;
         let (next_tiles, other_tiles) = self.tiles_pov_mut();
 
         let to = match mv {
@@ -310,6 +352,7 @@ impl Board for AtaxxBoard {
                 //   a real move, since otherwise the game would have finished already
                 self.next_player = self.next_player.other();
                 self.moves_since_last_copy += 1;
+                self.zobrist ^= ZOBRIST_NEXT_B;
                 return;
             }
             Move::Copy { to } => to,
@@ -319,11 +362,21 @@ impl Board for AtaxxBoard {
             }
         };
 
+        let mut zobrist_delta = zobrist_tile(player, to) ^ ZOBRIST_NEXT_B;
+        if let Move::Jump { from, .. } = mv {
+            zobrist_delta ^= zobrist_tile(player, from);
+        }
+
         let to = BitBoard8::coord(to);
         let converted = *other_tiles & to.adjacent();
         *next_tiles |= to | converted;
         *other_tiles &= !converted;
 
+        for coord in converted {
+            zobrist_delta ^= zobrist_tile(Player::A, coord) ^ zobrist_tile(Player::B, coord);
+        }
+        self.zobrist ^= zobrist_delta;
+
         self.moves_since_last_copy += 1;
         if let Move::Copy { .. } = mv {
             self.moves_since_last_copy = 0;
@@ -344,12 +397,18 @@ impl Board for AtaxxBoard {
 
 impl Alternating for AtaxxBoard {}
 
+impl ZobristHash for AtaxxBoard {
+    fn zobrist(&self) -> u64 {
+        self.zobrist
+    }
+}
+
 impl BoardSymmetry<AtaxxBoard> for AtaxxBoard {
     type Symmetry = D4Symmetry;
     type CanonicalKey = (u64, u64, u64);
 
     fn map(&self, sym: Self::Symmetry) -> Self {
-        AtaxxBoard {
+        let mut result = AtaxxBoard {
             size: self.size,
             tiles_a: self.map_tiles(self.tiles_a, sym),
             tiles_b: self.map_tiles(self.tiles_b, sym),
@@ -357,7 +416,10 @@ impl BoardSymmetry<AtaxxBoard> for AtaxxBoard {
             moves_since_last_copy: self.moves_since_last_copy,
             next_player: self.next_player,
             outcome: self.outcome,
-        }
+            zobrist: 0,
+        };
+        result.zobrist = result.compute_zobrist();
+        result
     }
 
     fn map_move(&self, sym: Self::Symmetry, mv: Move) -> Move {
@@ -2,7 +2,7 @@ use std::fmt::{Debug, Display, Formatter, Write};
 
 use itertools::Itertools;
 
-use crate::board::Player;
+use crate::board::{BoardNotation, Player};
 use crate::games::ataxx::{AtaxxBoard, Move};
 use crate::util::bitboard::BitBoard8;
 use crate::util::coord::Coord8;
@@ -45,10 +45,12 @@ impl Move {
     }
 
     pub fn from_uai(s: &str) -> Result<Move, InvalidUaiMove> {
+        let is_coord = |c: &[u8]| matches!(c, [b'a'..=b'h', b'1'..=b'8']);
+
         match s {
             "0000" => Ok(Move::Pass),
-            _ if s.len() == 2 => Ok(Move::Copy { to: coord_from_uai(s) }),
-            _ if s.len() == 4 => Ok(Move::Jump {
+            _ if s.len() == 2 && is_coord(s.as_bytes()) => Ok(Move::Copy { to: coord_from_uai(s) }),
+            _ if s.len() == 4 && is_coord(&s.as_bytes()[..2]) && is_coord(&s.as_bytes()[2..]) => Ok(Move::Jump {
                 from: coord_from_uai(&s[..2]),
                 to: coord_from_uai(&s[2..]),
             }),
@@ -127,6 +129,7 @@ impl AtaxxBoard {
         let _ = full_str.parse::<u32>().map_err(|_| err("Invalid full counter"))?;
 
         board.update_outcome();
+        board.zobrist = board.compute_zobrist();
         board.assert_valid();
 
         Ok(board)
@@ -182,6 +185,28 @@ impl AtaxxBoard {
     }
 }
 
+/// Positions are written as FEN and moves in UAI notation.
+impl BoardNotation for AtaxxBoard {
+    type PositionError = InvalidAtaxxFen;
+    type MoveError = InvalidUaiMove;
+
+    fn parse_position(position: &str) -> Result<Self, InvalidAtaxxFen> {
+        AtaxxBoard::from_fen(position)
+    }
+
+    fn format_position(&self) -> String {
+        self.to_fen()
+    }
+
+    fn parse_move(&self, mv: &str) -> Result<Move, InvalidUaiMove> {
+        Move::from_uai(mv)
+    }
+
+    fn format_move(&self, mv: Move) -> String {
+        mv.to_uai()
+    }
+}
+
 impl Debug for AtaxxBoard {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(f, "AtaxxBoard(\"{}\")", self.to_fen())
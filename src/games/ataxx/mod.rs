@@ -1,5 +1,7 @@
 pub use board::*;
 pub use io::*;
+pub use pgn::*;
 
 mod board;
 mod io;
+mod pgn;
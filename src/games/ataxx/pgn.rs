@@ -0,0 +1,281 @@
+use std::fmt::Write;
+
+use crate::board::{Board, Outcome, Player};
+use crate::games::ataxx::{AtaxxBoard, Move};
+use crate::util::bot_game::Replay;
+
+/// A game parsed from PGN by [parse_ataxx_pgn].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct AtaxxPgnGame {
+    /// All tags in the order they appeared, including `FEN` and `Result`.
+    pub tags: Vec<(String, String)>,
+    pub start: AtaxxBoard,
+    pub moves: Vec<Move>,
+    /// The result from the `Result` tag, the movetext or the final board, `None` if the game is unfinished.
+    pub outcome: Option<Outcome>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct InvalidAtaxxPgn {
+    pub pgn: String,
+    pub reason: String,
+}
+
+/// Format a game as PGN with the start position in the `FEN` tag and the moves in UAI notation.
+///
+/// Following the convention used by Ataxx GUIs `x` ([Player::A]) is Black and moves first,
+/// so a win for `x` is written as `0-1`.
+pub fn ataxx_game_to_pgn(
+    black: &str,
+    white: &str,
+    start: &AtaxxBoard,
+    moves: &[Move],
+    outcome: Option<Outcome>,
+) -> String {
+    let mut result = String::new();
+    let f = &mut result;
+    let result_str = outcome_to_pgn(outcome);
+
+    writeln!(f, "[Black \"{}\"]", escape(black)).unwrap();
+    writeln!(f, "[White \"{}\"]", escape(white)).unwrap();
+    writeln!(f, "[FEN \"{}\"]", start.to_fen()).unwrap();
+    writeln!(f, "[Result \"{}\"]", result_str).unwrap();
+    writeln!(f).unwrap();
+
+    let mut board = start.clone();
+    let mut number = 1;
+
+    for (i, &mv) in moves.iter().enumerate() {
+        match board.next_player() {
+            Player::A => write!(f, "{}. ", number).unwrap(),
+            Player::B => {
+                if i == 0 {
+                    write!(f, "{}... ", number).unwrap();
+                }
+                number += 1;
+            }
+        }
+
+        write!(f, "{} ", mv).unwrap();
+        board.play(mv);
+    }
+
+    writeln!(f, "{}", result_str).unwrap();
+    result
+}
+
+impl Replay<AtaxxBoard> {
+    pub fn to_pgn(&self) -> String {
+        let full_l = format!("L: {}", self.debug_l);
+        let full_r = format!("R: {}", self.debug_r);
+
+        let (black, white) = match self.player_l {
+            Player::A => (&full_l, &full_r),
+            Player::B => (&full_r, &full_l),
+        };
+        ataxx_game_to_pgn(black, white, &self.start, &self.moves, Some(self.outcome))
+    }
+}
+
+impl AtaxxPgnGame {
+    /// The value of the first tag with the given key.
+    pub fn tag(&self, key: &str) -> Option<&str> {
+        self.tags.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str())
+    }
+
+    /// The board after playing all moves.
+    pub fn final_board(&self) -> AtaxxBoard {
+        let mut board = self.start.clone();
+        for &mv in &self.moves {
+            board.play(mv);
+        }
+        board
+    }
+}
+
+/// Parse a single PGN game, see [parse_ataxx_pgns].
+pub fn parse_ataxx_pgn(pgn: &str) -> Result<AtaxxPgnGame, InvalidAtaxxPgn> {
+    let mut games = parse_ataxx_pgns(pgn)?;
+    match games.len() {
+        1 => Ok(games.remove(0)),
+        n => Err(InvalidAtaxxPgn {
+            pgn: pgn.to_owned(),
+            reason: format!("expected a single game, got {}", n),
+        }),
+    }
+}
+
+/// Parse all games in a PGN file.
+///
+/// The start position is taken from the `FEN` tag, or the default board if there is none.
+/// Move numbers, comments and annotations are skipped. All moves are checked to be available, and if the final board
+/// is done its outcome must match the result.
+pub fn parse_ataxx_pgns(text: &str) -> Result<Vec<AtaxxPgnGame>, InvalidAtaxxPgn> {
+    let mut games = vec![];
+    let mut tags = vec![];
+    let mut movetext = String::new();
+    let mut game_text = String::new();
+
+    for line in text.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') && !movetext.trim().is_empty() {
+            games.push(parse_game(&game_text, std::mem::take(&mut tags), &movetext)?);
+            movetext.clear();
+            game_text.clear();
+        }
+
+        game_text += line;
+        game_text.push('\n');
+
+        if trimmed.starts_with('[') {
+            let tag = parse_tag(trimmed).ok_or_else(|| InvalidAtaxxPgn {
+                pgn: game_text.clone(),
+                reason: format!("invalid tag '{}'", trimmed),
+            })?;
+            tags.push(tag);
+        } else if !trimmed.starts_with('%') {
+            movetext += trimmed;
+            movetext.push('\n');
+        }
+    }
+
+    if !tags.is_empty() || !movetext.trim().is_empty() {
+        games.push(parse_game(&game_text, tags, &movetext)?);
+    }
+
+    Ok(games)
+}
+
+fn parse_game(pgn: &str, tags: Vec<(String, String)>, movetext: &str) -> Result<AtaxxPgnGame, InvalidAtaxxPgn> {
+    let error = |reason: String| InvalidAtaxxPgn {
+        pgn: pgn.to_owned(),
+        reason,
+    };
+
+    let start = match tags.iter().find(|(k, _)| k == "FEN") {
+        Some((_, fen)) => {
+            AtaxxBoard::from_fen(fen).map_err(|e| error(format!("invalid FEN '{}': {}", fen, e.reason)))?
+        }
+        None => AtaxxBoard::default(),
+    };
+
+    let mut outcome = match tags.iter().find(|(k, _)| k == "Result") {
+        Some((_, result)) => {
+            outcome_from_pgn(result).ok_or_else(|| error(format!("invalid result tag '{}'", result)))?
+        }
+        None => None,
+    };
+
+    let mut board = start.clone();
+    let mut moves = vec![];
+
+    for token in movetext_tokens(movetext).map_err(error)? {
+        if let Some(result) = outcome_from_pgn(&token) {
+            if result != outcome && outcome.is_some() {
+                return Err(error(format!("result '{}' does not match the result tag", token)));
+            }
+            outcome = result;
+            break;
+        }
+
+        let mv = Move::from_uai(&token).map_err(|_| error(format!("invalid move '{}'", token)))?;
+        if board.is_done() || !board.is_available_move(mv) {
+            return Err(error(format!(
+                "move '{}' is not available in {}",
+                token,
+                board.to_fen()
+            )));
+        }
+        board.play(mv);
+        moves.push(mv);
+    }
+
+    if let Some(actual) = board.outcome() {
+        if outcome.is_none() {
+            outcome = Some(actual);
+        } else if outcome != Some(actual) {
+            return Err(error(format!(
+                "result {:?} does not match the final board outcome {:?}",
+                outcome, actual
+            )));
+        }
+    }
+
+    Ok(AtaxxPgnGame {
+        tags,
+        start,
+        moves,
+        outcome,
+    })
+}
+
+/// Split movetext into move and result tokens, skipping move numbers, comments and annotations.
+fn movetext_tokens(movetext: &str) -> Result<Vec<String>, String> {
+    let mut tokens = vec![];
+    let mut chars = movetext.chars();
+    let mut curr = String::new();
+
+    let mut flush = |curr: &mut String| {
+        // strip move numbers like `1.` or `1...`, also if they are attached to the move
+        let token = curr.rsplit('.').next().unwrap_or("");
+        if !token.is_empty() && !token.starts_with('$') {
+            tokens.push(token.to_owned());
+        }
+        curr.clear();
+    };
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' => {
+                flush(&mut curr);
+                if !chars.by_ref().any(|c| c == '}') {
+                    return Err("unterminated comment".to_owned());
+                }
+            }
+            ';' => {
+                flush(&mut curr);
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            }
+            c if c.is_whitespace() => flush(&mut curr),
+            c => curr.push(c),
+        }
+    }
+    flush(&mut curr);
+
+    Ok(tokens)
+}
+
+fn parse_tag(line: &str) -> Option<(String, String)> {
+    let inner = line.strip_prefix('[')?.strip_suffix(']')?;
+    let (key, value) = inner.split_once(' ')?;
+    let value = value.trim().strip_prefix('"')?.strip_suffix('"')?;
+    Some((key.to_owned(), value.replace("\\\"", "\"").replace("\\\\", "\\")))
+}
+
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', " ")
+}
+
+fn outcome_to_pgn(outcome: Option<Outcome>) -> &'static str {
+    match outcome {
+        Some(Outcome::WonBy(Player::A)) => "0-1",
+        Some(Outcome::WonBy(Player::B)) => "1-0",
+        Some(Outcome::Draw) => "1/2-1/2",
+        None => "*",
+    }
+}
+
+/// Parse a result token, the outer option is `None` if the token is not a result.
+fn outcome_from_pgn(s: &str) -> Option<Option<Outcome>> {
+    match s {
+        "0-1" => Some(Some(Outcome::WonBy(Player::A))),
+        "1-0" => Some(Some(Outcome::WonBy(Player::B))),
+        "1/2-1/2" => Some(Some(Outcome::Draw)),
+        "*" => Some(None),
+        _ => None,
+    }
+}
@@ -1,7 +1,7 @@
 use std::borrow::Cow;
 use std::fmt::{Debug, Write};
 use std::fmt::{Display, Formatter};
-use std::hash::Hash;
+use std::hash::{Hash, Hasher};
 use std::ops::ControlFlow;
 use std::str::FromStr;
 
@@ -9,7 +9,10 @@ use chess::{BoardStatus, ChessMove, Color, File, MoveGen, Piece, Square};
 use internal_iterator::{Internal, InternalIterator, IteratorExt};
 use rand::Rng;
 
-use crate::board::{AllMovesIterator, Alternating, Board, BoardMoves, Outcome, Player};
+use crate::board::{
+    play_then_undo_in_place, play_then_undo_wrapped_in_place, AllMovesIterator, Alternating, Board, BoardMoves,
+    BoardNotation, Outcome, Player, UndoBoard, ZobristHash,
+};
 use crate::impl_unit_symmetry_board;
 use crate::util::bot_game::Replay;
 
@@ -19,7 +22,7 @@ pub struct Rules {
     max_moves_without_pawn_or_capture: Option<u16>,
 }
 
-#[derive(Clone, Eq, PartialEq, Hash)]
+#[derive(Clone, Eq, PartialEq)]
 pub struct ChessBoard {
     rules: Rules,
 
@@ -32,6 +35,22 @@ pub struct ChessBoard {
     outcome: Option<Outcome>,
 }
 
+/// The undo token for [ChessBoard], this avoids cloning the history.
+#[derive(Debug, Clone)]
+pub struct ChessUndo {
+    inner: chess::Board,
+    history: HistoryUndo,
+    non_pawn_or_capture_moves: u16,
+    repetitions: u16,
+    outcome: Option<Outcome>,
+}
+
+#[derive(Debug, Clone)]
+enum HistoryUndo {
+    Pushed,
+    Cleared(Vec<chess::Board>),
+}
+
 #[derive(Debug, Clone)]
 pub struct ParseMoveError {
     pub board: ChessBoard,
@@ -132,7 +151,12 @@ impl ChessBoard {
     /// Count how often the given position occurs in this boards history.
     pub fn repetitions_for(&self, board: &chess::Board) -> usize {
         // TODO we only need to check half of the history, depending on the color xor
-        self.history.iter().filter(|&h| h == board).count()
+        // compare the incrementally updated hashes first, which is much cheaper than comparing the full boards
+        let hash = board.get_hash();
+        self.history
+            .iter()
+            .filter(|&h| h.get_hash() == hash && h == board)
+            .count()
     }
 
     pub fn rules(&self) -> Rules {
@@ -211,10 +235,42 @@ impl Board for ChessBoard {
     }
 
     fn play(&mut self, mv: Self::Move) {
+        self.play_undoable(mv);
+    }
+
+    fn play_then_undo<R>(&mut self, mv: Self::Move, f: impl FnOnce(&mut Self) -> R) -> R {
+        play_then_undo_in_place(self, mv, f)
+    }
+
+    fn play_then_undo_wrapped<W, R, F: FnOnce(&mut W) -> R>(
+        outer: &mut W,
+        get: fn(&mut W) -> &mut Self,
+        mv: Self::Move,
+        f: F,
+    ) -> Result<R, F> {
+        play_then_undo_wrapped_in_place(outer, get, mv, f)
+    }
+
+    fn outcome(&self) -> Option<Outcome> {
+        self.outcome
+    }
+
+    fn can_lose_after_move() -> bool {
+        false
+    }
+}
+
+impl UndoBoard for ChessBoard {
+    type Undo = ChessUndo;
+
+    fn play_undoable(&mut self, mv: ChessMove) -> ChessUndo {
         assert!(self.is_available_move(mv), "{:?} is not available on {:?}", mv, self);
 
         // keep track of stats for reversible moves
         let prev = self.inner;
+        let prev_non_pawn_or_capture_moves = self.non_pawn_or_capture_moves;
+        let prev_repetitions = self.repetitions;
+        let prev_outcome = self.outcome;
         let old_side_to_move = prev.side_to_move();
         let old_castle_rights = prev.castle_rights(old_side_to_move);
 
@@ -237,11 +293,12 @@ impl Board for ChessBoard {
 
         // update history
         let reset_history = was_capture || was_pawn_move || removed_castle || self.rules.max_repetitions.is_none();
-        if reset_history {
-            self.history.clear()
+        let history = if reset_history {
+            HistoryUndo::Cleared(std::mem::take(&mut self.history))
         } else {
-            self.history.push(prev)
-        }
+            self.history.push(prev);
+            HistoryUndo::Pushed
+        };
 
         // update repetition counter based on history
         self.repetitions = self.repetitions_for(&self.inner) as u16;
@@ -255,19 +312,74 @@ impl Board for ChessBoard {
                 BoardStatus::Stalemate => Some(Outcome::Draw),
                 BoardStatus::Checkmate => Some(Outcome::WonBy(self.next_player().other())),
             }
+        };
+
+        ChessUndo {
+            inner: prev,
+            history,
+            non_pawn_or_capture_moves: prev_non_pawn_or_capture_moves,
+            repetitions: prev_repetitions,
+            outcome: prev_outcome,
         }
     }
 
-    fn outcome(&self) -> Option<Outcome> {
-        self.outcome
+    fn undo(&mut self, undo: ChessUndo) {
+        self.inner = undo.inner;
+        match undo.history {
+            HistoryUndo::Pushed => {
+                self.history.pop();
+            }
+            HistoryUndo::Cleared(history) => self.history = history,
+        }
+        self.non_pawn_or_capture_moves = undo.non_pawn_or_capture_moves;
+        self.repetitions = undo.repetitions;
+        self.outcome = undo.outcome;
     }
+}
 
-    fn can_lose_after_move() -> bool {
-        false
+impl Alternating for ChessBoard {}
+
+/// Positions are written as FEN and parsed with the default rules and without history.
+/// Moves are written in UCI notation, and both UCI and SAN moves are parsed, see [ChessBoard::parse_move].
+impl BoardNotation for ChessBoard {
+    type PositionError = chess::Error;
+    type MoveError = ParseMoveError;
+
+    fn parse_position(position: &str) -> Result<Self, chess::Error> {
+        let inner = chess::Board::from_str(position)?;
+        Ok(ChessBoard::new_without_history(inner, Rules::default()))
+    }
+
+    fn format_position(&self) -> String {
+        self.inner.to_string()
+    }
+
+    fn parse_move(&self, mv: &str) -> Result<ChessMove, ParseMoveError> {
+        ChessBoard::parse_move(self, mv)
+    }
+
+    fn format_move(&self, mv: ChessMove) -> String {
+        mv.to_string()
     }
 }
 
-impl Alternating for ChessBoard {}
+/// The key of the current position as maintained by the chess crate, the history and counters are not included.
+impl ZobristHash for ChessBoard {
+    fn zobrist(&self) -> u64 {
+        self.inner.get_hash()
+    }
+}
+
+/// Only hashes the current position and counters and not the full history, equal boards still have equal hashes.
+impl Hash for ChessBoard {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.rules.hash(state);
+        state.write_u64(self.inner.get_hash());
+        self.non_pawn_or_capture_moves.hash(state);
+        self.repetitions.hash(state);
+        self.outcome.hash(state);
+    }
+}
 
 impl_unit_symmetry_board!(ChessBoard);
 
@@ -0,0 +1,455 @@
+use std::fmt::{Debug, Display, Formatter};
+use std::hash::{Hash, Hasher};
+use std::ops::Range;
+
+use internal_iterator::{Internal, IteratorExt};
+
+use crate::board::{
+    play_then_undo_in_place, Alternating, Board, BoardMoves, BoardNotation, BoardSymmetry, BruteforceMoveIterator,
+    InvalidNotation, Outcome, Player, UndoBoard, ZobristHash,
+};
+use crate::symmetry::D1Symmetry;
+use crate::util::bits::BitIter;
+use crate::util::zobrist::{zobrist_key, zobrist_table};
+
+/// The Connect-N game on a `W`x`H` board, where `N` tiles in a row win.
+/// The standard game is available as [Connect4](crate::games::connect4::Connect4).
+///
+/// The bitboard implementation is based on <http://blog.gamesolver.org/solving-connect-four/06-bitboard/>,
+/// with each column taking `H + 1` bits of a `u128`. The board size is checked at compile time,
+/// it must satisfy `W * (H + 1) <= 128` and `W <= 36` so each column can be written as a single character.
+#[derive(Clone, Eq, PartialEq)]
+pub struct ConnectN<const W: u8, const H: u8, const N: u8> {
+    tiles_next: u128,
+    tiles_occupied: u128,
+    outcome: Option<Outcome>,
+    zobrist: u64,
+}
+
+// zobrist keys, the tiles are indexed by `128 * player + bit`
+const ZOBRIST_TILES: [u64; 256] = zobrist_table(0);
+const ZOBRIST_NEXT_B: u64 = zobrist_key(256);
+
+impl<const W: u8, const H: u8, const N: u8> ConnectN<W, H, N> {
+    pub const WIDTH: u8 = W;
+    pub const HEIGHT: u8 = H;
+    pub const TILES: u8 = W * H;
+
+    /// The number of bits used per column, including the empty bit on top.
+    const STRIDE: u8 = H + 1;
+    /// The bottom tile of each column.
+    const BOTTOM: u128 = bottom_mask(W, H + 1);
+    /// All tiles in the bottom column.
+    const COLUMN: u128 = (1 << H) - 1;
+
+    const VALID: () = assert!(
+        W > 0 && H > 0 && N > 0 && W <= 36 && W as u32 * (H as u32 + 1) <= 128,
+        "board size does not fit in the bitboard or the notation"
+    );
+
+    #[allow(clippy::let_unit_value)]
+    fn empty() -> Self {
+        let () = Self::VALID;
+        ConnectN {
+            tiles_next: 0,
+            tiles_occupied: 0,
+            outcome: None,
+            zobrist: 0,
+        }
+    }
+
+    /// Return a 128-bit hash of this board, with the following properties:
+    /// * different boards have different hashes
+    /// * the hash is never zero
+    pub fn perfect_hash(&self) -> u128 {
+        let value = self.tiles_next + self.tiles_occupied + Self::BOTTOM;
+        debug_assert!(value != 0);
+        value
+    }
+
+    /// The number of moves already played.
+    pub fn game_length(&self) -> u32 {
+        self.tiles_occupied.count_ones()
+    }
+
+    /// Parse a board in the format of [ConnectN::to_fen].
+    /// The tiles are checked to be reachable: they can't float, the tile counts must match
+    /// and only the last player can have won.
+    pub fn from_fen(fen: &str) -> Result<Self, InvalidNotation> {
+        let error = |reason: &str| InvalidNotation::new(fen, reason);
+
+        let rows = fen.split('/').collect::<Vec<_>>();
+        if rows.len() != H as usize || rows.iter().any(|row| row.chars().count() != W as usize) {
+            return Err(error(&format!("expected {} rows of {} tiles", H, W)));
+        }
+
+        let mut tiles_a = 0;
+        let mut tiles_b = 0;
+        for (i, row) in rows.iter().enumerate() {
+            let y = H - 1 - i as u8;
+            for (x, c) in row.chars().enumerate() {
+                match c {
+                    'x' => tiles_a |= Self::mask(x as u8, y),
+                    'o' => tiles_b |= Self::mask(x as u8, y),
+                    '.' => {}
+                    _ => return Err(error("invalid tile")),
+                }
+            }
+        }
+
+        let tiles_occupied = tiles_a | tiles_b;
+        for x in 0..W {
+            for y in 1..H {
+                if Self::get(tiles_occupied, x, y) && !Self::get(tiles_occupied, x, y - 1) {
+                    return Err(error("floating tile"));
+                }
+            }
+        }
+
+        let tiles_next = match tiles_a.count_ones() as i32 - tiles_b.count_ones() as i32 {
+            0 => tiles_a,
+            1 => tiles_b,
+            _ => return Err(error("invalid number of tiles for each player")),
+        };
+
+        let outcome = match (Self::has_line(tiles_a), Self::has_line(tiles_b)) {
+            (true, true) => return Err(error("both players have a line")),
+            (true, false) if tiles_next != tiles_b => return Err(error("A won but B moved last")),
+            (false, true) if tiles_next != tiles_a => return Err(error("B won but A moved last")),
+            (true, false) => Some(Outcome::WonBy(Player::A)),
+            (false, true) => Some(Outcome::WonBy(Player::B)),
+            (false, false) if tiles_occupied.count_ones() == Self::TILES as u32 => Some(Outcome::Draw),
+            (false, false) => None,
+        };
+
+        let mut board = Self::empty();
+        board.tiles_next = tiles_next;
+        board.tiles_occupied = tiles_occupied;
+        board.outcome = outcome;
+        board.zobrist = board.compute_zobrist();
+        Ok(board)
+    }
+
+    /// Format the board as the rows from top to bottom separated by `/`, with `x` for [Player::A],
+    /// `o` for [Player::B] and `.` for empty tiles. The next player follows from the number of tiles.
+    pub fn to_fen(&self) -> String {
+        let (tiles_a, tiles_b) = self.tiles_a_b();
+
+        let rows = (0..H).rev().map(|y| {
+            (0..W)
+                .map(|x| match (Self::get(tiles_a, x, y), Self::get(tiles_b, x, y)) {
+                    (true, _) => 'x',
+                    (_, true) => 'o',
+                    _ => '.',
+                })
+                .collect::<String>()
+        });
+        rows.collect::<Vec<_>>().join("/")
+    }
+
+    /// Build a board by playing a sequence of columns from the start position, eg. `3324`.
+    /// Columns are written as single digits, continuing with letters for boards wider than 10.
+    pub fn from_moves(moves: &str) -> Result<Self, InvalidNotation> {
+        let mut board = Self::empty();
+        for c in moves.chars() {
+            let error = |reason: &str| InvalidNotation::new(moves, format!("{} at move '{}'", reason, c));
+
+            let mv = match c.to_digit(36) {
+                Some(col) if col < W as u32 => col as u8,
+                _ => return Err(error("invalid column")),
+            };
+            if board.is_done() {
+                return Err(error("game already ended"));
+            }
+            if !board.is_available_move(mv) {
+                return Err(error("column is full"));
+            }
+            board.play(mv);
+        }
+        Ok(board)
+    }
+
+    /// Compute the zobrist key from scratch.
+    fn compute_zobrist(&self) -> u64 {
+        let tiles_curr = self.tiles_next ^ self.tiles_occupied;
+        let (next, curr) = (self.next_player(), self.next_player().other());
+
+        let mut result = 0;
+        for bit in BitIter::new(self.tiles_next) {
+            result ^= zobrist_tile(next, bit);
+        }
+        for bit in BitIter::new(tiles_curr) {
+            result ^= zobrist_tile(curr, bit);
+        }
+        if next == Player::B {
+            result ^= ZOBRIST_NEXT_B;
+        }
+        result
+    }
+
+    fn tiles_a_b(&self) -> (u128, u128) {
+        match self.next_player() {
+            Player::A => (self.tiles_next, self.tiles_next ^ self.tiles_occupied),
+            Player::B => (self.tiles_next ^ self.tiles_occupied, self.tiles_next),
+        }
+    }
+
+    /// Whether `tiles` contains `N` in a row in any direction.
+    fn has_line(tiles: u128) -> bool {
+        [1, Self::STRIDE, Self::STRIDE + 1, Self::STRIDE - 1]
+            .iter()
+            .any(|&shift| {
+                let mut m = tiles;
+                for _ in 1..N {
+                    m &= m << shift;
+                }
+                m != 0
+            })
+    }
+
+    fn mask(col: u8, row: u8) -> u128 {
+        1 << (row + col * Self::STRIDE)
+    }
+
+    fn get(tiles: u128, col: u8, row: u8) -> bool {
+        tiles & Self::mask(col, row) != 0
+    }
+
+    fn mirror(tiles: u128) -> u128 {
+        (0..W).fold(0, |acc, col| {
+            let column = (tiles >> (col * Self::STRIDE)) & Self::COLUMN;
+            acc | (column << ((W - 1 - col) * Self::STRIDE))
+        })
+    }
+}
+
+const fn bottom_mask(width: u8, stride: u8) -> u128 {
+    let mut result = 0;
+    let mut col = 0;
+    while col < width {
+        result |= 1 << (col as u32 * stride as u32);
+        col += 1;
+    }
+    result
+}
+
+fn zobrist_tile(player: Player, bit: u8) -> u64 {
+    ZOBRIST_TILES[128 * player.index() as usize + bit as usize]
+}
+
+impl<const W: u8, const H: u8, const N: u8> Default for ConnectN<W, H, N> {
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+/// Moves can be undone cheaply, so the undo token is simply the column of the move.
+impl<const W: u8, const H: u8, const N: u8> UndoBoard for ConnectN<W, H, N> {
+    type Undo = u8;
+
+    fn play_undoable(&mut self, mv: u8) -> u8 {
+        self.play(mv);
+        mv
+    }
+
+    fn undo(&mut self, mv: u8) {
+        let column = self.tiles_occupied & (Self::COLUMN << (mv * Self::STRIDE));
+        assert!(column != 0, "column {} is empty on {:?}", mv, self);
+        let bit = (127 - column.leading_zeros()) as u8;
+
+        self.tiles_occupied ^= 1 << bit;
+        self.tiles_next ^= self.tiles_occupied;
+        self.outcome = None;
+        self.zobrist ^= zobrist_tile(self.next_player(), bit) ^ ZOBRIST_NEXT_B;
+    }
+}
+
+impl<const W: u8, const H: u8, const N: u8> Board for ConnectN<W, H, N> {
+    type Move = u8;
+
+    fn next_player(&self) -> Player {
+        if self.tiles_occupied.count_ones().is_multiple_of(2) {
+            Player::A
+        } else {
+            Player::B
+        }
+    }
+
+    fn is_available_move(&self, mv: Self::Move) -> bool {
+        assert!(!self.is_done());
+        assert!(mv < W);
+        self.tiles_occupied & Self::mask(mv, H - 1) == 0
+    }
+
+    fn play(&mut self, mv: Self::Move) {
+        assert!(self.is_available_move(mv), "{:?} is not available on {:?}", mv, self);
+        let curr_player = self.next_player();
+
+        // play move
+        let prev_occupied = self.tiles_occupied;
+        self.tiles_next ^= self.tiles_occupied;
+        self.tiles_occupied |= self.tiles_occupied + Self::mask(mv, 0);
+
+        let bit = (self.tiles_occupied ^ prev_occupied).trailing_zeros() as u8;
+        self.zobrist ^= zobrist_tile(curr_player, bit) ^ ZOBRIST_NEXT_B;
+
+        //update outcome
+        let tiles_curr = self.tiles_next ^ self.tiles_occupied;
+        if Self::has_line(tiles_curr) {
+            self.outcome = Some(Outcome::WonBy(curr_player));
+        } else if self.tiles_occupied.count_ones() == Self::TILES as u32 {
+            self.outcome = Some(Outcome::Draw)
+        }
+    }
+
+    fn play_then_undo<R>(&mut self, mv: Self::Move, f: impl FnOnce(&mut Self) -> R) -> R {
+        play_then_undo_in_place(self, mv, f)
+    }
+
+    fn outcome(&self) -> Option<Outcome> {
+        self.outcome
+    }
+
+    fn can_lose_after_move() -> bool {
+        false
+    }
+}
+
+impl<const W: u8, const H: u8, const N: u8> Alternating for ConnectN<W, H, N> {}
+
+impl<const W: u8, const H: u8, const N: u8> ZobristHash for ConnectN<W, H, N> {
+    fn zobrist(&self) -> u64 {
+        self.zobrist
+    }
+}
+
+/// Positions are written in the format of [ConnectN::to_fen], a sequence of moves as in [ConnectN::from_moves]
+/// is also accepted when parsing. Moves are written as a single column character like in [ConnectN::from_moves].
+impl<const W: u8, const H: u8, const N: u8> BoardNotation for ConnectN<W, H, N> {
+    type PositionError = InvalidNotation;
+    type MoveError = InvalidNotation;
+
+    fn parse_position(position: &str) -> Result<Self, InvalidNotation> {
+        if position.contains('/') {
+            Self::from_fen(position)
+        } else {
+            Self::from_moves(position)
+        }
+    }
+
+    fn format_position(&self) -> String {
+        self.to_fen()
+    }
+
+    fn parse_move(&self, mv: &str) -> Result<u8, InvalidNotation> {
+        let mut chars = mv.chars();
+        match (chars.next().and_then(|c| c.to_digit(36)), chars.next()) {
+            (Some(col), None) if col < W as u32 => Ok(col as u8),
+            _ => Err(InvalidNotation::new(mv, "expected a column")),
+        }
+    }
+
+    fn format_move(&self, mv: u8) -> String {
+        std::char::from_digit(mv as u32, 36).unwrap().to_string()
+    }
+}
+
+impl<'a, const W: u8, const H: u8, const N: u8> BoardMoves<'a, ConnectN<W, H, N>> for ConnectN<W, H, N> {
+    type AllMovesIterator = Internal<Range<u8>>;
+    type AvailableMovesIterator = BruteforceMoveIterator<'a, ConnectN<W, H, N>>;
+
+    fn all_possible_moves() -> Self::AllMovesIterator {
+        (0..W).into_internal()
+    }
+
+    fn available_moves(&'a self) -> Self::AvailableMovesIterator {
+        BruteforceMoveIterator::new(self)
+    }
+}
+
+impl<const W: u8, const H: u8, const N: u8> BoardSymmetry<ConnectN<W, H, N>> for ConnectN<W, H, N> {
+    type Symmetry = D1Symmetry;
+    type CanonicalKey = (u128, u128);
+
+    fn map(&self, sym: Self::Symmetry) -> Self {
+        if sym.mirror {
+            let mut result = ConnectN {
+                tiles_next: Self::mirror(self.tiles_next),
+                tiles_occupied: Self::mirror(self.tiles_occupied),
+                outcome: self.outcome,
+                zobrist: 0,
+            };
+            result.zobrist = result.compute_zobrist();
+            result
+        } else {
+            self.clone()
+        }
+    }
+
+    fn map_move(&self, sym: Self::Symmetry, mv: u8) -> u8 {
+        assert!(mv < W);
+        if sym.mirror {
+            W - mv - 1
+        } else {
+            mv
+        }
+    }
+
+    fn canonical_key(&self) -> Self::CanonicalKey {
+        (self.tiles_next, self.tiles_occupied)
+    }
+}
+
+impl<const W: u8, const H: u8, const N: u8> Debug for ConnectN<W, H, N> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let (sep, line) = if f.alternate() { ("\n    ", "\n") } else { (" ", "") };
+
+        write!(
+            f,
+            "ConnectN<{}, {}, {}> {{{}tiles_next: {:x},{}tiles_occupied: {:x},{}next_player: {:?},{}outcome: {:?}{}}}",
+            W,
+            H,
+            N,
+            sep,
+            self.tiles_next,
+            sep,
+            self.tiles_occupied,
+            sep,
+            self.next_player(),
+            sep,
+            self.outcome,
+            line,
+        )
+    }
+}
+
+impl<const W: u8, const H: u8, const N: u8> Display for ConnectN<W, H, N> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let (tiles_a, tiles_b) = self.tiles_a_b();
+
+        for row in (0..H).rev() {
+            for col in 0..W {
+                let c = match (Self::get(tiles_a, col, row), Self::get(tiles_b, col, row)) {
+                    (true, false) => 'a',
+                    (false, true) => 'b',
+                    (false, false) => '.',
+                    _ => unreachable!(),
+                };
+
+                write!(f, "{}", c)?;
+            }
+            if row == H / 2 {
+                write!(f, "    {}", self.next_player().to_char())?;
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<const W: u8, const H: u8, const N: u8> Hash for ConnectN<W, H, N> {
+    fn hash<S: Hasher>(&self, state: &mut S) {
+        state.write_u128(self.perfect_hash());
+    }
+}
@@ -29,7 +29,8 @@ use internal_iterator::{Internal, IteratorExt};
 use nom::error::Error;
 use nom::Finish;
 
-use crate::board::{Alternating, Board, BoardMoves, Outcome, Player};
+use crate::board::{Alternating, Board, BoardMoves, BoardNotation, InvalidNotation, Outcome, Player};
+use crate::impl_clone_undo_board;
 use crate::impl_unit_symmetry_board;
 
 mod parse {
@@ -84,6 +85,23 @@ enum Tree {
     Node(Vec<Tree>),
 }
 
+/// Formats in the same syntax that is parsed.
+impl fmt::Display for Tree {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Tree::Outcome(Outcome::WonBy(player)) => write!(f, "{}", player.to_char()),
+            Tree::Outcome(Outcome::Draw) => write!(f, "="),
+            Tree::Node(children) => {
+                write!(f, "(")?;
+                for child in children {
+                    write!(f, "{}", child)?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
+
 impl Tree {
     fn choose(&mut self, i: usize) {
         if let Tree::Node(boards) = self {
@@ -116,6 +134,8 @@ impl fmt::Display for DummyGame {
     }
 }
 
+impl_clone_undo_board!(DummyGame);
+
 impl Board for DummyGame {
     type Move = usize;
 
@@ -154,6 +174,40 @@ impl Alternating for DummyGame {}
 
 impl_unit_symmetry_board!(DummyGame);
 
+/// Positions are written as the tree followed by the next player, eg. `(AB=) B`.
+/// The player is optional when parsing and defaults to `A`. Moves are written as the index of the child.
+impl BoardNotation for DummyGame {
+    type PositionError = InvalidNotation;
+    type MoveError = InvalidNotation;
+
+    fn parse_position(position: &str) -> Result<Self, InvalidNotation> {
+        let (tree, player) = match position.split_once(' ') {
+            None => (position, Player::A),
+            Some((tree, "A")) => (tree, Player::A),
+            Some((tree, "B")) => (tree, Player::B),
+            Some(_) => return Err(InvalidNotation::new(position, "invalid player")),
+        };
+
+        let state = tree
+            .parse()
+            .map_err(|e: Error<String>| InvalidNotation::new(position, format!("invalid tree at '{}'", e.input)))?;
+        Ok(DummyGame { state, player })
+    }
+
+    fn format_position(&self) -> String {
+        format!("{} {}", self.state, self.player.to_char())
+    }
+
+    fn parse_move(&self, mv: &str) -> Result<usize, InvalidNotation> {
+        mv.parse()
+            .map_err(|_| InvalidNotation::new(mv, "expected a child index"))
+    }
+
+    fn format_move(&self, mv: usize) -> String {
+        mv.to_string()
+    }
+}
+
 impl<'a> BoardMoves<'a, DummyGame> for DummyGame {
     type AllMovesIterator = Internal<std::ops::RangeFrom<usize>>;
     type AvailableMovesIterator = Internal<std::ops::Range<usize>>;
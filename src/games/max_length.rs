@@ -2,7 +2,9 @@ use std::fmt::{Display, Formatter};
 
 use rand::Rng;
 
-use crate::board::{Board, BoardMoves, BoardSymmetry, Outcome, Player};
+use crate::board::{
+    Board, BoardMoves, BoardNotation, BoardSymmetry, InvalidNotation, Outcome, Player, UndoBoard, ZobristHash,
+};
 
 /// A wrapper around an existing board that has the same behaviour,
 /// except that the outcome is a draw after a fixed number of moves has been played.
@@ -54,6 +56,26 @@ impl<B: Board> Board for MaxMovesBoard<B> {
         self.moves += 1;
     }
 
+    fn play_then_undo<R>(&mut self, mv: Self::Move, f: impl FnOnce(&mut Self) -> R) -> R {
+        assert!(!self.is_done());
+        let f = |board: &mut Self| {
+            board.moves += 1;
+            let result = f(board);
+            board.moves -= 1;
+            result
+        };
+
+        // play in place if the inner board supports it, otherwise fall back to a clone
+        match B::play_then_undo_wrapped(self, inner_mut, mv, f) {
+            Ok(result) => result,
+            Err(f) => {
+                let mut child = self.clone();
+                child.inner.play(mv);
+                f(&mut child)
+            }
+        }
+    }
+
     fn outcome(&self) -> Option<Outcome> {
         if self.moves == self.max_moves {
             Some(Outcome::Draw)
@@ -67,6 +89,74 @@ impl<B: Board> Board for MaxMovesBoard<B> {
     }
 }
 
+fn inner_mut<B: Board>(board: &mut MaxMovesBoard<B>) -> &mut B {
+    &mut board.inner
+}
+
+/// The move counter is not included, like other counters that only matter for draw rules.
+impl<B: ZobristHash> ZobristHash for MaxMovesBoard<B> {
+    fn zobrist(&self) -> u64 {
+        self.inner.zobrist()
+    }
+}
+
+impl<B: UndoBoard> UndoBoard for MaxMovesBoard<B> {
+    type Undo = B::Undo;
+
+    fn play_undoable(&mut self, mv: Self::Move) -> Self::Undo {
+        assert!(!self.is_done());
+        self.moves += 1;
+        self.inner.play_undoable(mv)
+    }
+
+    fn undo(&mut self, undo: Self::Undo) {
+        self.inner.undo(undo);
+        self.moves -= 1;
+    }
+}
+
+/// Positions are written as the inner position followed by `moves/max_moves`, eg. `<inner> 3/100`.
+/// Moves are written in the notation of the inner board.
+impl<B: BoardNotation> BoardNotation for MaxMovesBoard<B> {
+    type PositionError = InvalidNotation;
+    type MoveError = B::MoveError;
+
+    fn parse_position(position: &str) -> Result<Self, InvalidNotation> {
+        let error = |reason: &str| InvalidNotation::new(position, reason);
+
+        let (inner, counter) = position
+            .rsplit_once(' ')
+            .ok_or_else(|| error("expected inner position and move counter"))?;
+        let (moves, max_moves) = counter
+            .split_once('/')
+            .ok_or_else(|| error("expected move counter as moves/max_moves"))?;
+        let moves = moves.parse::<u64>().map_err(|_| error("invalid number of moves"))?;
+        let max_moves = max_moves.parse::<u64>().map_err(|_| error("invalid max moves"))?;
+        if moves > max_moves {
+            return Err(error("more moves than max moves"));
+        }
+
+        let inner = B::parse_position(inner).map_err(|e| error(&format!("invalid inner position: {:?}", e)))?;
+        Ok(MaxMovesBoard {
+            inner,
+            moves,
+            max_moves,
+        })
+    }
+
+    fn format_position(&self) -> String {
+        format!("{} {}/{}", self.inner.format_position(), self.moves, self.max_moves)
+    }
+
+    fn parse_move(&self, mv: &str) -> Result<Self::Move, B::MoveError> {
+        self.inner.parse_move(mv)
+    }
+
+    fn format_move(&self, mv: Self::Move) -> String {
+        self.inner.format_move(mv)
+    }
+}
+
 impl<B: Board> BoardSymmetry<MaxMovesBoard<B>> for MaxMovesBoard<B> {
     type Symmetry = B::Symmetry;
     type CanonicalKey = B::CanonicalKey;
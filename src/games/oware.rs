@@ -19,7 +19,11 @@ use internal_iterator::{Internal, IteratorExt};
 use itertools::join;
 use std::fmt::{Debug, Display, Formatter};
 
-use crate::board::{Alternating, Board, BoardMoves, BruteforceMoveIterator, Outcome, Player};
+use crate::board::{
+    Alternating, Board, BoardMoves, BoardNotation, BruteforceMoveIterator, InvalidNotation, Outcome, Player, UndoBoard,
+    ZobristHash,
+};
+use crate::util::zobrist::zobrist_key;
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub struct OwareBoard<const PITS_PER_PLAYER: usize> {
@@ -28,6 +32,7 @@ pub struct OwareBoard<const PITS_PER_PLAYER: usize> {
     next_player: Player,
     outcome: Option<Outcome>,
     init_seeds: u8,
+    zobrist: u64,
 }
 
 impl<const P: usize> Default for OwareBoard<P> {
@@ -38,13 +43,101 @@ impl<const P: usize> Default for OwareBoard<P> {
 
 impl<const PITS: usize> OwareBoard<PITS> {
     pub fn new(init_seeds: u8) -> Self {
-        Self {
+        let mut result = Self {
             pits: [[init_seeds; PITS]; 2],
             init_seeds,
             scores: Default::default(),
             next_player: Player::A,
             outcome: None,
+            zobrist: 0,
+        };
+        result.zobrist = result.compute_zobrist();
+        result
+    }
+
+    /// Parse a board in the format of [OwareBoard::to_fen].
+    /// The initial number of seeds per pit follows from the total number of seeds, which must divide evenly.
+    /// If the game is not done the next player must have a move available.
+    pub fn from_fen(fen: &str) -> Result<Self, InvalidNotation> {
+        let error = |reason: &str| InvalidNotation::new(fen, reason);
+
+        let parts = fen.split(' ').collect::<Vec<_>>();
+        let (pits_str, score_a, score_b, next) = match parts.as_slice() {
+            &[pits, score_a, score_b, next] => (pits, score_a, score_b, next),
+            _ => return Err(error("expected pits, two scores and the next player")),
+        };
+
+        let mut pits = [[0; PITS]; 2];
+        let sides = pits_str.split('/').collect::<Vec<_>>();
+        if sides.len() != 2 {
+            return Err(error("expected pits for two players"));
         }
+        for (side, side_str) in sides.iter().enumerate() {
+            let seeds = side_str
+                .split(',')
+                .map(|s| s.parse::<u8>())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_| error("invalid seed count"))?;
+            if seeds.len() != PITS {
+                return Err(error("wrong number of pits"));
+            }
+            pits[side].copy_from_slice(&seeds);
+        }
+
+        let scores = [
+            score_a.parse::<u8>().map_err(|_| error("invalid score"))?,
+            score_b.parse::<u8>().map_err(|_| error("invalid score"))?,
+        ];
+        let next_player = match next {
+            "A" => Player::A,
+            "B" => Player::B,
+            _ => return Err(error("invalid next player")),
+        };
+
+        let total = pits
+            .iter()
+            .flatten()
+            .chain(scores.iter())
+            .map(|&x| x as u32)
+            .sum::<u32>();
+        if total == 0 || total % (2 * PITS as u32) != 0 {
+            return Err(error("total number of seeds must be a multiple of the number of pits"));
+        }
+        if total > u8::MAX as u32 {
+            return Err(error("too many seeds"));
+        }
+
+        let mut board = OwareBoard {
+            pits,
+            scores,
+            next_player,
+            outcome: None,
+            init_seeds: (total / (2 * PITS as u32)) as u8,
+            zobrist: 0,
+        };
+        board.outcome = board.outcome_from_scores();
+        board.zobrist = board.compute_zobrist();
+
+        if !board.is_done() && !(0..PITS).any(|mv| board.is_available_move(mv)) {
+            return Err(error("no available moves"));
+        }
+        Ok(board)
+    }
+
+    /// Format the board as `pits_a/pits_b score_a score_b next`, with the pits of each player separated by commas
+    /// in move order and `next` either `A` or `B`, eg. `4,4,4,4,4,4/4,4,4,4,4,4 0 0 A` for the start position.
+    pub fn to_fen(&self) -> String {
+        format!(
+            "{}/{} {} {} {}",
+            join(self.pits[0].iter(), ","),
+            join(self.pits[1].iter(), ","),
+            self.scores[0],
+            self.scores[1],
+            match self.next_player {
+                Player::A => "A",
+                Player::B => "B",
+            }
+        )
     }
 
     pub fn score(&self, player: Player) -> u8 {
@@ -87,11 +180,50 @@ impl<const PITS: usize> OwareBoard<PITS> {
     }
 
     fn capture(&mut self, idx: usize) -> u8 {
-        let seeds = self.pits[usize::from(idx >= PITS) ^ self.next_player.index() as usize][idx % PITS];
-        self.pits[usize::from(idx >= PITS) ^ self.next_player.index() as usize][idx % PITS] = 0;
+        let side = usize::from(idx >= PITS) ^ self.next_player.index() as usize;
+        let seeds = self.pits[side][idx % PITS];
+        self.set_pit(side, idx % PITS, 0);
         seeds
     }
 
+    // all changes to the pits and scores go through these functions to keep the zobrist key up to date
+    fn set_pit(&mut self, side: usize, pit: usize, seeds: u8) {
+        let prev = std::mem::replace(&mut self.pits[side][pit], seeds);
+        self.zobrist ^= zobrist_pit::<PITS>(side, pit, prev) ^ zobrist_pit::<PITS>(side, pit, seeds);
+    }
+
+    fn add_score(&mut self, side: usize, seeds: u8) {
+        let prev = self.scores[side];
+        self.scores[side] += seeds;
+        self.zobrist ^= zobrist_score(side, prev) ^ zobrist_score(side, self.scores[side]);
+    }
+
+    /// Compute the zobrist key from scratch.
+    fn compute_zobrist(&self) -> u64 {
+        let mut result = 0;
+        for side in 0..2 {
+            for pit in 0..PITS {
+                result ^= zobrist_pit::<PITS>(side, pit, self.pits[side][pit]);
+            }
+            result ^= zobrist_score(side, self.scores[side]);
+        }
+        if self.next_player == Player::B {
+            result ^= ZOBRIST_NEXT_B;
+        }
+        result
+    }
+
+    fn outcome_from_scores(&self) -> Option<Outcome> {
+        let draw = self.scores.iter().all(|&x| x == PITS as u8 * self.init_seeds);
+
+        self.scores
+            .iter()
+            .position(|&score| score > PITS as u8 * self.init_seeds)
+            .map_or(if draw { Some(Outcome::Draw) } else { None }, |pl| {
+                Some(Outcome::WonBy(Player::BOTH[pl]))
+            })
+    }
+
     fn can_overflow(&self, mv: usize) -> bool {
         mv % PITS + self.at(mv) as usize >= PITS
     }
@@ -107,6 +239,21 @@ impl<const PITS: usize> OwareBoard<PITS> {
     }
 }
 
+/// The board is small, so the undo token is simply the previous board.
+impl<const PITS: usize> UndoBoard for OwareBoard<PITS> {
+    type Undo = Self;
+
+    fn play_undoable(&mut self, mv: usize) -> Self {
+        let prev = self.clone();
+        self.play(mv);
+        prev
+    }
+
+    fn undo(&mut self, undo: Self) {
+        *self = undo;
+    }
+}
+
 impl<const PITS: usize> Board for OwareBoard<PITS> {
     type Move = usize;
 
@@ -136,13 +283,15 @@ impl<const PITS: usize> Board for OwareBoard<PITS> {
         while seeds > 0 {
             idx = (idx + usize::from((idx + 1) % (PITS * 2) == mv) + 1) % (PITS * 2);
             seeds -= 1;
-            self.pits[usize::from(idx >= PITS) ^ player][idx % PITS] += 1;
+            let side = usize::from(idx >= PITS) ^ player;
+            self.set_pit(side, idx % PITS, self.pits[side][idx % PITS] + 1);
         }
 
         // capture
         if !self.grand_slam(idx) {
             while idx >= PITS && matches!(self.at(idx), 2 | 3) {
-                self.scores[player] += self.capture(idx);
+                let captured = self.capture(idx);
+                self.add_score(player, captured);
                 idx = (idx + (PITS * 2) - 1) % (PITS * 2);
             }
         }
@@ -150,14 +299,15 @@ impl<const PITS: usize> Board for OwareBoard<PITS> {
         // No move endgame
         if self.pl_pits().all(|x| self.at(x) == 0) && !self.opp_pits().any(|x| self.can_overflow(x)) {
             self.opp_pits().for_each(|x| {
-                self.scores[(player + 1) % 2] += self.capture(x);
+                let captured = self.capture(x);
+                self.add_score((player + 1) % 2, captured);
             })
         }
 
         // Stalemate endgame
         if self.is_stalemate() {
             (0..PITS * 2).for_each(|x| _ = self.capture(x));
-            self.scores.iter_mut().for_each(|x| *x += 1);
+            (0..2).for_each(|side| self.add_score(side, 1));
         }
 
         assert!(
@@ -166,17 +316,9 @@ impl<const PITS: usize> Board for OwareBoard<PITS> {
             2 * PITS as u8 * self.init_seeds
         );
 
-        let draw = self.scores.iter().all(|&x| x == PITS as u8 * self.init_seeds);
-
-        self.outcome = self
-            .scores
-            .iter()
-            .position(|&score| score > PITS as u8 * self.init_seeds)
-            .map_or(if draw { Some(Outcome::Draw) } else { None }, |pl| {
-                Some(Outcome::WonBy(Player::BOTH[pl]))
-            });
-
+        self.outcome = self.outcome_from_scores();
         self.next_player = self.next_player.other();
+        self.zobrist ^= ZOBRIST_NEXT_B;
     }
 
     fn outcome(&self) -> Option<Outcome> {
@@ -190,6 +332,52 @@ impl<const PITS: usize> Board for OwareBoard<PITS> {
 
 impl<const PITS: usize> Alternating for OwareBoard<PITS> {}
 
+impl<const PITS: usize> ZobristHash for OwareBoard<PITS> {
+    fn zobrist(&self) -> u64 {
+        self.zobrist
+    }
+}
+
+/// Positions are written in the format of [OwareBoard::to_fen].
+/// Moves are written as the pit index, starting from 0.
+impl<const PITS: usize> BoardNotation for OwareBoard<PITS> {
+    type PositionError = InvalidNotation;
+    type MoveError = InvalidNotation;
+
+    fn parse_position(position: &str) -> Result<Self, InvalidNotation> {
+        OwareBoard::from_fen(position)
+    }
+
+    fn format_position(&self) -> String {
+        self.to_fen()
+    }
+
+    fn parse_move(&self, mv: &str) -> Result<usize, InvalidNotation> {
+        match mv.parse::<usize>() {
+            Ok(pit) if pit < PITS => Ok(pit),
+            _ => Err(InvalidNotation::new(
+                mv,
+                format!("expected a pit from 0 to {}", PITS - 1),
+            )),
+        }
+    }
+
+    fn format_move(&self, mv: usize) -> String {
+        mv.to_string()
+    }
+}
+
+// the number of seeds is unbounded, so zobrist keys are generated on the fly instead of stored in a table
+const ZOBRIST_NEXT_B: u64 = zobrist_key(0);
+
+fn zobrist_pit<const PITS: usize>(side: usize, pit: usize, seeds: u8) -> u64 {
+    zobrist_key(1 + ((((side * PITS + pit) as u64) << 8) | seeds as u64))
+}
+
+fn zobrist_score(side: usize, score: u8) -> u64 {
+    zobrist_key((1 << 62) | ((side as u64) << 8) | score as u64)
+}
+
 impl<const PITS: usize> crate::board::BoardSymmetry<OwareBoard<PITS>> for OwareBoard<PITS> {
     type Symmetry = crate::symmetry::UnitSymmetry;
     type CanonicalKey = ();
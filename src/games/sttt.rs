@@ -7,10 +7,19 @@ use itertools::Itertools;
 use rand::Rng;
 
 use crate::board::{
-    AllMovesIterator, Alternating, AvailableMovesIterator, Board, BoardMoves, BoardSymmetry, Outcome, Player,
+    AllMovesIterator, Alternating, AvailableMovesIterator, Board, BoardMoves, BoardNotation, BoardSymmetry,
+    InvalidNotation, Outcome, Player, ZobristHash,
 };
+use crate::impl_clone_undo_board;
 use crate::symmetry::D4Symmetry;
 use crate::util::bits::{get_nth_set_bit, BitIter};
+use crate::util::coord::{algebraic_to_xy, xy_to_algebraic};
+use crate::util::zobrist::{zobrist_key, zobrist_table};
+
+// zobrist keys, the tiles are indexed by `81 * player + o`
+const ZOBRIST_TILES: [u64; 162] = zobrist_table(0);
+const ZOBRIST_LAST_MOVE: [u64; 81] = zobrist_table(162);
+const ZOBRIST_NEXT_B: u64 = zobrist_key(243);
 
 #[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub struct Coord(u8);
@@ -26,6 +35,8 @@ pub struct STTTBoard {
 
     macro_mask: u32,
     macro_open: u32,
+
+    zobrist: u64,
 }
 
 impl Default for STTTBoard {
@@ -38,6 +49,7 @@ impl Default for STTTBoard {
             outcome: None,
             macro_mask: STTTBoard::FULL_MASK,
             macro_open: STTTBoard::FULL_MASK,
+            zobrist: 0,
         }
     }
 }
@@ -64,6 +76,103 @@ impl STTTBoard {
         self.grids.iter().map(|tile| tile.count_ones()).sum()
     }
 
+    /// Parse a board in the format of [STTTBoard::to_fen].
+    ///
+    /// The tiles are checked to be consistent with the last move: the tile counts must match, the last move tile
+    /// must belong to the last player and be in a grid that was still open, no small grid can be won by both
+    /// players and the game can't have ended before the last move. The macro state and outcome are derived
+    /// from the tiles.
+    pub fn from_fen(fen: &str) -> Result<Self, InvalidNotation> {
+        let error = |reason: &str| InvalidNotation::new(fen, reason);
+
+        let (grid, last_move) = fen
+            .split_once(' ')
+            .ok_or_else(|| error("expected tiles and last move"))?;
+        let rows = grid.split('/').collect::<Vec<_>>();
+        if rows.len() != 9 || rows.iter().any(|row| row.chars().count() != 9) {
+            return Err(error("expected 9 rows of 9 tiles"));
+        }
+
+        let mut tiles = vec![];
+        for (y, row) in rows.iter().enumerate() {
+            for (x, c) in row.chars().enumerate() {
+                let player = match c {
+                    'x' => Player::A,
+                    'o' => Player::B,
+                    '.' => continue,
+                    _ => return Err(error("invalid tile")),
+                };
+                tiles.push((player, Coord::from_xy(x as u8, y as u8)));
+            }
+        }
+
+        let last_move = match last_move {
+            "-" => None,
+            _ => match algebraic_to_xy(last_move) {
+                Some((x, y)) if x < 9 && y < 9 => Some(Coord::from_xy(x, y)),
+                _ => return Err(error("invalid last move")),
+            },
+        };
+
+        let count = |player| tiles.iter().filter(|&&(p, _)| p == player).count();
+        let last_player = match (count(Player::A) as isize - count(Player::B) as isize, last_move) {
+            (0, None) if tiles.is_empty() => None,
+            (0, None) => return Err(error("missing last move")),
+            (0, Some(_)) => Some(Player::B),
+            (1, Some(_)) => Some(Player::A),
+            _ => return Err(error("invalid number of tiles for each player")),
+        };
+
+        let mut board = STTTBoard::default();
+        if let (Some(last_player), Some(last_move)) = (last_player, last_move) {
+            if !tiles.contains(&(last_player, last_move)) {
+                return Err(error("last move tile does not belong to the last player"));
+            }
+
+            for &(player, coord) in &tiles {
+                if coord != last_move {
+                    board.set_tile_and_update(player, coord);
+                }
+            }
+            if board.outcome.is_some() {
+                return Err(error("game ended before the last move"));
+            }
+            if !board.is_macro_open(last_move.om()) {
+                return Err(error("last move in a grid that was already closed"));
+            }
+
+            board.set_tile_and_update(last_player, last_move);
+            board.last_move = Some(last_move);
+            board.next_player = last_player.other();
+
+            if (0..9).any(|om| has_bit(board.main_grid, om) && has_bit(board.main_grid, om + 9)) {
+                return Err(error("small grid won by both players"));
+            }
+        }
+
+        board.zobrist = board.compute_zobrist();
+        Ok(board)
+    }
+
+    /// Format the board as the rows separated by `/`, with `x` for [Player::A], `o` for [Player::B]
+    /// and `.` for empty tiles, followed by the last move as in `e5` or `-` if there is none.
+    pub fn to_fen(&self) -> String {
+        let rows = (0..9).map(|y| {
+            (0..9)
+                .map(|x| match self.tile(Coord::from_xy(x, y)) {
+                    Some(Player::A) => 'x',
+                    Some(Player::B) => 'o',
+                    None => '.',
+                })
+                .collect::<String>()
+        });
+        let last_move = match self.last_move {
+            Some(mv) => xy_to_algebraic(mv.x(), mv.y()),
+            None => "-".to_owned(),
+        };
+        format!("{} {}", rows.collect::<Vec<_>>().join("/"), last_move)
+    }
+
     fn set_tile_and_update(&mut self, player: Player, coord: Coord) {
         let om = coord.om();
         let os = coord.os();
@@ -72,6 +181,7 @@ impl STTTBoard {
         //set tile and macro, check win
         let new_grid = self.grids[om as usize] | (1 << (os + p));
         self.grids[om as usize] = new_grid;
+        self.zobrist ^= zobrist_tile(player, coord);
 
         let grid_win = is_win_grid((new_grid >> p) & STTTBoard::FULL_MASK);
         if grid_win {
@@ -93,6 +203,20 @@ impl STTTBoard {
         self.macro_mask = self.calc_macro_mask(os);
     }
 
+    /// Compute the zobrist key from scratch.
+    fn compute_zobrist(&self) -> u64 {
+        let mut result = zobrist_last_move(self.last_move);
+        for coord in Coord::all() {
+            if let Some(player) = self.tile(coord) {
+                result ^= zobrist_tile(player, coord);
+            }
+        }
+        if self.next_player == Player::B {
+            result ^= ZOBRIST_NEXT_B;
+        }
+        result
+    }
+
     fn calc_macro_mask(&self, os: u8) -> u32 {
         if has_bit(self.macro_open, os) {
             1u32 << os
@@ -102,6 +226,8 @@ impl STTTBoard {
     }
 }
 
+impl_clone_undo_board!(STTTBoard);
+
 impl Board for STTTBoard {
     type Move = Coord;
 
@@ -150,6 +276,7 @@ impl Board for STTTBoard {
         self.set_tile_and_update(self.next_player, mv);
 
         //update for next player
+        self.zobrist ^= zobrist_last_move(self.last_move) ^ zobrist_last_move(Some(mv)) ^ ZOBRIST_NEXT_B;
         self.last_move = Some(mv);
         self.next_player = self.next_player.other()
     }
@@ -165,6 +292,20 @@ impl Board for STTTBoard {
 
 impl Alternating for STTTBoard {}
 
+impl ZobristHash for STTTBoard {
+    fn zobrist(&self) -> u64 {
+        self.zobrist
+    }
+}
+
+fn zobrist_tile(player: Player, coord: Coord) -> u64 {
+    ZOBRIST_TILES[81 * player.index() as usize + coord.o() as usize]
+}
+
+fn zobrist_last_move(last_move: Option<Coord>) -> u64 {
+    last_move.map_or(0, |coord| ZOBRIST_LAST_MOVE[coord.o() as usize])
+}
+
 impl BoardSymmetry<STTTBoard> for STTTBoard {
     type Symmetry = D4Symmetry;
     type CanonicalKey = (u32, Option<Coord>, u32, u32);
@@ -175,7 +316,7 @@ impl BoardSymmetry<STTTBoard> for STTTBoard {
             grids[map_oo(sym, oo) as usize] = map_grid(sym, self.grids[oo as usize])
         }
 
-        STTTBoard {
+        let mut result = STTTBoard {
             grids,
             main_grid: map_grid(sym, self.main_grid),
             last_move: self.last_move.map(|c| self.map_move(sym, c)),
@@ -183,7 +324,10 @@ impl BoardSymmetry<STTTBoard> for STTTBoard {
             outcome: self.outcome,
             macro_mask: map_grid(sym, self.macro_mask),
             macro_open: map_grid(sym, self.macro_open),
-        }
+            zobrist: 0,
+        };
+        result.zobrist = result.compute_zobrist();
+        result
     }
 
     fn map_move(&self, sym: D4Symmetry, mv: Coord) -> Coord {
@@ -195,6 +339,32 @@ impl BoardSymmetry<STTTBoard> for STTTBoard {
     }
 }
 
+/// Positions are written in the format of [STTTBoard::to_fen], moves as the algebraic `x`, `y` coordinate
+/// from `a1` to `i9`.
+impl BoardNotation for STTTBoard {
+    type PositionError = InvalidNotation;
+    type MoveError = InvalidNotation;
+
+    fn parse_position(position: &str) -> Result<Self, InvalidNotation> {
+        STTTBoard::from_fen(position)
+    }
+
+    fn format_position(&self) -> String {
+        self.to_fen()
+    }
+
+    fn parse_move(&self, mv: &str) -> Result<Coord, InvalidNotation> {
+        match algebraic_to_xy(mv) {
+            Some((x, y)) if x < 9 && y < 9 => Ok(Coord::from_xy(x, y)),
+            _ => Err(InvalidNotation::new(mv, "expected a tile from a1 to i9")),
+        }
+    }
+
+    fn format_move(&self, mv: Coord) -> String {
+        xy_to_algebraic(mv.x(), mv.y())
+    }
+}
+
 impl<'a> BoardMoves<'a, STTTBoard> for STTTBoard {
     type AllMovesIterator = Internal<CoordIter>;
     type AvailableMovesIterator = AvailableMovesIterator<'a, STTTBoard>;
@@ -448,5 +618,6 @@ pub fn board_from_compact_string(s: &str) -> STTTBoard {
         board.next_player = last_player.other()
     }
 
+    board.zobrist = board.compute_zobrist();
     board
 }
@@ -2,17 +2,27 @@ use std::fmt::{Debug, Display, Formatter};
 
 use internal_iterator::{Internal, IteratorExt};
 
-use crate::board::{Alternating, Board, BoardMoves, BruteforceMoveIterator, Outcome, Player};
+use crate::board::{
+    Alternating, Board, BoardMoves, BoardNotation, BruteforceMoveIterator, InvalidNotation, Outcome, Player,
+    ZobristHash,
+};
+use crate::impl_clone_undo_board;
 use crate::impl_unit_symmetry_board;
-use crate::util::coord::{Coord3, CoordAllIter};
+use crate::util::coord::{algebraic_to_xy, xy_to_algebraic, Coord3, CoordAllIter};
+use crate::util::zobrist::{zobrist_key, zobrist_table};
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub struct TTTBoard {
     tiles: [Option<Player>; 9],
     next_player: Player,
     outcome: Option<Outcome>,
+    zobrist: u64,
 }
 
+// zobrist keys, the tiles are indexed by `9 * player + coord`
+const ZOBRIST_TILES: [u64; 18] = zobrist_table(0);
+const ZOBRIST_NEXT_B: u64 = zobrist_key(18);
+
 const LINES: &[[(usize, usize); 3]] = &[
     [(0, 0), (0, 1), (0, 2)],
     [(1, 0), (1, 1), (1, 2)],
@@ -30,6 +40,7 @@ impl Default for TTTBoard {
             tiles: Default::default(),
             next_player: Player::A,
             outcome: None,
+            zobrist: 0,
         }
     }
 }
@@ -38,8 +49,84 @@ impl TTTBoard {
     pub fn tile(&self, coord: Coord3) -> Option<Player> {
         self.tiles[coord.index() as usize]
     }
+
+    /// Parse a board in the format of [TTTBoard::to_fen].
+    /// The tiles are checked to be reachable: the tile counts must match and only the last player can have won.
+    pub fn from_fen(fen: &str) -> Result<Self, InvalidNotation> {
+        let error = |reason: &str| InvalidNotation::new(fen, reason);
+
+        let rows = fen.split('/').collect::<Vec<_>>();
+        if rows.len() != 3 || rows.iter().any(|row| row.chars().count() != 3) {
+            return Err(error("expected 3 rows of 3 tiles"));
+        }
+
+        let mut board = TTTBoard::default();
+        for (y, row) in rows.iter().enumerate() {
+            for (x, c) in row.chars().enumerate() {
+                let coord = Coord3::from_xy(x as u8, y as u8);
+                let tile = match c {
+                    'x' => Some(Player::A),
+                    'o' => Some(Player::B),
+                    '.' => None,
+                    _ => return Err(error("invalid tile")),
+                };
+                board.tiles[coord.index() as usize] = tile;
+                if let Some(player) = tile {
+                    board.zobrist ^= ZOBRIST_TILES[9 * player.index() as usize + coord.index() as usize];
+                }
+            }
+        }
+
+        let count = |player| board.tiles.iter().filter(|&&tile| tile == Some(player)).count();
+        board.next_player = match count(Player::A) as isize - count(Player::B) as isize {
+            0 => Player::A,
+            1 => Player::B,
+            _ => return Err(error("invalid number of tiles for each player")),
+        };
+        if board.next_player == Player::B {
+            board.zobrist ^= ZOBRIST_NEXT_B;
+        }
+
+        board.outcome = match (board.has_line(Player::A), board.has_line(Player::B)) {
+            (true, true) => return Err(error("both players have a line")),
+            (true, false) if board.next_player != Player::B => return Err(error("A won but B moved last")),
+            (false, true) if board.next_player != Player::A => return Err(error("B won but A moved last")),
+            (true, false) => Some(Outcome::WonBy(Player::A)),
+            (false, true) => Some(Outcome::WonBy(Player::B)),
+            (false, false) if board.tiles.iter().all(|tile| tile.is_some()) => Some(Outcome::Draw),
+            (false, false) => None,
+        };
+
+        Ok(board)
+    }
+
+    /// Format the board as the rows separated by `/`, with `x` for [Player::A], `o` for [Player::B]
+    /// and `.` for empty tiles, eg. `x.o/.x./...`. The next player follows from the number of tiles.
+    pub fn to_fen(&self) -> String {
+        let rows = (0..3).map(|y| {
+            (0..3)
+                .map(|x| match self.tile(Coord3::from_xy(x, y)) {
+                    Some(Player::A) => 'x',
+                    Some(Player::B) => 'o',
+                    None => '.',
+                })
+                .collect::<String>()
+        });
+        rows.collect::<Vec<_>>().join("/")
+    }
+
+    fn has_line(&self, player: Player) -> bool {
+        LINES.iter().any(|line| {
+            line.iter().all(|&(lx, ly)| {
+                let li = Coord3::from_xy(lx as u8, ly as u8).index() as usize;
+                self.tiles[li] == Some(player)
+            })
+        })
+    }
 }
 
+impl_clone_undo_board!(TTTBoard);
+
 impl Board for TTTBoard {
     type Move = Coord3;
 
@@ -56,13 +143,9 @@ impl Board for TTTBoard {
         assert!(self.is_available_move(mv), "{:?} is not available on {:?}", mv, self);
 
         self.tiles[mv.index() as usize] = Some(self.next_player);
+        self.zobrist ^= ZOBRIST_TILES[9 * self.next_player.index() as usize + mv.index() as usize] ^ ZOBRIST_NEXT_B;
 
-        let won = LINES.iter().any(|line| {
-            line.iter().all(|&(lx, ly)| {
-                let li = Coord3::from_xy(lx as u8, ly as u8).index() as usize;
-                self.tiles[li] == Some(self.next_player)
-            })
-        });
+        let won = self.has_line(self.next_player);
         let draw = self.tiles.iter().all(|tile| tile.is_some());
 
         self.outcome = if won {
@@ -87,8 +170,40 @@ impl Board for TTTBoard {
 
 impl Alternating for TTTBoard {}
 
+impl ZobristHash for TTTBoard {
+    fn zobrist(&self) -> u64 {
+        self.zobrist
+    }
+}
+
 impl_unit_symmetry_board!(TTTBoard);
 
+/// Positions are written in the format of [TTTBoard::to_fen].
+/// Moves are written as coordinates like `a1`, with the file for x and the rank for y.
+impl BoardNotation for TTTBoard {
+    type PositionError = InvalidNotation;
+    type MoveError = InvalidNotation;
+
+    fn parse_position(position: &str) -> Result<Self, InvalidNotation> {
+        TTTBoard::from_fen(position)
+    }
+
+    fn format_position(&self) -> String {
+        self.to_fen()
+    }
+
+    fn parse_move(&self, mv: &str) -> Result<Coord3, InvalidNotation> {
+        match algebraic_to_xy(mv) {
+            Some((x, y)) if x < 3 && y < 3 => Ok(Coord3::from_xy(x, y)),
+            _ => Err(InvalidNotation::new(mv, "expected a coordinate from a1 to c3")),
+        }
+    }
+
+    fn format_move(&self, mv: Coord3) -> String {
+        xy_to_algebraic(mv.x(), mv.y())
+    }
+}
+
 impl<'a> BoardMoves<'a, TTTBoard> for TTTBoard {
     type AllMovesIterator = Internal<CoordAllIter<Coord3>>;
     type AvailableMovesIterator = BruteforceMoveIterator<'a, TTTBoard>;
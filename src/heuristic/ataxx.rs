@@ -3,7 +3,7 @@ use std::cmp::{max, Ordering};
 use crate::ai::minimax::Heuristic;
 use crate::ai::solver::SolverHeuristic;
 use crate::board::Board;
-use crate::games::ataxx::AtaxxBoard;
+use crate::games::ataxx::{AtaxxBoard, Move};
 use crate::util::bitboard::BitBoard8;
 
 #[derive(Debug)]
@@ -59,6 +59,22 @@ impl Heuristic<AtaxxBoard> for AtaxxTileHeuristic {
         }
     }
 
+    /// Order moves by the immediate change in tile difference they cause.
+    fn move_order_score(&self, board: &AtaxxBoard, mv: Move) -> i32 {
+        let other = board.tiles_pov().1;
+        let converted = |to| (other & BitBoard8::coord(to).adjacent()).count() as i32;
+
+        match mv {
+            Move::Pass => 0,
+            Move::Copy { to } => 1 + 2 * converted(to),
+            Move::Jump { to, .. } => 2 * converted(to),
+        }
+    }
+
+    const VALUE_FROM_CHILD: bool = true;
+
+    const ORDER_MOVES: bool = true;
+
     fn merge(old: Self::V, new: Self::V) -> (Self::V, Ordering) {
         (max(old, new), new.cmp(&old))
     }
@@ -1,6 +1,6 @@
 use std::cmp::{max, Ordering};
 
-use chess::{Piece, ALL_PIECES};
+use chess::{ChessMove, Piece, ALL_PIECES};
 
 use crate::ai::minimax::Heuristic;
 use crate::ai::solver::SolverHeuristic;
@@ -21,13 +21,7 @@ impl Heuristic<ChessBoard> for ChessPieceValueHeuristic {
         let mut total = 0;
 
         for piece in ALL_PIECES {
-            let value = match piece {
-                Piece::Pawn => 1,
-                Piece::Knight | Piece::Bishop => 3,
-                Piece::Rook => 5,
-                Piece::Queen => 9,
-                Piece::King => 0,
-            };
+            let value = piece_value(piece);
 
             for square in *board.inner().pieces(piece) {
                 // SAFETY: unwrap is safe because `square` contains a piece.
@@ -42,7 +36,35 @@ impl Heuristic<ChessBoard> for ChessPieceValueHeuristic {
         total
     }
 
+    /// Order captures first using MVV-LVA (most valuable victim, least valuable attacker), then promotions.
+    fn move_order_score(&self, board: &ChessBoard, mv: ChessMove) -> i32 {
+        let inner = board.inner();
+
+        let capture = inner.piece_on(mv.get_dest()).map_or(0, |victim| {
+            // SAFETY: unwrap is safe because the source square of an available move contains a piece.
+            let attacker = inner.piece_on(mv.get_source()).unwrap();
+            10 * piece_value(victim) - piece_value(attacker)
+        });
+        let promotion = mv.get_promotion().map_or(0, piece_value);
+
+        capture + 10 * promotion
+    }
+
+    const VALUE_FROM_CHILD: bool = true;
+
+    const ORDER_MOVES: bool = true;
+
     fn merge(old: Self::V, new: Self::V) -> (Self::V, Ordering) {
         (max(old, new), new.cmp(&old))
     }
 }
+
+fn piece_value(piece: Piece) -> i32 {
+    match piece {
+        Piece::Pawn => 1,
+        Piece::Knight | Piece::Bishop => 3,
+        Piece::Rook => 5,
+        Piece::Queen => 9,
+        Piece::King => 0,
+    }
+}
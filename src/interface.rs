@@ -1,2 +1,5 @@
 pub mod aei;
+pub mod engine;
+mod runner;
 pub mod uai;
+pub mod uci;
@@ -2,9 +2,15 @@
 //!
 //! A derivative of the UCI protocol for the game Arimaa.
 //! Specification available at <https://github.com/Janzert/AEI/blob/master/aei-protocol.txt>.
+//!
+//! [server::AeiServer] runs any [Bot](crate::ai::Bot) for [ArimaaBoard](crate::games::arimaa::ArimaaBoard),
+//! the conversion between boards and the AEI notation is implemented in [notation].
 
 use std::fmt::{Display, Formatter};
 
+pub mod notation;
+pub mod server;
+
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum Command {
     AEI,
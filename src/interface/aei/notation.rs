@@ -0,0 +1,306 @@
+//! Conversion between [ArimaaBoard] and the AEI notation for positions and moves.
+//!
+//! A position is written as `<side> [<squares>]`, with `side` either `g` or `s` and `squares` the 64 pieces from
+//! a8 to h1, using a space for empty squares. A move is a sequence of steps like `Ed2n`, followed by captures like
+//! `rc3x`, or a sequence of placements like `Ra1` during setup.
+use std::fmt::Write;
+use std::str::FromStr;
+
+use arimaa_engine_step::{Action, Piece, Square};
+use internal_iterator::InternalIterator;
+
+use crate::board::{Board, BoardMoves, Player};
+use crate::games::arimaa::ArimaaBoard;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct InvalidAeiPosition {
+    pub position: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct InvalidAeiMove {
+    /// The board the move was parsed for, boxed to keep the error small.
+    pub board: Box<ArimaaBoard>,
+    pub mv: String,
+    pub step: String,
+}
+
+pub fn piece_char(piece: Piece, player: Player) -> char {
+    let c = match piece {
+        Piece::Rabbit => 'r',
+        Piece::Cat => 'c',
+        Piece::Dog => 'd',
+        Piece::Horse => 'h',
+        Piece::Camel => 'm',
+        Piece::Elephant => 'e',
+    };
+    match player {
+        Player::A => c.to_ascii_uppercase(),
+        Player::B => c,
+    }
+}
+
+pub fn parse_position(position: &str) -> Result<ArimaaBoard, InvalidAeiPosition> {
+    let error = |reason: &str| InvalidAeiPosition {
+        position: position.to_owned(),
+        reason: reason.to_owned(),
+    };
+
+    let (side, squares) = position
+        .split_once(' ')
+        .ok_or_else(|| error("expected side and squares"))?;
+    let side = match side {
+        "g" | "w" => 'w',
+        "s" | "b" => 'b',
+        _ => return Err(error("invalid side")),
+    };
+    let squares = squares
+        .strip_prefix('[')
+        .and_then(|s| s.strip_suffix(']'))
+        .ok_or_else(|| error("squares should be surrounded by brackets"))?;
+
+    let squares = squares.chars().collect::<Vec<_>>();
+    if squares.len() != 64 {
+        return Err(error("expected 64 squares"));
+    }
+
+    // convert to the text format used by the engine
+    let mut board = format!("2{}\n +-----------------+\n", side);
+    for (i, row) in squares.chunks(8).enumerate() {
+        write!(&mut board, "{}|", 8 - i).unwrap();
+        for &c in row {
+            let c = match c {
+                ' ' | '.' | 'x' | 'X' => '.',
+                c if "rcdhmeRCDHME".contains(c) => c,
+                _ => return Err(error("invalid piece")),
+            };
+            write!(&mut board, " {}", c).unwrap();
+        }
+        board.push_str(" |\n");
+    }
+    board.push_str(" +-----------------+\n   a b c d e f g h\n");
+
+    ArimaaBoard::from_str(&board).map_err(|e| error(&format!("{:?}", e)))
+}
+
+pub fn format_position(board: &ArimaaBoard) -> String {
+    let mut squares = [' '; 64];
+    for_each_piece(board, |index, piece, player| {
+        let name = square_name(index);
+        let name = name.as_bytes();
+        let file = (name[0] - b'a') as usize;
+        let rank = (name[1] - b'1') as usize;
+        squares[(7 - rank) * 8 + file] = piece_char(piece, player);
+    });
+
+    let side = match board.next_player() {
+        Player::A => 'g',
+        Player::B => 's',
+    };
+    format!("{} [{}]", side, squares.iter().collect::<String>())
+}
+
+/// The AEI tokens for a single action: the step or placement itself followed by any resulting captures.
+/// Passing results in no tokens.
+pub fn action_tokens(board: &ArimaaBoard, action: Action) -> Vec<String> {
+    let mut next = board.clone();
+    next.play(action);
+
+    let mut tokens = vec![];
+    let mut moved = None;
+
+    if let Action::Move(square, _) = action {
+        let from = square_index(square);
+        let (piece, player) = piece_at(board, from).expect("a move should start from a piece");
+        tokens.push(format!("{}{}", piece_char(piece, player), action));
+        moved = Some((from, piece, player));
+    }
+
+    for piece in Piece::ALL {
+        for player in [Player::A, Player::B] {
+            let before = board.bits_for_piece(piece, player).0;
+            let after = next.bits_for_piece(piece, player).0;
+            let mut removed = before & !after;
+            let added = after & !before;
+
+            match action {
+                Action::Pass => {}
+                Action::Place(_) => {
+                    for index in bit_indices(added) {
+                        tokens.push(format!("{}{}", piece_char(piece, player), square_name(index)));
+                    }
+                }
+                Action::Move(_, _) => {
+                    if let Some((from, moved_piece, moved_player)) = moved {
+                        if (moved_piece, moved_player) == (piece, player) {
+                            removed &= !(1u64 << from);
+                            if added == 0 {
+                                // the piece moved onto a trap and was captured immediately
+                                let trap = adjacent_trap(from).expect("captured piece should have moved onto a trap");
+                                tokens.push(format!("{}{}x", piece_char(piece, player), square_name(trap)));
+                            }
+                        }
+                    }
+                    for index in bit_indices(removed) {
+                        tokens.push(format!("{}{}x", piece_char(piece, player), square_name(index)));
+                    }
+                }
+            }
+        }
+    }
+
+    tokens
+}
+
+/// Format a single action as its first AEI token without the captures, or `pass` for [Action::Pass].
+pub fn format_action(board: &ArimaaBoard, action: Action) -> String {
+    action_tokens(board, action)
+        .into_iter()
+        .next()
+        .unwrap_or_else(|| "pass".to_owned())
+}
+
+/// Parse a single action formatted by [format_action]. Only available actions are found.
+pub fn parse_action(board: &ArimaaBoard, mv: &str) -> Result<Action, InvalidAeiMove> {
+    let error = || InvalidAeiMove {
+        board: Box::new(board.clone()),
+        mv: mv.to_owned(),
+        step: mv.to_owned(),
+    };
+
+    if board.is_done() {
+        return Err(error());
+    }
+    if mv == "pass" {
+        return Ok(Action::Pass);
+    }
+
+    let mut found = None;
+    board.available_moves().for_each(|action: Action| {
+        if found.is_none() && action_tokens(board, action).first().map(String::as_str) == Some(mv) {
+            found = Some(action);
+        }
+    });
+    found.ok_or_else(error)
+}
+
+/// Format the actions that make up a single turn, starting from `board`.
+pub fn format_turn(board: &ArimaaBoard, actions: &[Action]) -> String {
+    let mut board = board.clone();
+    let mut tokens = vec![];
+
+    for &action in actions {
+        tokens.extend(action_tokens(&board, action));
+        board.play(action);
+    }
+
+    tokens.join(" ")
+}
+
+/// Parse a single turn into actions, including the final pass if the turn ends before all steps are used.
+/// Captures are implied by the steps so capture tokens are skipped, setup placements can be in any order.
+pub fn parse_turn(board: &ArimaaBoard, mv: &str) -> Result<Vec<Action>, InvalidAeiMove> {
+    let player = board.next_player();
+    let mut curr = board.clone();
+    let mut actions = vec![];
+
+    let mut tokens = mv
+        .split_whitespace()
+        .filter(|token| !(token.len() == 4 && token.ends_with('x')))
+        .collect::<Vec<_>>();
+
+    while !tokens.is_empty() {
+        let error = || InvalidAeiMove {
+            board: Box::new(board.clone()),
+            mv: mv.to_owned(),
+            step: tokens[0].to_owned(),
+        };
+
+        if curr.is_done() || curr.next_player() != player {
+            return Err(error());
+        }
+
+        // steps have to be in order, but placements can be in any order
+        let candidates = if curr.state().is_play_phase() {
+            &tokens[..1]
+        } else {
+            &tokens[..]
+        };
+
+        let mut found = None;
+        curr.available_moves().for_each(|action: Action| {
+            if found.is_none() {
+                if let Some(first) = action_tokens(&curr, action).first() {
+                    if let Some(i) = candidates.iter().position(|token| token == first) {
+                        found = Some((i, action));
+                    }
+                }
+            }
+        });
+
+        let (i, action) = found.ok_or_else(error)?;
+        tokens.remove(i);
+        curr.play(action);
+        actions.push(action);
+    }
+
+    if !curr.is_done()
+        && curr.next_player() == player
+        && curr.state().is_play_phase()
+        && curr.is_available_move(Action::Pass)
+    {
+        actions.push(Action::Pass);
+    }
+
+    Ok(actions)
+}
+
+fn piece_at(board: &ArimaaBoard, index: u32) -> Option<(Piece, Player)> {
+    let mut result = None;
+    for_each_piece(board, |i, piece, player| {
+        if i == index {
+            result = Some((piece, player));
+        }
+    });
+    result
+}
+
+fn for_each_piece(board: &ArimaaBoard, mut f: impl FnMut(u32, Piece, Player)) {
+    for piece in Piece::ALL {
+        for player in [Player::A, Player::B] {
+            for index in bit_indices(board.bits_for_piece(piece, player).0) {
+                f(index, piece, player);
+            }
+        }
+    }
+}
+
+fn bit_indices(bits: u64) -> impl Iterator<Item = u32> {
+    (0..64).filter(move |&i| (bits >> i) & 1 != 0)
+}
+
+fn adjacent_trap(index: u32) -> Option<u32> {
+    let (row, col) = (index / 8, index % 8);
+    let neighbors = [
+        (row > 0).then(|| index - 8),
+        (row < 7).then(|| index + 8),
+        (col > 0).then(|| index - 1),
+        (col < 7).then(|| index + 1),
+    ];
+    neighbors
+        .iter()
+        .flatten()
+        .copied()
+        .find(|&i| (ArimaaBoard::TRAP_MASK.0 >> i) & 1 != 0)
+}
+
+fn square_index(square: Square) -> u32 {
+    (0..64)
+        .find(|&i| Square::from_index(i as _) == square)
+        .expect("square should have an index")
+}
+
+fn square_name(index: u32) -> String {
+    Square::from_index(index as _).to_string()
+}
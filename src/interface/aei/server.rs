@@ -0,0 +1,424 @@
+use std::cmp::min;
+use std::fmt::{Debug, Formatter};
+use std::io::{Read, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use arimaa_engine_step::{Action, Piece};
+
+use crate::ai::info::{InfoCallback, SearchInfo};
+use crate::ai::limits::{Clock, SearchLimits};
+use crate::ai::Bot;
+use crate::board::{Board, Player};
+use crate::games::arimaa::ArimaaBoard;
+use crate::interface::aei::notation::{action_tokens, format_turn, parse_position, parse_turn};
+use crate::interface::aei::{Command, IdType, InfoType, OptionName, Response, TCOptionName};
+use crate::interface::runner::{spawn_search, Event, Output, Protocol, SearchCommand};
+
+/// The number of pieces each player places during setup.
+const SETUP_PIECES: u32 = 16;
+
+/// An AEI engine server that lets `T` play Arimaa.
+///
+/// The bot is asked for one step at a time until the turn is over, the resulting steps are sent as a single
+/// `bestmove`. The time control options (`tcmove`, `greserve`, ...) are tracked and turned into
+/// [SearchLimits] for each step, see [AeiTimeControl]. Other options are ignored.
+///
+/// Commands other than `stop`, `isready` and `quit` received while searching are handled after the search finishes.
+pub struct AeiServer<T> {
+    name: String,
+    author: String,
+    sender: Sender<Event<Message<T>>>,
+    receiver: Receiver<Event<Message<T>>>,
+}
+
+/// The time control as set by the `tc*` options, all values are in seconds.
+/// Zero means unlimited for `tc_max`, `tc_total` and `tc_turn_time`.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub struct AeiTimeControl {
+    pub tc_move: Option<u64>,
+    pub tc_reserve: Option<u64>,
+    pub tc_percent: Option<u64>,
+    pub tc_max: Option<u64>,
+    pub tc_total: Option<u64>,
+    pub tc_turns: Option<u64>,
+    pub tc_turn_time: Option<u64>,
+    pub g_reserve: Option<u64>,
+    pub s_reserve: Option<u64>,
+    pub g_used: Option<u64>,
+    pub s_used: Option<u64>,
+    pub last_move_used: Option<u64>,
+    pub move_used: Option<u64>,
+}
+
+enum Message<T> {
+    Info(SearchInfo<Action>),
+    /// The search moved on to the next step, starting from the given board.
+    Step(ArimaaBoard),
+    Done {
+        bot: T,
+        actions: Vec<Action>,
+    },
+}
+
+/// A search running in the background.
+struct Search {
+    start_board: ArimaaBoard,
+    /// The board of the step currently being searched.
+    board: ArimaaBoard,
+    stop: Arc<AtomicBool>,
+    start: Instant,
+}
+
+impl AeiTimeControl {
+    pub fn set(&mut self, name: TCOptionName, value: u64) {
+        let field = match name {
+            TCOptionName::TcMove => &mut self.tc_move,
+            TCOptionName::TcReserve => &mut self.tc_reserve,
+            TCOptionName::TcPercent => &mut self.tc_percent,
+            TCOptionName::TcMax => &mut self.tc_max,
+            TCOptionName::TcTotal => &mut self.tc_total,
+            TCOptionName::TcTurns => &mut self.tc_turns,
+            TCOptionName::TcTurnTime => &mut self.tc_turn_time,
+            TCOptionName::GReserve => &mut self.g_reserve,
+            TCOptionName::SReserve => &mut self.s_reserve,
+            TCOptionName::GUsed => &mut self.g_used,
+            TCOptionName::SUsed => &mut self.s_used,
+            TCOptionName::LastMoveUsed => &mut self.last_move_used,
+            TCOptionName::MoveUsed => &mut self.move_used,
+        };
+        *field = Some(value);
+    }
+
+    /// The time to spend on the current turn of `player`, `None` if no time control has been set.
+    ///
+    /// This is the time left for this turn plus a share of the reserve, limited by `tc_turn_time`
+    /// and with a margin of 10% for communication overhead.
+    pub fn turn_time(&self, player: Player) -> Option<Duration> {
+        let reserve = match player {
+            Player::A => self.g_reserve,
+            Player::B => self.s_reserve,
+        }
+        .or(self.tc_reserve);
+
+        if self.tc_move.is_none() && reserve.is_none() {
+            return None;
+        }
+
+        let used = self.move_used.unwrap_or(0);
+        let reserve_share = Clock {
+            remaining: Duration::from_secs(reserve.unwrap_or(0)),
+            increment: Duration::ZERO,
+            moves_to_go: None,
+        }
+        .allocate();
+
+        let mut time = Duration::from_secs(self.tc_move.unwrap_or(0).saturating_sub(used)) + reserve_share;
+        if let Some(turn_time) = self.tc_turn_time.filter(|&t| t > 0) {
+            time = min(time, Duration::from_secs(turn_time.saturating_sub(used)));
+        }
+
+        Some(time * 9 / 10)
+    }
+}
+
+impl<T: Bot<ArimaaBoard> + Send + 'static> AeiServer<T> {
+    pub fn new(name: &str, author: &str) -> Self {
+        let (sender, receiver) = channel();
+        AeiServer {
+            name: name.to_owned(),
+            author: author.to_owned(),
+            sender,
+            receiver,
+        }
+    }
+
+    /// A callback that sends [SearchInfo] to the controller as `info` responses.
+    /// Pass this to the bot, eg. using [MCTSBot::with_info_callback](crate::ai::mcts::MCTSBot::with_info_callback).
+    pub fn info_callback(&self) -> InfoCallback<Action> {
+        let sender = self.sender.clone();
+        Box::new(move |info: &SearchInfo<Action>| {
+            // the server might have stopped already, in which case there is no one left to report to
+            let _ = sender.send(Event::Message(Message::Info(info.clone())));
+        })
+    }
+
+    /// Run the server until `quit` is received or `input` ends.
+    /// `input` is read on a separate thread, so the server can react to `stop` while the bot is searching.
+    pub fn run(
+        self,
+        bot: T,
+        input: impl Read + Send + 'static,
+        output: impl Write,
+        log: impl Write,
+    ) -> std::io::Result<()> {
+        let mut session = Session {
+            server: self,
+            state: State {
+                bot: Some(bot),
+                board: ArimaaBoard::default(),
+                time_control: AeiTimeControl::default(),
+                search: None,
+            },
+        };
+        crate::interface::runner::run(&mut session, input, output, log)
+    }
+
+    /// Handle a single command while no search is running, returns whether the server should quit.
+    fn handle_command<O: Write, L: Write>(
+        &self,
+        state: &mut State<T>,
+        output: &mut Output<O, L>,
+        line: &str,
+    ) -> std::io::Result<bool> {
+        let command = match Command::parse(line) {
+            Ok(command) => command,
+            Err(_) => {
+                output.error(&format!("failed to parse command '{}'", line))?;
+                return Ok(false);
+            }
+        };
+
+        match command {
+            Command::AEI => {
+                output.respond(Response::ProtocolV1)?;
+                output.respond(Response::Id {
+                    ty: IdType::Name,
+                    value: self.name.clone(),
+                })?;
+                output.respond(Response::Id {
+                    ty: IdType::Author,
+                    value: self.author.clone(),
+                })?;
+                output.respond(Response::AeiOk)?;
+            }
+            Command::IsReady => output.respond(Response::ReadyOk)?,
+            Command::NewGame => {
+                state.board = ArimaaBoard::default();
+                state.time_control = AeiTimeControl::default();
+            }
+            Command::SetPosition(position) => match parse_position(&position) {
+                Ok(board) => state.board = board,
+                Err(e) => output.error(&format!("invalid position '{}': {:?}", position, e))?,
+            },
+            Command::SetOption { name, value } => match name {
+                OptionName::TC(name) => match value.as_deref().map(str::parse::<u64>) {
+                    Some(Ok(value)) => state.time_control.set(name, value),
+                    _ => output.error(&format!("invalid value {:?} for option {:?}", value, name))?,
+                },
+                _ => output.log(&format!("ignoring option, name={:?}, value={:?}", name, value))?,
+            },
+            Command::MakeMove(mv) => {
+                if state.board.is_done() {
+                    output.error(&format!("cannot play move '{}', board is already done", mv))?;
+                    return Ok(false);
+                }
+                match parse_turn(&state.board, &mv) {
+                    Ok(actions) => {
+                        for action in actions {
+                            state.board.play(action);
+                        }
+                    }
+                    Err(e) => output.error(&format!("invalid move '{}': {:?}", mv, e))?,
+                }
+            }
+            Command::Go { ponder: true } => {
+                output.respond(Response::Log(
+                    "pondering is not supported, ignoring go ponder".to_owned(),
+                ))?;
+            }
+            Command::Go { ponder: false } => {
+                let board = state.board.clone();
+                if let Some(outcome) = board.outcome() {
+                    output.error(&format!("cannot go on done board, outcome: {:?}", outcome))?;
+                    return Ok(false);
+                }
+
+                let stop = Arc::new(AtomicBool::new(false));
+                let start = Instant::now();
+                let deadline = state
+                    .time_control
+                    .turn_time(board.next_player())
+                    .map(|time| start + time);
+
+                let mut bot = state.bot.take().expect("bot should be available when not searching");
+                let step_sender = self.sender.clone();
+                let search_stop = stop.clone();
+                let mut search_board = board.clone();
+
+                spawn_search(self.sender.clone(), move || {
+                    let player = search_board.next_player();
+                    let mut actions = vec![];
+
+                    while !search_board.is_done() && search_board.next_player() == player {
+                        let _ = step_sender.send(Event::Message(Message::Step(search_board.clone())));
+                        let limits = step_limits(&search_board, deadline).with_stop(search_stop.clone());
+                        let action = bot.select_move_limited(&search_board, &limits);
+                        search_board.play(action);
+                        actions.push(action);
+                    }
+
+                    Message::Done { bot, actions }
+                });
+
+                state.search = Some(Search {
+                    start_board: board.clone(),
+                    board,
+                    stop,
+                    start,
+                });
+            }
+            // there is no search to stop
+            Command::Stop => {}
+            Command::Quit => return Ok(true),
+        }
+
+        Ok(false)
+    }
+}
+
+/// The limits for a single step: an equal share of the time left until `deadline` over the remaining steps.
+fn step_limits(board: &ArimaaBoard, deadline: Option<Instant>) -> SearchLimits {
+    let deadline = match deadline {
+        Some(deadline) => deadline,
+        None => return SearchLimits::default(),
+    };
+
+    let steps_left = if board.state().is_play_phase() {
+        ArimaaBoard::MAX_STEPS_PER_TURN as u32 - board.steps_taken() as u32
+    } else {
+        let placed: u32 = Piece::ALL
+            .iter()
+            .map(|&piece| board.bits_for_piece(piece, board.next_player()).count() as u32)
+            .sum();
+        SETUP_PIECES - placed
+    };
+
+    let left = deadline.saturating_duration_since(Instant::now());
+    SearchLimits::move_time(left / steps_left.max(1))
+}
+
+/// Format the steps of `pv` that are available, continuing into later turns.
+fn format_pv(board: &ArimaaBoard, pv: &[Action]) -> String {
+    let mut board = board.clone();
+    let mut tokens = vec![];
+
+    for &action in pv {
+        if board.is_done() || !board.is_available_move(action) {
+            break;
+        }
+        tokens.extend(action_tokens(&board, action));
+        board.play(action);
+    }
+
+    tokens.join(" ")
+}
+
+impl<T> Debug for AeiServer<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "AeiServer {{ name: {}, author: {} }}", self.name, self.author)
+    }
+}
+
+struct State<T> {
+    /// The bot, `None` while it is searching on another thread.
+    bot: Option<T>,
+    board: ArimaaBoard,
+    time_control: AeiTimeControl,
+    search: Option<Search>,
+}
+
+/// An [AeiServer] together with the state of a single [AeiServer::run].
+struct Session<T> {
+    server: AeiServer<T>,
+    state: State<T>,
+}
+
+impl<T: Bot<ArimaaBoard> + Send + 'static> Protocol for Session<T> {
+    type Message = Message<T>;
+
+    fn sender(&self) -> &Sender<Event<Self::Message>> {
+        &self.server.sender
+    }
+
+    fn receiver(&self) -> &Receiver<Event<Self::Message>> {
+        &self.server.receiver
+    }
+
+    fn is_searching(&self) -> bool {
+        self.state.search.is_some()
+    }
+
+    fn stop_search(&self) {
+        if let Some(search) = &self.state.search {
+            search.stop.store(true, Ordering::Relaxed);
+        }
+    }
+
+    fn search_command(&self, line: &str) -> SearchCommand {
+        match Command::parse(line) {
+            Ok(Command::Stop) => SearchCommand::Stop,
+            Ok(Command::IsReady) => SearchCommand::IsReady,
+            Ok(Command::Quit) => SearchCommand::Quit,
+            _ => SearchCommand::Later,
+        }
+    }
+
+    fn handle_command<O: Write, L: Write>(&mut self, output: &mut Output<O, L>, line: &str) -> std::io::Result<bool> {
+        self.server.handle_command(&mut self.state, output, line)
+    }
+
+    fn handle_message<O: Write, L: Write>(
+        &mut self,
+        output: &mut Output<O, L>,
+        message: Self::Message,
+    ) -> std::io::Result<()> {
+        match message {
+            Message::Step(board) => {
+                if let Some(search) = &mut self.state.search {
+                    search.board = board;
+                }
+            }
+            Message::Info(info) => {
+                if let Some(search) = &self.state.search {
+                    output.info(InfoType::Depth, info.depth.to_string())?;
+                    output.info(InfoType::Nodes, info.nodes.to_string())?;
+                    output.info(InfoType::Time, info.time.as_secs().to_string())?;
+                    output.info(InfoType::Score, ((info.score.value * 100.0).round() as i64).to_string())?;
+                    let pv = format_pv(&search.board, &info.pv);
+                    if !pv.is_empty() {
+                        output.info(InfoType::Pv, pv)?;
+                    }
+                }
+            }
+            Message::Done { bot, actions } => {
+                let search = self
+                    .state
+                    .search
+                    .take()
+                    .expect("received search result without running search");
+                self.state.bot = Some(bot);
+
+                output.log(&format!("time used: {}s", search.start.elapsed().as_secs_f32()))?;
+                output.respond(Response::BestMove(format_turn(&search.start_board, &actions)))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn format_error(&self, error: &str) -> String {
+        Response::Log(format!("Error: {}", error)).to_string()
+    }
+}
+
+impl<O: Write, L: Write> Output<O, L> {
+    fn info(&mut self, ty: InfoType, value: String) -> std::io::Result<()> {
+        self.respond(Response::Info { ty, value })
+    }
+
+    fn error(&mut self, s: &str) -> std::io::Result<()> {
+        self.respond(Response::Log(format!("Error: {}", s)))
+    }
+}
@@ -0,0 +1,190 @@
+//! A generic engine server that speaks a UCI-style text protocol for any [Board].
+//!
+//! The board-specific parts of the protocol (positions, moves and search info) are provided by a [BoardCodec],
+//! so the same server can speak UAI for Ataxx, UCI for chess or a custom dialect for other games.
+//! See [server::EngineServer] for the supported commands.
+//!
+//! The other direction is implemented by [process::ProcessBot], which plays by running an external engine.
+
+use std::fmt::{Debug, Display, Formatter};
+
+use crate::ai::info::{Score, SearchInfo};
+use crate::board::{Board, BoardNotation};
+
+pub mod process;
+pub mod server;
+
+/// The text encoding of boards and moves used by an [server::EngineServer].
+pub trait BoardCodec<B: Board>: Debug {
+    type PositionError: Debug;
+    type MoveError: Debug;
+
+    /// The name of the protocol, eg. `"uai"` or `"uci"`.
+    /// The handshake command is this name, and it is also used to build the `ok` and `newgame` commands.
+    fn protocol(&self) -> &str;
+
+    /// The board used for `position startpos` and new games.
+    fn start_position(&self) -> B;
+
+    fn parse_position(&self, position: &str) -> Result<B, Self::PositionError>;
+
+    fn format_position(&self, board: &B) -> String;
+
+    /// Parse `mv` as a move for `board`. This does not need to check whether the move is available.
+    fn parse_move(&self, board: &B, mv: &str) -> Result<B::Move, Self::MoveError>;
+
+    fn format_move(&self, board: &B, mv: B::Move) -> String;
+
+    /// Format `info` as a single `info` response, `board` is the board the search started from.
+    ///
+    /// The default implementation produces `info depth <d> nodes <n> nps <n> time <ms> <score> pv <moves>`,
+    /// with the score formatted by [Self::format_score] and followed by `wdl <w> <d> <l>` in permille
+    /// if the search estimates it.
+    fn format_info(&self, board: &B, info: &SearchInfo<B::Move>) -> String {
+        let mut result = format!(
+            "info depth {} nodes {} nps {:.0} time {} {}",
+            info.depth,
+            info.nodes,
+            info.nps(),
+            info.time.as_millis(),
+            self.format_score(&info.score)
+        );
+
+        if let Some(wdl) = info.score.wdl {
+            let permille = |x: f32| (x * 1000.0).round() as u32;
+            result += &format!(
+                " wdl {} {} {}",
+                permille(wdl.win),
+                permille(wdl.draw),
+                permille(wdl.loss)
+            );
+        }
+
+        if !info.pv.is_empty() {
+            result += " pv";
+            result += &format_moves(self, board, &info.pv);
+        }
+
+        result
+    }
+
+    /// Format `score` as part of an `info` response, the default implementation produces `score <value>`.
+    fn format_score(&self, score: &Score) -> String {
+        format!("score {:.3}", score.value)
+    }
+}
+
+/// A [BoardCodec] that uses the [BoardNotation] of the board, so any game with a notation
+/// can be used with an [server::EngineServer], a [process::ProcessBot] or a game record.
+#[derive(Debug, Clone)]
+pub struct NotationCodec<B> {
+    protocol: String,
+    start_position: B,
+}
+
+impl<B: BoardNotation> NotationCodec<B> {
+    pub fn new(protocol: &str, start_position: B) -> Self {
+        NotationCodec {
+            protocol: protocol.to_owned(),
+            start_position,
+        }
+    }
+}
+
+impl<B: BoardNotation> BoardCodec<B> for NotationCodec<B> {
+    type PositionError = B::PositionError;
+    type MoveError = B::MoveError;
+
+    fn protocol(&self) -> &str {
+        &self.protocol
+    }
+
+    fn start_position(&self) -> B {
+        self.start_position.clone()
+    }
+
+    fn parse_position(&self, position: &str) -> Result<B, B::PositionError> {
+        B::parse_position(position)
+    }
+
+    fn format_position(&self, board: &B) -> String {
+        board.format_position()
+    }
+
+    fn parse_move(&self, board: &B, mv: &str) -> Result<B::Move, B::MoveError> {
+        board.parse_move(mv)
+    }
+
+    fn format_move(&self, board: &B, mv: B::Move) -> String {
+        board.format_move(mv)
+    }
+}
+
+/// An option that can be changed with `setoption`, listed in the response to the handshake.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct EngineOption {
+    pub name: String,
+    pub kind: OptionKind,
+}
+
+/// The type of an [EngineOption], following the UCI option types.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum OptionKind {
+    Check { default: bool },
+    Spin { default: i64, min: i64, max: i64 },
+    Combo { default: String, values: Vec<String> },
+    Button,
+    String { default: String },
+}
+
+impl EngineOption {
+    pub fn new(name: &str, kind: OptionKind) -> Self {
+        assert!(
+            !name.contains(" value "),
+            "option name {:?} cannot contain \" value \"",
+            name
+        );
+        EngineOption {
+            name: name.to_owned(),
+            kind,
+        }
+    }
+}
+
+/// Formats as the option declaration, eg. `option name Hash type spin default 16 min 1 max 1024`.
+impl Display for EngineOption {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "option name {} type ", self.name)?;
+        match &self.kind {
+            OptionKind::Check { default } => write!(f, "check default {}", default),
+            OptionKind::Spin { default, min, max } => write!(f, "spin default {} min {} max {}", default, min, max),
+            OptionKind::Combo { default, values } => {
+                write!(f, "combo default {}", default)?;
+                for value in values {
+                    write!(f, " var {}", value)?;
+                }
+                Ok(())
+            }
+            OptionKind::Button => write!(f, "button"),
+            OptionKind::String { default } => write!(f, "string default {}", default),
+        }
+    }
+}
+
+/// Format a sequence of moves starting from `board`, each move prefixed with a space.
+/// Stops early if a move is not available.
+pub fn format_moves<B: Board, C: BoardCodec<B> + ?Sized>(codec: &C, board: &B, moves: &[B::Move]) -> String {
+    let mut result = String::new();
+    let mut board = board.clone();
+
+    for &mv in moves {
+        if board.is_done() || !board.is_available_move(mv) {
+            break;
+        }
+        result.push(' ');
+        result += &codec.format_move(&board, mv);
+        board.play(mv);
+    }
+
+    result
+}
@@ -0,0 +1,282 @@
+use std::fmt::{Debug, Formatter};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::process::{Child, ChildStdin, Stdio};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+use internal_iterator::InternalIterator;
+
+use crate::ai::limits::SearchLimits;
+use crate::ai::Bot;
+use crate::board::Board;
+use crate::interface::engine::{format_moves, BoardCodec};
+use crate::interface::uai::command::{Command, GoSettings, Position};
+
+/// How often the stop flag is checked while waiting for the engine.
+const STOP_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// A [Bot] that plays by running an external engine process, talking UAI, UCI or another protocol
+/// determined by the [BoardCodec].
+///
+/// Every search sends the position followed by a `go` command with all the limits, see
+/// [GoSettings::from_limits], and waits for `bestmove`. The stop flag of the limits is forwarded as `stop`.
+/// Engine failures cause panics, since bots cannot return errors.
+///
+/// The position is sent as the start of the game followed by the moves played since, so the engine can detect
+/// repetitions. The moves are tracked between searches: a board that follows from the previous one by the move
+/// this bot selected and at most one opponent move continues the game, any other board starts a new one.
+pub struct ProcessBot<B: Board, C: BoardCodec<B>> {
+    codec: C,
+    default_limits: SearchLimits,
+
+    name: Option<String>,
+    child: Child,
+    stdin: BufWriter<ChildStdin>,
+    lines: Receiver<String>,
+
+    history: Option<History<B>>,
+    ph: std::marker::PhantomData<B>,
+}
+
+impl<B: Board, C: BoardCodec<B>> ProcessBot<B, C> {
+    /// Spawn the engine and run the protocol handshake.
+    /// `default_limits` are used when a search is started without any limits, eg. from [Bot::select_move].
+    pub fn new(mut command: std::process::Command, codec: C, default_limits: SearchLimits) -> std::io::Result<Self> {
+        let mut child = command.stdin(Stdio::piped()).stdout(Stdio::piped()).spawn()?;
+
+        let stdin = BufWriter::new(child.stdin.take().expect("stdin should be piped"));
+        let stdout = child.stdout.take().expect("stdout should be piped");
+
+        // read on a separate thread so we can check the stop flag while waiting
+        let (sender, lines) = channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                let line = match line {
+                    Ok(line) => line,
+                    Err(_) => break,
+                };
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut bot = ProcessBot {
+            codec,
+            default_limits,
+            name: None,
+            child,
+            stdin,
+            lines,
+            history: None,
+            ph: Default::default(),
+        };
+
+        bot.send(Command::Uai)?;
+        let ok = format!("{}ok", bot.codec.protocol());
+        loop {
+            let line = bot.receive()?;
+            if line == ok {
+                break;
+            }
+            if let Some(name) = line.strip_prefix("id name ") {
+                bot.name = Some(name.to_owned());
+            }
+        }
+
+        bot.send(Command::NewGame)?;
+        bot.sync()?;
+
+        Ok(bot)
+    }
+
+    /// The name the engine reported during the handshake.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    pub fn set_option(&mut self, name: &str, value: &str) -> std::io::Result<()> {
+        self.send(Command::SetOption { name, value })?;
+        self.sync()
+    }
+
+    /// Start a new game, some engines clear their caches.
+    pub fn new_game(&mut self) -> std::io::Result<()> {
+        self.history = None;
+        self.send(Command::NewGame)?;
+        self.sync()
+    }
+
+    /// Send `isready` and wait for `readyok`.
+    pub fn sync(&mut self) -> std::io::Result<()> {
+        self.send(Command::IsReady)?;
+        while self.receive()? != "readyok" {}
+        Ok(())
+    }
+
+    fn search(&mut self, board: &B, limits: &SearchLimits) -> std::io::Result<B::Move> {
+        assert!(!board.is_done(), "cannot search done board {:?}", board);
+
+        let limits = if limits.is_limited() {
+            limits.clone()
+        } else {
+            let mut default = self.default_limits.clone();
+            default.stop = limits.stop.clone();
+            default
+        };
+
+        let mut history = self.history.take().unwrap_or_else(|| History::new(board));
+        if !history.extend_to(board) {
+            history = History::new(board);
+        }
+
+        let start = if history.start == self.codec.start_position() {
+            None
+        } else {
+            Some(self.codec.format_position(&history.start))
+        };
+        let moves = format_moves(&self.codec, &history.start, &history.moves);
+        self.send(Command::Position {
+            position: start.as_deref().map_or(Position::StartPos, Position::Fen),
+            moves: Some(moves.trim_start()).filter(|moves| !moves.is_empty()),
+        })?;
+        self.history = Some(history);
+
+        self.send(Command::Go(GoSettings::from_limits(&limits, board.next_player())))?;
+
+        let mut stop_sent = false;
+        loop {
+            let line = match self.lines.recv_timeout(STOP_POLL_INTERVAL) {
+                Ok(line) => line,
+                Err(RecvTimeoutError::Timeout) => {
+                    if !stop_sent && limits.is_stopped() {
+                        self.send(Command::Stop)?;
+                        stop_sent = true;
+                    }
+                    continue;
+                }
+                Err(RecvTimeoutError::Disconnected) => return Err(disconnected()),
+            };
+
+            if let Some(rest) = line.strip_prefix("bestmove ") {
+                // ignore trailing arguments like `ponder <move>`
+                let mv_str = rest.split_whitespace().next().unwrap_or("");
+                let mv = self.codec.parse_move(board, mv_str).map_err(|e| {
+                    std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        format!("engine returned invalid move '{}': {:?}", mv_str, e),
+                    )
+                })?;
+                if !board.is_available_move(mv) {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        format!("engine returned unavailable move '{}'", mv_str),
+                    ));
+                }
+                if let Some(history) = &mut self.history {
+                    history.push(mv);
+                }
+                return Ok(mv);
+            }
+        }
+    }
+
+    fn send(&mut self, command: Command) -> std::io::Result<()> {
+        writeln!(&mut self.stdin, "{}", command.format(self.codec.protocol()))?;
+        self.stdin.flush()
+    }
+
+    fn receive(&mut self) -> std::io::Result<String> {
+        self.lines.recv().map_err(|_| disconnected())
+    }
+}
+
+/// The game played so far, `current` is `start` with `moves` played.
+struct History<B: Board> {
+    start: B,
+    moves: Vec<B::Move>,
+    current: B,
+}
+
+impl<B: Board> History<B> {
+    fn new(board: &B) -> Self {
+        History {
+            start: board.clone(),
+            moves: vec![],
+            current: board.clone(),
+        }
+    }
+
+    fn push(&mut self, mv: B::Move) {
+        self.moves.push(mv);
+        self.current.play(mv);
+    }
+
+    /// Extend the game to `board` with at most one move, returns whether that was possible.
+    fn extend_to(&mut self, board: &B) -> bool {
+        if &self.current == board {
+            return true;
+        }
+        if self.current.is_done() {
+            return false;
+        }
+
+        let current = &self.current;
+        match current
+            .available_moves()
+            .find(|&mv| &current.clone_and_play(mv) == board)
+        {
+            Some(mv) => {
+                self.push(mv);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+fn disconnected() -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "engine process closed its output")
+}
+
+impl<B: Board, C: BoardCodec<B>> Bot<B> for ProcessBot<B, C> {
+    fn select_move(&mut self, board: &B) -> B::Move {
+        self.select_move_limited(board, &SearchLimits::default())
+    }
+
+    fn select_move_limited(&mut self, board: &B, limits: &SearchLimits) -> B::Move {
+        match self.search(board, limits) {
+            Ok(mv) => mv,
+            Err(e) => panic!("engine {:?} failed: {}", self.name, e),
+        }
+    }
+}
+
+impl<B: Board, C: BoardCodec<B>> Drop for ProcessBot<B, C> {
+    fn drop(&mut self) {
+        // the engine might already be gone, so errors are ignored
+        let _ = self.send(Command::Quit);
+        for _ in 0..100 {
+            if !matches!(self.child.try_wait(), Ok(None)) {
+                return;
+            }
+            thread::sleep(STOP_POLL_INTERVAL);
+        }
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+impl<B: Board, C: BoardCodec<B>> Debug for ProcessBot<B, C> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "ProcessBot {{ name: {:?}, codec: {:?}, pid: {}, default_limits: {:?} }}",
+            self.name,
+            self.codec,
+            self.child.id(),
+            self.default_limits
+        )
+    }
+}
@@ -0,0 +1,419 @@
+use std::collections::VecDeque;
+use std::fmt::{Debug, Formatter};
+use std::io::{Read, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::ai::info::{InfoCallback, SearchInfo};
+use crate::ai::Bot;
+use crate::board::Board;
+use crate::interface::engine::{BoardCodec, EngineOption};
+use crate::interface::runner::{spawn_search, Event, Output, Protocol, SearchCommand};
+use crate::interface::uai::command::{Command, Position};
+
+pub const MAX_STACK_SIZE: usize = 100;
+
+/// An engine server that lets `T` play `B` through a UCI-style protocol, the text format is determined by `C`.
+///
+/// The supported commands are, with `<p>` the [protocol](BoardCodec::protocol) name:
+/// * `<p>`: identify the engine, answered with `id name`, `id author` and `<p>ok`.
+/// * `isready`: answered with `readyok`, also while searching.
+/// * `<p>newgame`: clear the position stack and push the start position.
+/// * `position (startpos | fen <fen>) [moves <moves>]`: push a new position on the stack.
+/// * `moves <moves>`: play moves on the current position, each resulting board is pushed on the stack.
+/// * `takeback`: pop the current position from the stack.
+/// * `print` or `d`: print the current board.
+/// * `go [movetime <ms>] [wtime <ms>] [btime <ms>] [winc <ms>] [binc <ms>] [movestogo <n>] [nodes <n>] [depth <n>] [infinite]`:
+///   search the current position, see [GoSettings::to_limits](crate::interface::uai::command::GoSettings::to_limits).
+///   The search runs in the background and ends with `bestmove <move>`.
+///   For `go infinite` the `bestmove` is only sent after `stop`, even if the bot finishes earlier.
+/// * `stop`: stop the current search as soon as possible.
+/// * `setoption name <name> [value <value>]`: change one of the options set with [EngineServer::with_options].
+/// * `quit`: stop the current search and exit.
+///
+/// Other commands received while searching are handled after the search finishes.
+/// If the bot panics during a search the error is reported to the client and [EngineServer::run] returns an error.
+pub struct EngineServer<B: Board, C: BoardCodec<B>, T> {
+    codec: C,
+    name: String,
+    author: String,
+    options: Vec<EngineOption>,
+    option_handler: Option<OptionHandler<T>>,
+    sender: Sender<Event<Message<B, T>>>,
+    receiver: Receiver<Event<Message<B, T>>>,
+}
+
+/// Applies a `setoption` command to the bot, see [EngineServer::with_options].
+pub type OptionHandler<T> = Box<dyn FnMut(&mut T, &str, Option<&str>) -> Result<(), String>>;
+
+enum Message<B: Board, T> {
+    Info(SearchInfo<B::Move>),
+    Done {
+        bot: T,
+        mv: B::Move,
+    },
+    /// `stop` was received during an infinite search, which might be waiting for it.
+    Stopped,
+}
+
+/// A search running in the background.
+struct Search<B: Board, T> {
+    board: B,
+    stop: Arc<AtomicBool>,
+    start: Instant,
+    /// Whether this is a `go infinite` search, its result is held back until `stop` is received.
+    infinite: bool,
+    /// The result of an infinite search that finished before `stop` was received.
+    held: Option<(T, B::Move)>,
+}
+
+impl<B: Board, C: BoardCodec<B>, T: Bot<B> + Send + 'static> EngineServer<B, C, T> {
+    pub fn new(codec: C, name: &str, author: &str) -> Self {
+        let (sender, receiver) = channel();
+        EngineServer {
+            codec,
+            name: name.to_owned(),
+            author: author.to_owned(),
+            options: vec![],
+            option_handler: None,
+            sender,
+            receiver,
+        }
+    }
+
+    /// Declare the options of this engine. When a `setoption` command for one of them is received,
+    /// `handler` is called with the bot, the declared name of the option and the value.
+    /// Options are matched case-insensitively, unknown options are reported as warnings.
+    pub fn with_options(
+        mut self,
+        options: Vec<EngineOption>,
+        handler: impl FnMut(&mut T, &str, Option<&str>) -> Result<(), String> + 'static,
+    ) -> Self {
+        self.options = options;
+        self.option_handler = Some(Box::new(handler));
+        self
+    }
+
+    /// A callback that sends [SearchInfo] to the client as `info` responses.
+    /// Pass this to the bot, eg. using [MCTSBot::with_info_callback](crate::ai::mcts::MCTSBot::with_info_callback).
+    pub fn info_callback(&self) -> InfoCallback<B::Move> {
+        let sender = self.sender.clone();
+        Box::new(move |info: &SearchInfo<B::Move>| {
+            // the server might have stopped already, in which case there is no one left to report to
+            let _ = sender.send(Event::Message(Message::Info(info.clone())));
+        })
+    }
+
+    /// Run the server until `quit` is received or `input` ends.
+    /// `input` is read on a separate thread, so the server can react to `stop` while the bot is searching.
+    pub fn run(
+        self,
+        bot: T,
+        input: impl Read + Send + 'static,
+        output: impl Write,
+        log: impl Write,
+    ) -> std::io::Result<()> {
+        let mut session = Session {
+            server: self,
+            state: State {
+                bot: Some(bot),
+                board_stack: VecDeque::new(),
+                search: None,
+            },
+        };
+        crate::interface::runner::run(&mut session, input, output, log)
+    }
+
+    /// Handle a single command while no search is running, returns whether the server should quit.
+    fn handle_command<O: Write, L: Write>(
+        &mut self,
+        state: &mut State<B, T>,
+        output: &mut Output<O, L>,
+        line: &str,
+    ) -> std::io::Result<bool> {
+        while state.board_stack.len() > MAX_STACK_SIZE {
+            state.board_stack.pop_back();
+        }
+
+        let command = match Command::parse_protocol(self.codec.protocol(), line) {
+            Ok(command) => command,
+            Err(_) => {
+                output.respond(format!("info string error: failed to parse command '{}'", line))?;
+                return Ok(false);
+            }
+        };
+
+        match command {
+            Command::Uai => {
+                output.respond(format!("id name {}", self.name))?;
+                output.respond(format!("id author {}", self.author))?;
+                for option in &self.options {
+                    output.respond(option)?;
+                }
+                output.respond(format!("{}ok", self.codec.protocol()))?;
+            }
+            Command::IsReady => output.respond("readyok")?,
+            Command::NewGame => {
+                state.board_stack.clear();
+                state.board_stack.push_front(self.codec.start_position());
+            }
+            Command::Quit => return Ok(true),
+            Command::Takeback => {
+                if state.board_stack.pop_front().is_none() {
+                    output.respond("info string error: cannot takeback, board stack is empty")?;
+                }
+            }
+            Command::Print => match state.board_stack.front() {
+                Some(board) => {
+                    output.respond(format!("info string position {}", self.codec.format_position(board)))?;
+                    for line in board.to_string().lines() {
+                        output.respond(format!("info string {}", line))?;
+                    }
+                }
+                None => output.respond("info string error: cannot print, no board")?,
+            },
+            Command::Position { position, moves } => {
+                let board = match position {
+                    Position::StartPos => self.codec.start_position(),
+                    Position::Fen(fen) => match self.codec.parse_position(fen) {
+                        Ok(board) => board,
+                        Err(e) => {
+                            output.respond(format!("info string error: invalid position '{}': {:?}", fen, e))?;
+                            return Ok(false);
+                        }
+                    },
+                };
+                state.board_stack.push_front(board);
+                if let Some(moves) = moves {
+                    self.apply_moves(state, output, moves)?;
+                }
+            }
+            Command::Moves(moves) => self.apply_moves(state, output, moves)?,
+            Command::Go(settings) => {
+                let board = match state.board_stack.front() {
+                    Some(board) => board.clone(),
+                    None => {
+                        output.respond("info string error: received go command without having a board")?;
+                        return Ok(false);
+                    }
+                };
+                if let Some(outcome) = board.outcome() {
+                    output.respond(format!(
+                        "info string error: cannot go on done board, outcome: {:?}",
+                        outcome
+                    ))?;
+                    return Ok(false);
+                }
+
+                let stop = Arc::new(AtomicBool::new(false));
+                let limits = settings.to_limits(board.next_player()).with_stop(stop.clone());
+
+                let mut bot = state.bot.take().expect("bot should be available when not searching");
+                let search_board = board.clone();
+                spawn_search(self.sender.clone(), move || {
+                    let mv = bot.select_move_limited(&search_board, &limits);
+                    Message::Done { bot, mv }
+                });
+
+                state.search = Some(Search {
+                    board,
+                    stop,
+                    start: Instant::now(),
+                    infinite: settings.infinite,
+                    held: None,
+                });
+            }
+            // there is no search to stop
+            Command::Stop => {}
+            Command::SetOption { name, value } => {
+                let value = Some(value).filter(|value| !value.is_empty());
+                let option = self
+                    .options
+                    .iter()
+                    .find(|option| option.name.eq_ignore_ascii_case(name));
+
+                match (option, &mut self.option_handler) {
+                    (Some(option), Some(handler)) => {
+                        let bot = state.bot.as_mut().expect("bot should be available when not searching");
+                        if let Err(e) = handler(bot, &option.name, value) {
+                            output.respond(format!("info string error: failed to set option {}: {}", name, e))?;
+                        }
+                    }
+                    _ => {
+                        output.respond(format!(
+                            "info string warning: ignoring unknown option, name={}, value={:?}",
+                            name, value
+                        ))?;
+                    }
+                }
+            }
+        }
+
+        Ok(false)
+    }
+
+    fn apply_moves<O: Write, L: Write>(
+        &self,
+        state: &mut State<B, T>,
+        output: &mut Output<O, L>,
+        moves: &str,
+    ) -> std::io::Result<()> {
+        let mut curr_board = match state.board_stack.front() {
+            None => {
+                output.respond("info string error: received moves command without having a board")?;
+                return Ok(());
+            }
+            Some(board) => board.clone(),
+        };
+
+        for mv_str in moves.split_whitespace() {
+            if curr_board.is_done() {
+                output.respond(format!(
+                    "info string error: cannot play move '{}', board is already done",
+                    mv_str
+                ))?;
+                return Ok(());
+            }
+
+            let mv = match self.codec.parse_move(&curr_board, mv_str) {
+                Ok(mv) => mv,
+                Err(e) => {
+                    output.respond(format!("info string error: invalid move '{}': {:?}", mv_str, e))?;
+                    return Ok(());
+                }
+            };
+
+            if !curr_board.is_available_move(mv) {
+                output.respond(format!("info string error: move '{}' is not available", mv_str))?;
+                return Ok(());
+            }
+
+            curr_board.play(mv);
+            state.board_stack.push_front(curr_board.clone());
+        }
+
+        Ok(())
+    }
+}
+
+impl<B: Board, C: BoardCodec<B>, T> Debug for EngineServer<B, C, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "EngineServer {{ name: {}, author: {}, codec: {:?}, options: {:?} }}",
+            self.name, self.author, self.codec, self.options
+        )
+    }
+}
+
+struct State<B: Board, T> {
+    /// The bot, `None` while it is searching on another thread.
+    bot: Option<T>,
+    board_stack: VecDeque<B>,
+    search: Option<Search<B, T>>,
+}
+
+/// An [EngineServer] together with the state of a single [EngineServer::run].
+struct Session<B: Board, C: BoardCodec<B>, T> {
+    server: EngineServer<B, C, T>,
+    state: State<B, T>,
+}
+
+impl<B: Board, C: BoardCodec<B>, T> Session<B, C, T> {
+    /// End the current search by sending `bestmove` and give the bot back.
+    fn finish_search<O: Write, L: Write>(
+        &mut self,
+        output: &mut Output<O, L>,
+        bot: T,
+        mv: B::Move,
+    ) -> std::io::Result<()> {
+        let search = self
+            .state
+            .search
+            .take()
+            .expect("finished search without running search");
+        self.state.bot = Some(bot);
+
+        output.log(&format!("time used: {}s", search.start.elapsed().as_secs_f32()))?;
+        let codec = &self.server.codec;
+        output.respond(format!("bestmove {}", codec.format_move(&search.board, mv)))
+    }
+}
+
+impl<B: Board, C: BoardCodec<B>, T: Bot<B> + Send + 'static> Protocol for Session<B, C, T> {
+    type Message = Message<B, T>;
+
+    fn sender(&self) -> &Sender<Event<Self::Message>> {
+        &self.server.sender
+    }
+
+    fn receiver(&self) -> &Receiver<Event<Self::Message>> {
+        &self.server.receiver
+    }
+
+    fn is_searching(&self) -> bool {
+        self.state.search.is_some()
+    }
+
+    fn stop_search(&self) {
+        if let Some(search) = &self.state.search {
+            search.stop.store(true, Ordering::Relaxed);
+            if search.infinite {
+                let _ = self.server.sender.send(Event::Message(Message::Stopped));
+            }
+        }
+    }
+
+    fn search_command(&self, line: &str) -> SearchCommand {
+        match Command::parse_protocol(self.server.codec.protocol(), line) {
+            Ok(Command::Stop) => SearchCommand::Stop,
+            Ok(Command::IsReady) => SearchCommand::IsReady,
+            Ok(Command::Quit) => SearchCommand::Quit,
+            _ => SearchCommand::Later,
+        }
+    }
+
+    fn handle_command<O: Write, L: Write>(&mut self, output: &mut Output<O, L>, line: &str) -> std::io::Result<bool> {
+        self.server.handle_command(&mut self.state, output, line)
+    }
+
+    fn handle_message<O: Write, L: Write>(
+        &mut self,
+        output: &mut Output<O, L>,
+        message: Self::Message,
+    ) -> std::io::Result<()> {
+        match message {
+            Message::Info(info) => {
+                if let Some(search) = &self.state.search {
+                    output.respond(self.server.codec.format_info(&search.board, &info))?;
+                }
+            }
+            Message::Done { bot, mv } => {
+                let search = self
+                    .state
+                    .search
+                    .as_mut()
+                    .expect("received search result without running search");
+
+                if search.infinite && !search.stop.load(Ordering::Relaxed) {
+                    search.held = Some((bot, mv));
+                } else {
+                    self.finish_search(output, bot, mv)?;
+                }
+            }
+            Message::Stopped => {
+                let held = self.state.search.as_mut().and_then(|search| search.held.take());
+                if let Some((bot, mv)) = held {
+                    self.finish_search(output, bot, mv)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn format_error(&self, error: &str) -> String {
+        format!("info string error: {}", error)
+    }
+}
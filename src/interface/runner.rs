@@ -0,0 +1,197 @@
+//! The event loop shared by the engine servers, the protocol specific parts are provided by a [Protocol].
+//!
+//! Input is read on a separate thread and searches run on yet another thread, so the server can react to
+//! `stop`, `isready` and `quit` while the bot is searching. Other commands are queued until the search finishes.
+use std::any::Any;
+use std::collections::VecDeque;
+use std::fmt::Display;
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::mpsc::{Receiver, Sender};
+use std::thread;
+
+pub(crate) enum Event<M> {
+    Line(String),
+    /// A message sent by the search thread or by a callback passed to the bot.
+    Message(M),
+    /// The bot panicked during the search, with the panic message if there is one.
+    Panicked(String),
+}
+
+/// The way a command received while searching is handled.
+pub(crate) enum SearchCommand {
+    Stop,
+    IsReady,
+    Quit,
+    /// Handle the command after the search finishes.
+    Later,
+}
+
+pub(crate) trait Protocol {
+    type Message;
+
+    fn sender(&self) -> &Sender<Event<Self::Message>>;
+
+    fn receiver(&self) -> &Receiver<Event<Self::Message>>;
+
+    fn is_searching(&self) -> bool;
+
+    /// Ask the running search to stop as soon as possible.
+    fn stop_search(&self);
+
+    fn search_command(&self, line: &str) -> SearchCommand;
+
+    /// Handle a single command while no search is running, returns whether the server should quit.
+    fn handle_command<O: Write, L: Write>(&mut self, output: &mut Output<O, L>, line: &str) -> std::io::Result<bool>;
+
+    fn handle_message<O: Write, L: Write>(
+        &mut self,
+        output: &mut Output<O, L>,
+        message: Self::Message,
+    ) -> std::io::Result<()>;
+
+    /// Format `error` as a single response.
+    fn format_error(&self, error: &str) -> String;
+}
+
+/// Run `protocol` until `quit` is received or `input` ends.
+///
+/// If the bot panics during a search the error is reported to the client and an error is returned,
+/// there is no bot left to continue with.
+pub(crate) fn run<P: Protocol>(
+    protocol: &mut P,
+    input: impl Read + Send + 'static,
+    output: impl Write,
+    log: impl Write,
+) -> std::io::Result<()>
+where
+    P::Message: Send + 'static,
+{
+    let mut output = Output {
+        output: BufWriter::new(output),
+        log: BufWriter::new(log),
+    };
+
+    let input_sender = protocol.sender().clone();
+    thread::spawn(move || {
+        for line in BufReader::new(input).lines() {
+            let line = match line {
+                Ok(line) => line,
+                Err(_) => break,
+            };
+            if input_sender.send(Event::Line(line)).is_err() {
+                return;
+            }
+        }
+        // treat the end of the input as quit
+        let _ = input_sender.send(Event::Line("quit".to_owned()));
+    });
+
+    let mut pending: VecDeque<String> = VecDeque::new();
+    let mut quitting = false;
+
+    loop {
+        output.flush()?;
+
+        // handle commands that arrived during the last search first
+        if !protocol.is_searching() {
+            if let Some(line) = pending.pop_front() {
+                if protocol.handle_command(&mut output, &line)? {
+                    return Ok(());
+                }
+                continue;
+            }
+        }
+
+        let event = match protocol.receiver().recv() {
+            Ok(event) => event,
+            // the protocol always keeps a sender around itself, so this can't happen
+            Err(_) => unreachable!(),
+        };
+
+        match event {
+            Event::Line(line) => {
+                let line = line.trim();
+                output.log(&format!("> {}", line))?;
+
+                if line.is_empty() {
+                    continue;
+                }
+
+                if protocol.is_searching() {
+                    match protocol.search_command(line) {
+                        SearchCommand::Stop => protocol.stop_search(),
+                        SearchCommand::IsReady => output.respond("readyok")?,
+                        SearchCommand::Quit => {
+                            protocol.stop_search();
+                            quitting = true;
+                        }
+                        SearchCommand::Later => pending.push_back(line.to_owned()),
+                    }
+                } else if protocol.handle_command(&mut output, line)? {
+                    return Ok(());
+                }
+            }
+            Event::Message(message) => {
+                protocol.handle_message(&mut output, message)?;
+
+                if quitting && !protocol.is_searching() {
+                    output.flush()?;
+                    return Ok(());
+                }
+            }
+            Event::Panicked(message) => {
+                let error = format!("bot panicked during search: {}", message);
+                output.respond(protocol.format_error(&error))?;
+                output.flush()?;
+                return Err(std::io::Error::other(error));
+            }
+        }
+    }
+}
+
+/// Run `search` on a new thread and send its result, or [Event::Panicked] if it panics.
+pub(crate) fn spawn_search<M: Send + 'static>(sender: Sender<Event<M>>, search: impl FnOnce() -> M + Send + 'static) {
+    thread::spawn(move || {
+        let event = match catch_unwind(AssertUnwindSafe(search)) {
+            Ok(message) => Event::Message(message),
+            Err(payload) => Event::Panicked(panic_message(payload.as_ref())),
+        };
+        let _ = sender.send(event);
+    });
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(&s) = payload.downcast_ref::<&str>() {
+        s.to_owned()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "unknown panic".to_owned()
+    }
+}
+
+pub(crate) struct Output<O, L> {
+    output: O,
+    log: L,
+}
+
+impl<O: Write, L: Write> Output<O, L> {
+    pub fn respond(&mut self, response: impl Display) -> std::io::Result<()> {
+        let s = response.to_string();
+        assert!(!s.contains('\n'), "response cannot contain newline");
+        writeln!(&mut self.log, "< {}", s)?;
+        writeln!(&mut self.output, "{}", s)?;
+        Ok(())
+    }
+
+    pub fn log(&mut self, s: &str) -> std::io::Result<()> {
+        writeln!(&mut self.log, "{}", s)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.output.flush()?;
+        self.log.flush()?;
+        Ok(())
+    }
+}
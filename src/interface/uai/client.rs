@@ -5,7 +5,7 @@ use std::time::Instant;
 
 use crate::board::{Board, Player};
 use crate::games::ataxx::{AtaxxBoard, Move};
-use crate::interface::uai::command::{Command, GoTimeSettings, Position};
+use crate::interface::uai::command::{Command, Position};
 
 pub const MAX_STACK_SIZE: usize = 100;
 
@@ -97,7 +97,7 @@ pub fn run(
             Command::Moves(moves) => {
                 apply_moves(&mut output, &mut board_stack, moves)?;
             }
-            Command::Go(time_settings) => {
+            Command::Go(settings) => {
                 let curr_board = match board_stack.front() {
                     Some(curr_board) => curr_board,
                     None => {
@@ -114,16 +114,20 @@ pub fn run(
                     continue;
                 }
 
-                let time_to_use = match time_settings {
-                    GoTimeSettings::Move(time) => 0.95 * (time as f32 / 1000.0),
-                    GoTimeSettings::Clock { w_time, b_time, .. } => {
-                        let time_left_ms = match curr_board.next_player() {
-                            Player::A => w_time,
-                            Player::B => b_time,
-                        };
+                let time_left = match curr_board.next_player() {
+                    Player::A => settings.w_time,
+                    Player::B => settings.b_time,
+                };
+                let time_to_use = match (settings.move_time, time_left) {
+                    (Some(time), _) => 0.95 * (time as f32 / 1000.0),
+                    (None, Some(time_left_ms)) => {
                         let time_left = time_left_ms as f32 / 1000.0;
                         time_left / 30.0
                     }
+                    (None, None) => {
+                        output.respond("info (error): only movetime and clock time settings are supported")?;
+                        continue;
+                    }
                 };
 
                 output.respond(&format!("info (info): planning to use {}s", time_to_use))?;
@@ -137,6 +141,8 @@ pub fn run(
                 output.respond(&format!("info (info): {}", info))?;
                 output.respond(&format!("bestmove {}", best_move.to_uai()))?;
             }
+            // the search is synchronous, so there is never anything to stop
+            Command::Stop => {}
             Command::Quit => return Ok(()),
         }
     }
@@ -0,0 +1,36 @@
+use crate::games::ataxx::{AtaxxBoard, InvalidAtaxxFen, InvalidUaiMove, Move};
+use crate::interface::engine::BoardCodec;
+
+/// The [BoardCodec] for UAI, to run an Ataxx engine with an
+/// [EngineServer](crate::interface::engine::server::EngineServer).
+#[derive(Debug, Copy, Clone, Default)]
+pub struct UaiCodec;
+
+impl BoardCodec<AtaxxBoard> for UaiCodec {
+    type PositionError = InvalidAtaxxFen;
+    type MoveError = InvalidUaiMove;
+
+    fn protocol(&self) -> &str {
+        "uai"
+    }
+
+    fn start_position(&self) -> AtaxxBoard {
+        AtaxxBoard::default()
+    }
+
+    fn parse_position(&self, position: &str) -> Result<AtaxxBoard, InvalidAtaxxFen> {
+        AtaxxBoard::from_fen(position)
+    }
+
+    fn format_position(&self, board: &AtaxxBoard) -> String {
+        board.to_fen()
+    }
+
+    fn parse_move(&self, _: &AtaxxBoard, mv: &str) -> Result<Move, InvalidUaiMove> {
+        Move::from_uai(mv)
+    }
+
+    fn format_move(&self, _: &AtaxxBoard, mv: Move) -> String {
+        mv.to_uai()
+    }
+}
@@ -1,5 +1,11 @@
+use std::time::Duration;
+
+use crate::ai::limits::{Clock, SearchLimits};
+use crate::board::Player;
+
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum Command<'a> {
+    /// The protocol handshake, `uai` or the protocol passed to [Command::parse_protocol].
     Uai,
     IsReady,
     NewGame,
@@ -10,7 +16,9 @@ pub enum Command<'a> {
         position: Position<'a>,
         moves: Option<&'a str>,
     },
-    Go(GoTimeSettings),
+    Go(GoSettings),
+    Stop,
+    /// `value` is empty for options without a value, eg. buttons.
     SetOption {
         name: &'a str,
         value: &'a str,
@@ -18,15 +26,21 @@ pub enum Command<'a> {
     Moves(&'a str),
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
-pub enum GoTimeSettings {
-    Move(u32),
-    Clock {
-        b_time: u32,
-        w_time: u32,
-        b_inc: u32,
-        w_inc: u32,
-    },
+/// The arguments of the `go` command, all times are in milliseconds.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub struct GoSettings {
+    pub move_time: Option<u32>,
+    /// The time left for the first player.
+    pub w_time: Option<u32>,
+    /// The time left for the second player.
+    pub b_time: Option<u32>,
+    pub w_inc: Option<u32>,
+    pub b_inc: Option<u32>,
+    pub moves_to_go: Option<u32>,
+    pub nodes: Option<u64>,
+    pub depth: Option<u32>,
+    /// Search until `stop` is received.
+    pub infinite: bool,
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
@@ -37,49 +51,195 @@ pub enum Position<'a> {
 
 impl<'a> Command<'a> {
     pub fn parse(input: &'a str) -> Result<Command, nom::Err<nom::error::Error<&str>>> {
-        parse::command()(input).map(|(left, command)| {
+        Self::parse_protocol("uai", input)
+    }
+
+    /// Like [Command::parse], but with `protocol` instead of `uai` in the handshake and new game commands,
+    /// eg. `uci` and `ucinewgame`.
+    pub fn parse_protocol(protocol: &str, input: &'a str) -> Result<Command<'a>, nom::Err<nom::error::Error<&'a str>>> {
+        parse::command(protocol, input).map(|(left, command)| {
             assert!(left.is_empty());
             command
         })
     }
+
+    /// Format this command as a single line, the inverse of [Command::parse].
+    /// `protocol` replaces `uai` in the handshake and new game commands, so the same commands can be sent to
+    /// UCI engines.
+    pub fn format(&self, protocol: &str) -> String {
+        match self {
+            Command::Uai => protocol.to_owned(),
+            Command::IsReady => "isready".to_owned(),
+            Command::NewGame => format!("{}newgame", protocol),
+            Command::Quit => "quit".to_owned(),
+            Command::Takeback => "takeback".to_owned(),
+            Command::Print => "print".to_owned(),
+            Command::Position { position, moves } => {
+                let mut result = match position {
+                    Position::StartPos => "position startpos".to_owned(),
+                    Position::Fen(fen) => format!("position fen {}", fen),
+                };
+                if let Some(moves) = moves {
+                    result += &format!(" moves {}", moves);
+                }
+                result
+            }
+            Command::Go(settings) => {
+                let mut result = "go".to_owned();
+                let mut arg = |name: &str, value: Option<String>| {
+                    if let Some(value) = value {
+                        result += &format!(" {} {}", name, value);
+                    }
+                };
+                arg("movetime", settings.move_time.map(|t| t.to_string()));
+                arg("wtime", settings.w_time.map(|t| t.to_string()));
+                arg("btime", settings.b_time.map(|t| t.to_string()));
+                arg("winc", settings.w_inc.map(|t| t.to_string()));
+                arg("binc", settings.b_inc.map(|t| t.to_string()));
+                arg("movestogo", settings.moves_to_go.map(|n| n.to_string()));
+                arg("nodes", settings.nodes.map(|n| n.to_string()));
+                arg("depth", settings.depth.map(|d| d.to_string()));
+                if settings.infinite {
+                    result += " infinite";
+                }
+                result
+            }
+            Command::Stop => "stop".to_owned(),
+            Command::SetOption { name, value: "" } => format!("setoption name {}", name),
+            Command::SetOption { name, value } => format!("setoption name {} value {}", name, value),
+            Command::Moves(moves) => format!("moves {}", moves),
+        }
+    }
+}
+
+impl GoSettings {
+    /// Convert search limits for `player`, the player that is about to move, to settings.
+    /// The clocks are sent as `wtime`/`btime` depending on `player`, all other limits are sent as they are.
+    /// If there are no limits, or only an unlimited number of nodes, `infinite` is sent instead.
+    pub fn from_limits(limits: &SearchLimits, player: Player) -> GoSettings {
+        let millis = |d: Duration| d.as_millis().min(u32::MAX as u128) as u32;
+
+        let (w_clock, b_clock) = match player {
+            Player::A => (limits.clock, limits.opponent_clock),
+            Player::B => (limits.opponent_clock, limits.clock),
+        };
+        let nodes = limits.nodes.filter(|&nodes| nodes != u64::MAX);
+
+        GoSettings {
+            move_time: limits.move_time.map(millis),
+            w_time: w_clock.map(|clock| millis(clock.remaining)),
+            b_time: b_clock.map(|clock| millis(clock.remaining)),
+            w_inc: w_clock.map(|clock| millis(clock.increment)),
+            b_inc: b_clock.map(|clock| millis(clock.increment)),
+            moves_to_go: limits.clock.and_then(|clock| clock.moves_to_go),
+            nodes,
+            depth: limits.depth,
+            infinite: limits.move_time.is_none() && limits.clock.is_none() && nodes.is_none() && limits.depth.is_none(),
+        }
+    }
+
+    /// Convert these settings to search limits for `player`, the player that is about to move.
+    /// `infinite` is represented as an unlimited number of nodes, so the search only ends when it is stopped
+    /// or when the bot has nothing left to search.
+    pub fn to_limits(&self, player: Player) -> SearchLimits {
+        let millis = |t: u32| Duration::from_millis(t as u64);
+        let clock = |time: Option<u32>, inc: Option<u32>| {
+            time.map(|time| Clock {
+                remaining: millis(time),
+                increment: millis(inc.unwrap_or(0)),
+                moves_to_go: self.moves_to_go,
+            })
+        };
+
+        let white = clock(self.w_time, self.w_inc);
+        let black = clock(self.b_time, self.b_inc);
+        let (clock, opponent_clock) = match player {
+            Player::A => (white, black),
+            Player::B => (black, white),
+        };
+
+        SearchLimits {
+            move_time: self.move_time.map(millis),
+            clock,
+            opponent_clock,
+            nodes: if self.infinite { Some(u64::MAX) } else { self.nodes },
+            depth: self.depth,
+            stop: None,
+        }
+    }
 }
 
 mod parse {
     use nom::branch::alt;
     use nom::bytes::complete::{tag, take_until, take_while};
-    use nom::character::complete::digit1;
-    use nom::combinator::{eof, map, opt, value};
+    use nom::character::complete::{digit1, space1};
+    use nom::combinator::{eof, map, map_res, opt, value};
+    use nom::multi::fold_many0;
     use nom::sequence::{preceded, terminated, tuple};
     use nom::IResult;
 
     use super::*;
 
-    pub fn command<'a>() -> impl FnMut(&'a str) -> IResult<&'a str, Command<'a>> {
-        let int = || map(digit1, |s: &str| s.parse().unwrap());
+    #[derive(Debug, Copy, Clone)]
+    enum GoArg {
+        MoveTime(u32),
+        WTime(u32),
+        BTime(u32),
+        WInc(u32),
+        BInc(u32),
+        MovesToGo(u32),
+        Nodes(u64),
+        Depth(u32),
+        Infinite,
+    }
 
-        let move_time = preceded(tag("movetime "), map(int(), GoTimeSettings::Move));
+    fn int<T: std::str::FromStr>(input: &str) -> IResult<&str, T> {
+        map_res(digit1, |s: &str| s.parse())(input)
+    }
 
-        let clock_time = map(
-            tuple((
-                tag("btime "),
-                int(),
-                tag(" wtime "),
-                int(),
-                tag(" binc "),
-                int(),
-                tag(" winc "),
-                int(),
-            )),
-            |(_, b_time, _, w_time, _, b_inc, _, w_inc)| GoTimeSettings::Clock {
-                b_time,
-                w_time,
-                b_inc,
-                w_inc,
-            },
-        );
+    fn go_arg(input: &str) -> IResult<&str, GoArg> {
+        alt((
+            map(preceded(tag("movetime "), int), GoArg::MoveTime),
+            map(preceded(tag("wtime "), int), GoArg::WTime),
+            map(preceded(tag("btime "), int), GoArg::BTime),
+            map(preceded(tag("winc "), int), GoArg::WInc),
+            map(preceded(tag("binc "), int), GoArg::BInc),
+            map(preceded(tag("movestogo "), int), GoArg::MovesToGo),
+            map(preceded(tag("nodes "), int), GoArg::Nodes),
+            map(preceded(tag("depth "), int), GoArg::Depth),
+            value(GoArg::Infinite, tag("infinite")),
+        ))(input)
+    }
+
+    fn go(input: &str) -> IResult<&str, GoSettings> {
+        preceded(
+            tag("go"),
+            fold_many0(preceded(space1, go_arg), GoSettings::default, |mut settings, arg| {
+                match arg {
+                    GoArg::MoveTime(t) => settings.move_time = Some(t),
+                    GoArg::WTime(t) => settings.w_time = Some(t),
+                    GoArg::BTime(t) => settings.b_time = Some(t),
+                    GoArg::WInc(t) => settings.w_inc = Some(t),
+                    GoArg::BInc(t) => settings.b_inc = Some(t),
+                    GoArg::MovesToGo(n) => settings.moves_to_go = Some(n),
+                    GoArg::Nodes(n) => settings.nodes = Some(n),
+                    GoArg::Depth(d) => settings.depth = Some(d),
+                    GoArg::Infinite => settings.infinite = true,
+                }
+                settings
+            }),
+        )(input)
+    }
 
-        let go = preceded(tag("go "), map(alt((move_time, clock_time)), Command::Go));
+    /// A move list following `prefix`, which may be empty: `moves` parses as an empty list.
+    fn moves<'a>(prefix: &'static str) -> impl FnMut(&'a str) -> IResult<&'a str, &'a str> {
+        preceded(
+            tag(prefix),
+            alt((preceded(tag(" "), take_while(|_| true)), value("", eof))),
+        )
+    }
 
+    pub fn command<'a>(protocol: &str, input: &'a str) -> IResult<&'a str, Command<'a>> {
         let position = map(
             tuple((
                 tag("position "),
@@ -90,40 +250,43 @@ mod parse {
                         map(alt((take_until(" moves"), take_while(|_| true))), Position::Fen),
                     ),
                 )),
-                opt(preceded(tag(" moves "), take_while(|_| true))),
+                opt(moves(" moves")),
             )),
             |(_, position, moves)| Command::Position { position, moves },
         );
 
-        let moves = map(preceded(tag("moves "), take_while(|_| true)), |moves| {
-            Command::Moves(moves)
-        });
-
         let set_option = preceded(
             tag("setoption "),
             map(
-                tuple((tag("name "), take_until(" "), tag(" value "), take_while(|_| true))),
-                |(_, name, _, value)| Command::SetOption { name, value },
+                tuple((
+                    tag("name "),
+                    alt((take_until(" value "), take_while(|_| true))),
+                    opt(preceded(tag(" value "), take_while(|_| true))),
+                )),
+                |(_, name, value)| Command::SetOption {
+                    name,
+                    value: value.unwrap_or(""),
+                },
             ),
         );
 
         let main = alt((
-            value(Command::NewGame, tag("uainewgame")),
-            value(Command::Uai, tag("uai")),
+            value(Command::NewGame, tuple((tag(protocol), tag("newgame")))),
+            value(Command::Uai, tuple((tag(protocol), eof))),
             value(Command::IsReady, tag("isready")),
             value(Command::Quit, tag("quit")),
+            value(Command::Stop, tag("stop")),
             value(Command::Takeback, tag("takeback")),
             value(Command::Print, alt((tag("print"), tag("d")))),
             position,
-            moves,
-            go,
+            map(moves("moves"), Command::Moves),
+            map(go, Command::Go),
             set_option,
         ));
 
-        terminated(main, eof)
+        terminated(main, eof)(input)
     }
 }
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -178,4 +341,164 @@ mod tests {
             Command::parse("position fen x5o/2o2o1/7/7/4x2/5xx/o6 x 1 4 moves a b c")
         )
     }
+
+    #[test]
+    fn position_empty_moves() {
+        assert_eq!(
+            Ok(Command::Position {
+                position: Position::StartPos,
+                moves: Some(""),
+            }),
+            Command::parse("position startpos moves")
+        );
+        assert_eq!(Ok(Command::Moves("")), Command::parse("moves"));
+        assert!(Command::parse("position startpos movesa1").is_err());
+    }
+
+    #[test]
+    fn protocol() {
+        assert_eq!(Ok(Command::Uai), Command::parse_protocol("uci", "uci"));
+        assert_eq!(Ok(Command::NewGame), Command::parse_protocol("uci", "ucinewgame"));
+        assert!(Command::parse_protocol("uai", "uci").is_err());
+    }
+
+    #[test]
+    fn go() {
+        let go = |settings| Ok(Command::Go(settings));
+        let default = GoSettings::default();
+
+        assert_eq!(go(default), Command::parse("go"));
+        let expected = GoSettings {
+            move_time: Some(100),
+            ..default
+        };
+        assert_eq!(go(expected), Command::parse("go movetime 100"));
+        let expected = GoSettings {
+            nodes: Some(500),
+            depth: Some(3),
+            ..default
+        };
+        assert_eq!(go(expected), Command::parse("go nodes 500 depth 3"));
+        let expected = GoSettings {
+            infinite: true,
+            ..default
+        };
+        assert_eq!(go(expected), Command::parse("go infinite"));
+
+        let expected = GoSettings {
+            w_time: Some(1000),
+            b_time: Some(2000),
+            w_inc: Some(10),
+            b_inc: Some(20),
+            moves_to_go: Some(5),
+            ..default
+        };
+        assert_eq!(
+            go(expected),
+            Command::parse("go btime 2000 wtime 1000 binc 20 winc 10 movestogo 5")
+        );
+    }
+
+    #[test]
+    fn set_option() {
+        assert_eq!(
+            Ok(Command::SetOption {
+                name: "Clear Hash",
+                value: "",
+            }),
+            Command::parse("setoption name Clear Hash")
+        );
+        assert_eq!(
+            Ok(Command::SetOption {
+                name: "Move Overhead",
+                value: "10",
+            }),
+            Command::parse("setoption name Move Overhead value 10")
+        );
+    }
+
+    #[test]
+    fn limits() {
+        let clock = |remaining: u64| Clock {
+            remaining: Duration::from_millis(remaining),
+            increment: Duration::from_millis(10),
+            moves_to_go: Some(5),
+        };
+        let limits = SearchLimits {
+            clock: Some(clock(1000)),
+            nodes: Some(500),
+            depth: Some(3),
+            ..SearchLimits::default()
+        }
+        .with_opponent_clock(clock(2000));
+
+        let settings = GoSettings::from_limits(&limits, Player::B);
+        let expected = GoSettings {
+            w_time: Some(2000),
+            b_time: Some(1000),
+            w_inc: Some(10),
+            b_inc: Some(10),
+            moves_to_go: Some(5),
+            nodes: Some(500),
+            depth: Some(3),
+            ..GoSettings::default()
+        };
+        assert_eq!(settings, expected);
+
+        let round_trip = settings.to_limits(Player::B);
+        assert_eq!(round_trip.clock, Some(clock(1000)));
+        assert_eq!(round_trip.opponent_clock, Some(clock(2000)));
+        assert_eq!((round_trip.nodes, round_trip.depth), (Some(500), Some(3)));
+
+        let settings = GoSettings::from_limits(&SearchLimits::nodes(u64::MAX), Player::A);
+        assert!(settings.infinite);
+        assert_eq!(settings.nodes, None);
+    }
+
+    #[test]
+    fn format_round_trip() {
+        let commands = [
+            Command::Uai,
+            Command::NewGame,
+            Command::Stop,
+            Command::Position {
+                position: Position::Fen("x5o/7/7/7/7/7/o5x x 0 1"),
+                moves: Some("a1 b2"),
+            },
+            Command::Position {
+                position: Position::StartPos,
+                moves: None,
+            },
+            Command::Position {
+                position: Position::StartPos,
+                moves: Some(""),
+            },
+            Command::Go(GoSettings {
+                b_time: Some(1000),
+                w_time: Some(2000),
+                b_inc: Some(10),
+                w_inc: Some(20),
+                moves_to_go: Some(3),
+                ..GoSettings::default()
+            }),
+            Command::Go(GoSettings {
+                nodes: Some(500),
+                infinite: true,
+                ..GoSettings::default()
+            }),
+            Command::SetOption {
+                name: "Hash",
+                value: "128",
+            },
+            Command::SetOption {
+                name: "Clear Hash",
+                value: "",
+            },
+        ];
+
+        for command in commands {
+            let line = command.format("uai");
+            assert_eq!(Ok(command), Command::parse(&line), "line: {:?}", line);
+        }
+    }
 }
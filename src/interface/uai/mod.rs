@@ -2,6 +2,10 @@
 //!
 //! A derivative of the UCI protocol for the game Ataxx.
 //! Loose specification available at <https://ataxx.org/#comm>.
+//!
+//! The [client] module contains a simple UAI loop for a bot callback, while [codec::UaiCodec] allows running any
+//! [Bot](crate::ai::Bot) with the generic [EngineServer](crate::interface::engine::server::EngineServer).
 
 pub mod client;
+pub mod codec;
 pub mod command;
@@ -0,0 +1,85 @@
+use std::str::FromStr;
+
+use chess::ChessMove;
+
+use crate::ai::info::Score;
+use crate::games::chess::{ChessBoard, ParseMoveError, Rules};
+use crate::interface::engine::BoardCodec;
+
+/// The largest centipawn score reported, larger scores (eg. solved positions) are clamped to this value.
+pub const MAX_CENTIPAWNS: i32 = 20000;
+
+/// The [BoardCodec] for UCI, to run a chess engine with an
+/// [EngineServer](crate::interface::engine::server::EngineServer).
+///
+/// Scores are reported as `score cp <x>`, followed by `wdl <w> <d> <l>` in permille if the search estimates them.
+#[derive(Debug, Copy, Clone)]
+pub struct UciCodec {
+    rules: Rules,
+    centipawns_per_value: f32,
+}
+
+impl UciCodec {
+    /// Positions are created with `rules`, scalar heuristic values are converted to centipawns
+    /// by multiplying with `centipawns_per_value`.
+    pub fn new(rules: Rules, centipawns_per_value: f32) -> Self {
+        UciCodec {
+            rules,
+            centipawns_per_value,
+        }
+    }
+
+    /// Convert a score to centipawns.
+    ///
+    /// Scores with a WDL estimate are converted from the expected value in `-1..1`
+    /// using the same curve as Leela Chess Zero, other scores are scaled by `centipawns_per_value`.
+    pub fn centipawns(&self, score: &Score) -> i32 {
+        let cp = match score.wdl {
+            Some(wdl) => 290.68063 * (1.5480908 * wdl.value().clamp(-1.0, 1.0)).tan(),
+            None => score.value * self.centipawns_per_value,
+        };
+        (cp.round() as i32).clamp(-MAX_CENTIPAWNS, MAX_CENTIPAWNS)
+    }
+}
+
+/// Standard chess rules, with values in pawns as used by
+/// [ChessPieceValueHeuristic](crate::heuristic::chess::ChessPieceValueHeuristic).
+impl Default for UciCodec {
+    fn default() -> Self {
+        UciCodec::new(Rules::default(), 100.0)
+    }
+}
+
+impl BoardCodec<ChessBoard> for UciCodec {
+    type PositionError = chess::Error;
+    type MoveError = ParseMoveError;
+
+    fn protocol(&self) -> &str {
+        "uci"
+    }
+
+    fn start_position(&self) -> ChessBoard {
+        ChessBoard::default_with_rules(self.rules)
+    }
+
+    fn parse_position(&self, position: &str) -> Result<ChessBoard, chess::Error> {
+        let inner = chess::Board::from_str(position)?;
+        Ok(ChessBoard::new_without_history(inner, self.rules))
+    }
+
+    fn format_position(&self, board: &ChessBoard) -> String {
+        board.inner().to_string()
+    }
+
+    fn parse_move(&self, board: &ChessBoard, mv: &str) -> Result<ChessMove, ParseMoveError> {
+        board.parse_move(mv)
+    }
+
+    fn format_move(&self, _: &ChessBoard, mv: ChessMove) -> String {
+        mv.to_string()
+    }
+
+    fn format_score(&self, score: &Score) -> String {
+        format!("score cp {}", self.centipawns(score))
+    }
+}
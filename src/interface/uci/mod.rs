@@ -0,0 +1,8 @@
+//! The Universal Chess Interface (UCI).
+//!
+//! Specification available at <https://backscattering.de/chess/uci/>.
+//!
+//! [codec::UciCodec] allows running any [Bot](crate::ai::Bot) for [ChessBoard](crate::games::chess::ChessBoard)
+//! with the generic [EngineServer](crate::interface::engine::server::EngineServer).
+
+pub mod codec;
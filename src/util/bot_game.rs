@@ -1,18 +1,22 @@
 //! Utilities to run bots against each other and report the results.
+use std::collections::HashMap;
 use std::fmt::Write;
 use std::fmt::{Debug, Formatter};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Mutex;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use itertools::Itertools;
 use rayon::iter::IntoParallelIterator;
 use rayon::iter::ParallelIterator;
 
+use crate::ai::limits::{Clock, SearchLimits};
 use crate::ai::Bot;
 use crate::board::{Board, Outcome, Player};
 use crate::pov::NonPov;
 use crate::util::rating::elo_from_wdl;
-use crate::wdl::WDL;
+use crate::util::sprt::{Pentanomial, Sprt, SprtResult};
+use crate::wdl::{OutcomeWDL, WDL};
 
 /// Run `bot_l` against `bot_r` against each other on the board given by `start`.
 ///
@@ -28,6 +32,155 @@ pub fn run<B: Board, L: Bot<B>, R: Bot<B>>(
     games_per_side: u32,
     both_sides: bool,
     callback: impl Fn(WDL<u32>, &Replay<B>) + Sync,
+) -> BotGameResult<B> {
+    run_with_time_control(
+        start,
+        bot_l,
+        bot_r,
+        games_per_side,
+        both_sides,
+        TimeControl::Unlimited,
+        callback,
+    )
+}
+
+/// The time control for games run by [run_with_time_control].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum TimeControl {
+    /// Bots are not limited, [Bot::select_move] is used.
+    Unlimited,
+    /// Bots get a fixed amount of time per move. Exceeding it is not penalized.
+    MoveTime(Duration),
+    /// Each side has a clock that starts at `initial` and gets `increment` added after each move.
+    /// A bot that runs out of time loses the game.
+    Clock { initial: Duration, increment: Duration },
+}
+
+/// Same as [run], but bots are called through [Bot::select_move_limited] according to `time_control`.
+#[must_use]
+pub fn run_with_time_control<B: Board, L: Bot<B>, R: Bot<B>>(
+    start: impl Fn() -> B + Sync,
+    bot_l: impl Fn() -> L + Sync,
+    bot_r: impl Fn() -> R + Sync,
+    games_per_side: u32,
+    both_sides: bool,
+    time_control: TimeControl,
+    callback: impl Fn(WDL<u32>, &Replay<B>) + Sync,
+) -> BotGameResult<B> {
+    let stop = AtomicBool::new(false);
+    run_impl(
+        start,
+        bot_l,
+        bot_r,
+        games_per_side,
+        both_sides,
+        time_control,
+        &stop,
+        |_, wdl, replay| callback(wdl, replay),
+    )
+}
+
+/// Same as [run_with_time_control], but stops as soon as `sprt` resolves.
+/// At most `games_per_side` games are played per side, games that are already running when the test resolves
+/// are still finished and included in the result.
+///
+/// If [Sprt::pentanomial] is set `both_sides` must be true, and only complete pairs of games are counted.
+/// `callback` also receives the current log-likelihood ratio.
+#[must_use]
+#[allow(clippy::too_many_arguments)]
+pub fn run_sprt<B: Board, L: Bot<B>, R: Bot<B>>(
+    start: impl Fn() -> B + Sync,
+    bot_l: impl Fn() -> L + Sync,
+    bot_r: impl Fn() -> R + Sync,
+    games_per_side: u32,
+    both_sides: bool,
+    time_control: TimeControl,
+    sprt: Sprt,
+    callback: impl Fn(WDL<u32>, f64, &Replay<B>) + Sync,
+) -> SprtResult<B> {
+    assert!(
+        both_sides || !sprt.pentanomial,
+        "pentanomial statistics require both_sides"
+    );
+
+    let stop = AtomicBool::new(false);
+    let stats = Mutex::new(SprtStats::default());
+
+    let result = run_impl(
+        start,
+        bot_l,
+        bot_r,
+        games_per_side,
+        both_sides,
+        time_control,
+        &stop,
+        |game_i, wdl, replay| {
+            let mut stats = stats.lock().unwrap();
+            stats.add(&sprt, game_i, replay);
+            let llr = stats.llr(&sprt);
+            if sprt.status(llr).is_resolved() {
+                stop.store(true, Ordering::Relaxed);
+            }
+            callback(wdl, llr, replay);
+        },
+    );
+
+    let stats = stats.into_inner().unwrap();
+    let llr = stats.llr(&sprt);
+
+    SprtResult {
+        result,
+        llr,
+        status: sprt.status(llr),
+        pentanomial: sprt.pentanomial.then_some(stats.pentanomial),
+    }
+}
+
+#[derive(Default)]
+struct SprtStats {
+    wdl: WDL<u32>,
+    pentanomial: Pentanomial,
+    /// The first finished game of each incomplete pair.
+    unpaired: HashMap<u32, OutcomeWDL>,
+}
+
+impl SprtStats {
+    fn add<B: Board>(&mut self, sprt: &Sprt, game_i: u32, replay: &Replay<B>) {
+        let outcome = replay.outcome.pov(replay.player_l);
+        self.wdl += outcome.to_wdl();
+
+        if sprt.pentanomial {
+            let pair_i = game_i / 2;
+            match self.unpaired.remove(&pair_i) {
+                Some(other) => self.pentanomial.add_pair(other, outcome),
+                None => {
+                    self.unpaired.insert(pair_i, outcome);
+                }
+            }
+        }
+    }
+
+    fn llr(&self, sprt: &Sprt) -> f64 {
+        if sprt.pentanomial {
+            sprt.llr_pentanomial(self.pentanomial)
+        } else {
+            sprt.llr_trinomial(self.wdl)
+        }
+    }
+}
+
+/// Play games until all are done or `stop` is set, games that have not started yet when `stop` is set are skipped.
+/// `callback` also receives the index of the game.
+#[allow(clippy::too_many_arguments)]
+fn run_impl<B: Board, L: Bot<B>, R: Bot<B>>(
+    start: impl Fn() -> B + Sync,
+    bot_l: impl Fn() -> L + Sync,
+    bot_r: impl Fn() -> R + Sync,
+    games_per_side: u32,
+    both_sides: bool,
+    time_control: TimeControl,
+    stop: &AtomicBool,
+    callback: impl Fn(u32, WDL<u32>, &Replay<B>) + Sync,
 ) -> BotGameResult<B> {
     let callback = &callback;
 
@@ -43,20 +196,25 @@ pub fn run<B: Board, L: Bot<B>, R: Bot<B>>(
     let replays: Vec<Replay<B>> = (0..game_count)
         .into_par_iter()
         .panic_fuse()
-        .map(|game_i| {
+        .filter_map(|game_i| {
+            if stop.load(Ordering::Relaxed) {
+                return None;
+            }
+
             let flip = if both_sides { game_i % 2 == 1 } else { false };
             let pair_i = if both_sides { game_i / 2 } else { game_i };
             let start = &starts[pair_i as usize];
 
-            let replay = play_single_game(start, flip, &mut bot_l(), &mut bot_r());
+            let replay = play_single_game(start, flip, time_control, &mut bot_l(), &mut bot_r());
 
             let mut partial_wdl = partial_wdl.lock().unwrap();
             *partial_wdl += replay.outcome.pov(replay.player_l).to_wdl();
-            callback(*partial_wdl, &replay);
+            callback(game_i, *partial_wdl, &replay);
 
-            replay
+            Some(replay)
         })
         .collect();
+    let game_count = replays.len() as u32;
 
     let total_time_l = replays.iter().map(|r| r.total_time_l).sum::<f32>();
     let total_time_r = replays.iter().map(|r| r.total_time_r).sum::<f32>();
@@ -75,7 +233,13 @@ pub fn run<B: Board, L: Bot<B>, R: Bot<B>>(
     }
 }
 
-fn play_single_game<B: Board>(start: &B, flip: bool, bot_l: &mut impl Bot<B>, bot_r: &mut impl Bot<B>) -> Replay<B> {
+fn play_single_game<B: Board>(
+    start: &B,
+    flip: bool,
+    time_control: TimeControl,
+    bot_l: &mut impl Bot<B>,
+    bot_r: &mut impl Bot<B>,
+) -> Replay<B> {
     let mut board = start.clone();
     let player_l = if flip {
         board.next_player().other()
@@ -89,21 +253,65 @@ fn play_single_game<B: Board>(start: &B, flip: bool, bot_l: &mut impl Bot<B>, bo
     let mut move_count_r: u32 = 0;
     let mut moves = vec![];
 
+    let initial_clock = match time_control {
+        TimeControl::Clock { initial, .. } => initial,
+        TimeControl::Unlimited | TimeControl::MoveTime(_) => Duration::ZERO,
+    };
+    let mut clock_l = initial_clock;
+    let mut clock_r = initial_clock;
+    let mut lost_on_time = None;
+
     loop {
-        match board.outcome() {
+        let outcome = lost_on_time.map(|player: Player| Outcome::WonBy(player.other()));
+
+        match outcome.or_else(|| board.outcome()) {
             None => {
+                let is_l = board.next_player() == player_l;
+                let (clock, opponent_clock) = if is_l {
+                    (&mut clock_l, clock_r)
+                } else {
+                    (&mut clock_r, clock_l)
+                };
+
+                let limits = match time_control {
+                    TimeControl::Unlimited => None,
+                    TimeControl::MoveTime(time) => Some(SearchLimits::move_time(time)),
+                    TimeControl::Clock { increment, .. } => {
+                        let opponent_clock = Clock {
+                            remaining: opponent_clock,
+                            increment,
+                            moves_to_go: None,
+                        };
+                        Some(SearchLimits::clock(*clock, increment).with_opponent_clock(opponent_clock))
+                    }
+                };
+
                 let start_time = Instant::now();
-                let mv = if board.next_player() == player_l {
-                    let mv = bot_l.select_move(&board);
-                    total_time_l += start_time.elapsed().as_secs_f32();
+                let mv = match (is_l, &limits) {
+                    (true, None) => bot_l.select_move(&board),
+                    (true, Some(limits)) => bot_l.select_move_limited(&board, limits),
+                    (false, None) => bot_r.select_move(&board),
+                    (false, Some(limits)) => bot_r.select_move_limited(&board, limits),
+                };
+                let elapsed = start_time.elapsed();
+
+                if is_l {
+                    total_time_l += elapsed.as_secs_f32();
                     move_count_l += 1;
-                    mv
                 } else {
-                    let mv = bot_r.select_move(&board);
-                    total_time_r += start_time.elapsed().as_secs_f32();
+                    total_time_r += elapsed.as_secs_f32();
                     move_count_r += 1;
-                    mv
-                };
+                }
+
+                if let TimeControl::Clock { increment, .. } = time_control {
+                    match clock.checked_sub(elapsed) {
+                        Some(left) => *clock = left + increment,
+                        None => {
+                            lost_on_time = Some(board.next_player());
+                            continue;
+                        }
+                    }
+                }
 
                 moves.push(mv);
                 board.play(mv);
@@ -114,6 +322,7 @@ fn play_single_game<B: Board>(start: &B, flip: bool, bot_l: &mut impl Bot<B>, bo
                     player_l,
                     moves,
                     outcome,
+                    lost_on_time,
                     total_time_l,
                     total_time_r,
                     move_count_l,
@@ -133,6 +342,9 @@ pub struct Replay<B: Board> {
 
     pub moves: Vec<B::Move>,
     pub outcome: Outcome,
+    /// The player that lost the game by running out of time, if any.
+    /// In that case the last move of that player is not included in `moves`.
+    pub lost_on_time: Option<Player>,
 
     pub total_time_l: f32,
     pub total_time_r: f32,
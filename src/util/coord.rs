@@ -69,6 +69,24 @@ impl<const X: u8, const Y: u8> Coord<X, Y> {
     }
 }
 
+/// Format `(x, y)` as a file letter followed by a rank number starting from 1, eg. `a1` for `(0, 0)`.
+pub fn xy_to_algebraic(x: u8, y: u8) -> String {
+    assert!(x < 26, "file {} does not fit in a single letter", x);
+    format!("{}{}", (b'a' + x) as char, y + 1)
+}
+
+/// Parse the output of [xy_to_algebraic] back into `(x, y)`, `None` if the string is not in that format.
+pub fn algebraic_to_xy(s: &str) -> Option<(u8, u8)> {
+    let mut chars = s.chars();
+    let file = chars.next().filter(|c| c.is_ascii_lowercase())?;
+    let rank = chars.as_str();
+    if !rank.starts_with(|c: char| c.is_ascii_digit() && c != '0') {
+        return None;
+    }
+    let rank: u8 = rank.parse().ok()?;
+    Some((file as u8 - b'a', rank - 1))
+}
+
 impl<const X: u8, const Y: u8> Display for Coord<X, Y> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(f, "({}, {})", self.x(), self.y())
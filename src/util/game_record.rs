@@ -0,0 +1,273 @@
+//! A portable text format for [Replay]s, so matches can be archived and loaded again for analysis.
+//!
+//! The format is similar to PGN: each game is a block of `[Key "value"]` tag lines followed by a single line
+//! with the moves separated by spaces, and games are separated by empty lines.
+//! Positions and moves are written using a [BoardCodec], so any board with a codec can be stored.
+//!
+//! ```text
+//! [Start "x5o/7/7/7/7/7/o5x x 0 1"]
+//! [PlayerL "A"]
+//! [BotL "RandomBot"]
+//! [BotR "MCTSBot { iterations: 1000 }"]
+//! [Outcome "B"]
+//! [TimeL "0.0012"]
+//! [TimeR "1.53"]
+//! [MoveCountL "21"]
+//! [MoveCountR "21"]
+//! f1 a2 g2 ...
+//! ```
+//!
+//! `Outcome` is `A`, `B` or `Draw`. The optional `LostOnTime` tag contains the player that ran out of time.
+//! Tag values are escaped with `\"`, `\\` and `\n`, and unknown tags are ignored when reading.
+use std::fmt::Write as _;
+use std::io::Write;
+
+use crate::board::{Board, Outcome, Player};
+use crate::interface::engine::BoardCodec;
+use crate::util::bot_game::Replay;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct InvalidGameRecord {
+    /// The line number, starting from 1.
+    pub line: usize,
+    pub reason: String,
+}
+
+/// Format a single replay as a game record, including the trailing newline but not the separating empty line.
+pub fn format_replay<B: Board, C: BoardCodec<B>>(codec: &C, replay: &Replay<B>) -> String {
+    let mut result = String::new();
+    let mut tag = |key: &str, value: &str| writeln!(&mut result, "[{} \"{}\"]", key, escape(value)).unwrap();
+
+    tag("Start", &codec.format_position(&replay.start));
+    tag("PlayerL", player_to_str(replay.player_l));
+    tag("BotL", &replay.debug_l);
+    tag("BotR", &replay.debug_r);
+    tag("Outcome", outcome_to_str(replay.outcome));
+    if let Some(player) = replay.lost_on_time {
+        tag("LostOnTime", player_to_str(player));
+    }
+    tag("TimeL", &replay.total_time_l.to_string());
+    tag("TimeR", &replay.total_time_r.to_string());
+    tag("MoveCountL", &replay.move_count_l.to_string());
+    tag("MoveCountR", &replay.move_count_r.to_string());
+
+    let mut board = replay.start.clone();
+    let mut moves = vec![];
+    for &mv in &replay.moves {
+        moves.push(codec.format_move(&board, mv));
+        board.play(mv);
+    }
+    if !moves.is_empty() {
+        result += &moves.join(" ");
+        result.push('\n');
+    }
+
+    result
+}
+
+/// Write all replays as game records separated by empty lines.
+pub fn write_replays<B: Board, C: BoardCodec<B>>(
+    codec: &C,
+    replays: &[Replay<B>],
+    mut f: impl Write,
+) -> std::io::Result<()> {
+    for (i, replay) in replays.iter().enumerate() {
+        if i != 0 {
+            writeln!(f)?;
+        }
+        write!(f, "{}", format_replay(codec, replay))?;
+    }
+    f.flush()
+}
+
+/// Parse all game records in `text`, the inverse of [write_replays].
+pub fn parse_replays<B: Board, C: BoardCodec<B>>(codec: &C, text: &str) -> Result<Vec<Replay<B>>, InvalidGameRecord> {
+    let mut replays = vec![];
+    let mut block = vec![];
+
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            if !block.is_empty() {
+                replays.push(parse_block(codec, &block)?);
+                block.clear();
+            }
+        } else {
+            block.push((i + 1, line));
+        }
+    }
+    if !block.is_empty() {
+        replays.push(parse_block(codec, &block)?);
+    }
+
+    Ok(replays)
+}
+
+/// Read and parse a file of game records, see [parse_replays].
+pub fn read_replays<B: Board, C: BoardCodec<B>>(codec: &C, path: &str) -> std::io::Result<Vec<Replay<B>>> {
+    let text = std::fs::read_to_string(path)?;
+    parse_replays(codec, &text).map_err(|e| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("invalid game record on line {}: {}", e.line, e.reason),
+        )
+    })
+}
+
+fn parse_block<B: Board, C: BoardCodec<B>>(codec: &C, block: &[(usize, &str)]) -> Result<Replay<B>, InvalidGameRecord> {
+    let first_line = block[0].0;
+    let error = |line: usize, reason: String| InvalidGameRecord { line, reason };
+
+    let mut start = None;
+    let mut player_l = None;
+    let mut outcome = None;
+    let mut lost_on_time = None;
+    let mut debug_l = String::new();
+    let mut debug_r = String::new();
+    let mut total_time_l = 0.0;
+    let mut total_time_r = 0.0;
+    let mut move_count_l = 0;
+    let mut move_count_r = 0;
+    let mut move_line = None;
+
+    for &(line, text) in block {
+        if !text.starts_with('[') {
+            if move_line.is_some() {
+                return Err(error(line, "multiple move lines".to_owned()));
+            }
+            move_line = Some((line, text));
+            continue;
+        }
+        if move_line.is_some() {
+            return Err(error(line, "tag after the moves".to_owned()));
+        }
+
+        let (key, value) = parse_tag(text).ok_or_else(|| error(line, format!("invalid tag '{}'", text)))?;
+        let invalid = || error(line, format!("invalid value '{}' for tag '{}'", value, key));
+
+        match key {
+            "Start" => {
+                let board = codec
+                    .parse_position(&value)
+                    .map_err(|e| error(line, format!("invalid start position: {:?}", e)))?;
+                start = Some(board);
+            }
+            "PlayerL" => player_l = Some(player_from_str(&value).ok_or_else(invalid)?),
+            "BotL" => debug_l = value,
+            "BotR" => debug_r = value,
+            "Outcome" => outcome = Some(outcome_from_str(&value).ok_or_else(invalid)?),
+            "LostOnTime" => lost_on_time = Some(player_from_str(&value).ok_or_else(invalid)?),
+            "TimeL" => total_time_l = value.parse().map_err(|_| invalid())?,
+            "TimeR" => total_time_r = value.parse().map_err(|_| invalid())?,
+            "MoveCountL" => move_count_l = value.parse().map_err(|_| invalid())?,
+            "MoveCountR" => move_count_r = value.parse().map_err(|_| invalid())?,
+            // ignore unknown tags for forward compatibility
+            _ => {}
+        }
+    }
+
+    let missing = |key: &str| error(first_line, format!("missing tag '{}'", key));
+    let start = start.ok_or_else(|| missing("Start"))?;
+    let player_l = player_l.ok_or_else(|| missing("PlayerL"))?;
+    let outcome = outcome.ok_or_else(|| missing("Outcome"))?;
+
+    let mut moves = vec![];
+    if let Some((line, text)) = move_line {
+        let mut board = start.clone();
+        for mv_str in text.split_whitespace() {
+            if board.is_done() {
+                return Err(error(line, format!("move '{}' played after the game ended", mv_str)));
+            }
+            let mv = codec
+                .parse_move(&board, mv_str)
+                .map_err(|e| error(line, format!("invalid move '{}': {:?}", mv_str, e)))?;
+            if !board.is_available_move(mv) {
+                return Err(error(line, format!("move '{}' is not available", mv_str)));
+            }
+            board.play(mv);
+            moves.push(mv);
+        }
+    }
+
+    Ok(Replay {
+        start,
+        player_l,
+        moves,
+        outcome,
+        lost_on_time,
+        total_time_l,
+        total_time_r,
+        move_count_l,
+        move_count_r,
+        debug_l,
+        debug_r,
+    })
+}
+
+/// Parse a `[Key "value"]` tag into the key and unescaped value.
+fn parse_tag(line: &str) -> Option<(&str, String)> {
+    let inner = line.strip_prefix('[')?.strip_suffix(']')?;
+    let (key, value) = inner.split_once(' ')?;
+    let value = value.strip_prefix('"')?.strip_suffix('"')?;
+    Some((key, unescape(value)?))
+}
+
+fn escape(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => result.push_str("\\\\"),
+            '"' => result.push_str("\\\""),
+            '\n' => result.push_str("\\n"),
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+fn unescape(value: &str) -> Option<String> {
+    let mut result = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next()? {
+                '\\' => result.push('\\'),
+                '"' => result.push('"'),
+                'n' => result.push('\n'),
+                _ => return None,
+            },
+            '"' => return None,
+            c => result.push(c),
+        }
+    }
+    Some(result)
+}
+
+fn player_to_str(player: Player) -> &'static str {
+    match player {
+        Player::A => "A",
+        Player::B => "B",
+    }
+}
+
+fn player_from_str(s: &str) -> Option<Player> {
+    match s {
+        "A" => Some(Player::A),
+        "B" => Some(Player::B),
+        _ => None,
+    }
+}
+
+fn outcome_to_str(outcome: Outcome) -> &'static str {
+    match outcome {
+        Outcome::WonBy(player) => player_to_str(player),
+        Outcome::Draw => "Draw",
+    }
+}
+
+fn outcome_from_str(s: &str) -> Option<Outcome> {
+    match s {
+        "Draw" => Some(Outcome::Draw),
+        _ => player_from_str(s).map(Outcome::WonBy),
+    }
+}
@@ -13,11 +13,11 @@ use crate::wdl::WDL;
 /// The number of legal positions reachable after `depth` moves, including duplicates.
 /// See <https://www.chessprogramming.org/Perft>.
 pub fn perft<B: Board>(board: &B, depth: u32) -> u64 {
-    let mut map = HashMap::default();
-    perft_recurse(&mut map, board.clone(), depth)
+    let mut maps = vec![HashMap::default(); depth as usize + 1];
+    perft_recurse(&mut maps, &mut board.clone(), depth)
 }
 
-fn perft_recurse<B: Board + Hash>(map: &mut HashMap<(B, u32), u64>, board: B, depth: u32) -> u64 {
+fn perft_recurse<B: Board + Hash>(maps: &mut [HashMap<B, u64>], board: &mut B, depth: u32) -> u64 {
     if depth == 0 {
         return 1;
     }
@@ -28,20 +28,18 @@ fn perft_recurse<B: Board + Hash>(map: &mut HashMap<(B, u32), u64>, board: B, de
         return board.available_moves().count() as u64;
     }
 
-    // we need keys (B, depth) because otherwise we risk miscounting if the same board is encountered at different depths
-    let key = (board, depth);
-    let board = &key.0;
-
-    if let Some(&p) = map.get(&key) {
+    // we need a map per depth, otherwise we risk miscounting if the same board is encountered at different depths
+    if let Some(&p) = maps[depth as usize].get(&*board) {
         return p;
     }
 
+    let moves: Vec<B::Move> = board.available_moves().collect();
     let mut p = 0;
-    board.available_moves().for_each(|mv: B::Move| {
-        p += perft_recurse(map, board.clone_and_play(mv), depth - 1);
-    });
+    for mv in moves {
+        p += board.play_then_undo(mv, |child| perft_recurse(maps, child, depth - 1));
+    }
 
-    map.insert(key, p);
+    maps[depth as usize].insert(board.clone(), p);
     p
 }
 
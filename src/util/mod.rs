@@ -1,11 +1,16 @@
 //! Various utility functions.
 pub mod board_gen;
 pub mod bot_game;
+pub mod game_record;
 pub mod game_stats;
+pub mod opening;
 pub mod pathfind;
 
 pub mod bitboard;
 pub mod bits;
 pub mod coord;
+pub mod zobrist;
 
 pub mod rating;
+pub mod sprt;
+pub mod tournament;
@@ -0,0 +1,214 @@
+//! Opening books to start bot matches from a variety of positions, see [OpeningBook].
+//!
+//! Books can be read from files with one position per line, or generated by playing random moves and only keeping
+//! positions that a search considers roughly balanced.
+use std::collections::HashSet;
+use std::fmt::{Debug, Display, Formatter};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use rand::Rng;
+
+use crate::ai::minimax::{minimax_value, Heuristic};
+use crate::board::Board;
+use crate::games::ataxx::{AtaxxBoard, Move};
+use crate::games::chess::{ChessBoard, Rules};
+use crate::util::board_gen::random_board_with_moves;
+
+/// A list of start positions.
+#[derive(Clone)]
+pub struct OpeningBook<B: Board> {
+    positions: Vec<B>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct InvalidOpening {
+    /// The line number, starting from 1.
+    pub line: usize,
+    pub text: String,
+    pub reason: String,
+}
+
+impl<B: Board> OpeningBook<B> {
+    pub fn new(positions: Vec<B>) -> Self {
+        assert!(!positions.is_empty(), "opening book must contain at least one position");
+        OpeningBook { positions }
+    }
+
+    /// Parse a book with one position per line using `parse`. Empty lines and lines starting with `#` are skipped.
+    pub fn parse_lines<E: Display>(text: &str, parse: impl Fn(&str) -> Result<B, E>) -> Result<Self, InvalidOpening> {
+        let mut positions = vec![];
+
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let position = parse(line).map_err(|e| InvalidOpening {
+                line: i + 1,
+                text: line.to_owned(),
+                reason: e.to_string(),
+            })?;
+            positions.push(position);
+        }
+
+        if positions.is_empty() {
+            return Err(InvalidOpening {
+                line: 0,
+                text: String::new(),
+                reason: "no positions found".to_owned(),
+            });
+        }
+
+        Ok(OpeningBook { positions })
+    }
+
+    /// Read and parse a book file, see [OpeningBook::parse_lines].
+    pub fn read_file<E: Display>(path: &str, parse: impl Fn(&str) -> Result<B, E>) -> std::io::Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        Self::parse_lines(&text, parse).map_err(|e| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("invalid opening on line {} '{}': {}", e.line, e.text, e.reason),
+            )
+        })
+    }
+
+    /// Generate `count` distinct positions by playing `moves` random moves on `start`,
+    /// only keeping positions for which `filter` returns true.
+    ///
+    /// Panics if not enough positions are found, for example because there are fewer than `count` distinct
+    /// positions reachable in `moves` moves.
+    pub fn generate(
+        start: &B,
+        count: usize,
+        moves: u32,
+        rng: &mut impl Rng,
+        mut filter: impl FnMut(&B) -> bool,
+    ) -> Self {
+        assert!(count > 0, "opening book must contain at least one position");
+
+        let max_attempts = 1000 + 100 * count;
+        let mut seen = HashSet::new();
+        let mut positions = vec![];
+
+        for _ in 0..max_attempts {
+            let board = random_board_with_moves(start, moves, rng);
+            if board.is_done() || seen.contains(&board) || !filter(&board) {
+                continue;
+            }
+
+            seen.insert(board.clone());
+            positions.push(board);
+            if positions.len() == count {
+                return OpeningBook { positions };
+            }
+        }
+
+        panic!(
+            "only found {} out of {} openings after {} attempts",
+            positions.len(),
+            count,
+            max_attempts
+        );
+    }
+
+    /// Generate positions like [OpeningBook::generate], only keeping positions with a minimax value of at most
+    /// `max_value` for either player.
+    pub fn generate_balanced<H: Heuristic<B>>(
+        start: &B,
+        count: usize,
+        moves: u32,
+        heuristic: &H,
+        depth: u32,
+        max_value: H::V,
+        rng: &mut impl Rng,
+    ) -> Self
+    where
+        H::V: PartialOrd,
+    {
+        Self::generate(start, count, moves, rng, |board| {
+            let value = minimax_value(board, heuristic, depth);
+            -max_value <= value && value <= max_value
+        })
+    }
+
+    pub fn positions(&self) -> &[B] {
+        &self.positions
+    }
+
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    /// A start function for [run](crate::util::bot_game::run) that cycles through the positions in order.
+    pub fn sequential(&self) -> impl Fn() -> B + Sync + '_ {
+        let next = AtomicUsize::new(0);
+        move || {
+            let i = next.fetch_add(1, Ordering::Relaxed);
+            self.positions[i % self.positions.len()].clone()
+        }
+    }
+
+    /// A start function for [run](crate::util::bot_game::run) that picks random positions.
+    pub fn random<'a, R: Rng + Send + 'a>(&'a self, rng: R) -> impl Fn() -> B + Sync + 'a {
+        let rng = Mutex::new(rng);
+        move || {
+            let i = rng.lock().unwrap().gen_range(0..self.positions.len());
+            self.positions[i].clone()
+        }
+    }
+}
+
+/// Parse an Ataxx opening, either a FEN or a sequence of UAI moves from the default start position.
+pub fn parse_ataxx_opening(line: &str) -> Result<AtaxxBoard, String> {
+    if line.contains('/') {
+        return AtaxxBoard::from_fen(line).map_err(|e| e.reason.to_owned());
+    }
+
+    let mut board = AtaxxBoard::default();
+    for mv_str in line.split_whitespace() {
+        let mv = Move::from_uai(mv_str).map_err(|_| format!("invalid move '{}'", mv_str))?;
+        if board.is_done() || !board.is_available_move(mv) {
+            return Err(format!("move '{}' is not available", mv_str));
+        }
+        board.play(mv);
+    }
+    Ok(board)
+}
+
+/// Parse a chess opening, either a FEN or a sequence of UCI or SAN moves from the default start position.
+/// Move numbers like `1.` are skipped.
+pub fn parse_chess_opening(line: &str) -> Result<ChessBoard, String> {
+    if line.contains('/') {
+        let inner = chess::Board::from_str(line).map_err(|e| format!("invalid fen: {:?}", e))?;
+        return Ok(ChessBoard::new_without_history(inner, Rules::default()));
+    }
+
+    let mut board = ChessBoard::default();
+    for mv_str in line.split_whitespace() {
+        if mv_str.ends_with('.') {
+            continue;
+        }
+        if board.is_done() {
+            return Err(format!("move '{}' played after the game ended", mv_str));
+        }
+        let mv = board
+            .parse_move(mv_str)
+            .map_err(|_| format!("invalid move '{}'", mv_str))?;
+        board.play(mv);
+    }
+    Ok(board)
+}
+
+impl<B: Board> Debug for OpeningBook<B> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "OpeningBook {{ positions: {} }}", self.positions.len())
+    }
+}
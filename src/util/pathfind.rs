@@ -3,19 +3,21 @@ use internal_iterator::InternalIterator;
 
 /// Find a list of `len` moves that when played on `start` results in `target`.
 pub fn pathfind_exact_length<B: Board>(start: &B, target: &B, len: u32) -> Option<Vec<B::Move>> {
-    if len == 0 || start.is_done() {
-        return if start == target { Some(vec![]) } else { None };
-    }
+    let mut path = pathfind_exact_length_impl(&mut start.clone(), target, len)?;
+    path.reverse();
+    Some(path)
+}
 
-    start
-        .available_moves()
-        .filter_map(|mv: B::Move| {
-            let next = start.clone_and_play(mv);
+/// The moves in the returned path are in reverse order.
+fn pathfind_exact_length_impl<B: Board>(curr: &mut B, target: &B, len: u32) -> Option<Vec<B::Move>> {
+    if len == 0 || curr.is_done() {
+        return if curr == target { Some(vec![]) } else { None };
+    }
 
-            pathfind_exact_length(&next, target, len - 1).map(|mut left| {
-                left.insert(0, mv);
-                left
-            })
-        })
-        .next()
+    let moves: Vec<B::Move> = curr.available_moves().collect();
+    moves.into_iter().find_map(|mv| {
+        let mut path = curr.play_then_undo(mv, |next| pathfind_exact_length_impl(next, target, len - 1))?;
+        path.push(mv);
+        Some(path)
+    })
 }
@@ -8,3 +8,196 @@ pub fn elo_from_wdl(wdl: WDL<f32>) -> f32 {
     // fix annoying negative zero case
     elo + 0.0
 }
+
+/// The largest rating difference reported, estimates for players with perfect scores are clamped to this range.
+pub const MAX_ELO: f32 = 2000.0;
+
+/// The z-score used for the confidence intervals reported in [Rating::margin], corresponding to 95%.
+pub const CONFIDENCE_Z: f32 = 1.96;
+
+/// The method used by [ratings_from_cross_table].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum RatingMethod {
+    /// Maximum likelihood estimate of the Bradley-Terry model, counting draws as half a win and half a loss.
+    MaximumLikelihood,
+    /// The model used by BayesElo: draws are explicitly modelled through `draw_elo` and `prior` virtual draws
+    /// are added between every pair of players that played each other, which keeps the ratings of players with
+    /// perfect scores finite.
+    BayesElo { draw_elo: f32, prior: f32 },
+}
+
+impl RatingMethod {
+    /// The default settings of BayesElo.
+    pub fn bayes_elo() -> Self {
+        RatingMethod::BayesElo {
+            draw_elo: 97.3,
+            prior: 2.0,
+        }
+    }
+}
+
+/// An estimated rating with a confidence interval.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Rating {
+    pub elo: f32,
+    /// Half of the width of the 95% confidence interval, infinite for players without any games.
+    pub margin: f32,
+}
+
+/// Estimate ratings for all players jointly from a cross table,
+/// where `cross_table[i][j]` is the WDL of player `i` in games against player `j`.
+///
+/// The ratings are shifted to have an average of zero. Confidence intervals are approximated from
+/// the curvature of the log-likelihood for each player separately, keeping the other ratings fixed.
+pub fn ratings_from_cross_table(cross_table: &[Vec<WDL<u32>>], method: RatingMethod) -> Vec<Rating> {
+    let n = cross_table.len();
+    assert!(
+        cross_table.iter().all(|row| row.len() == n),
+        "cross table must be square"
+    );
+
+    let model = Model::new(cross_table, method);
+    let max_elo = MAX_ELO as f64;
+    let mut elo = vec![0.0; n];
+
+    // gradient ascent with an adaptive step size
+    let mut step = 1.0;
+    let mut curr = model.log_likelihood(&elo);
+    for _ in 0..100_000 {
+        let grad = model.gradient(&elo);
+        if grad.iter().all(|g| g.abs() < 1e-9) {
+            break;
+        }
+
+        let next_elo = elo
+            .iter()
+            .zip(&grad)
+            .map(|(&e, &g)| (e + step * g).clamp(-max_elo, max_elo))
+            .collect::<Vec<_>>();
+        let next = model.log_likelihood(&next_elo);
+
+        if next >= curr {
+            elo = next_elo;
+            curr = next;
+            step *= 1.5;
+        } else {
+            step /= 2.0;
+            if step < 1e-12 {
+                break;
+            }
+        }
+    }
+
+    let mean = elo.iter().sum::<f64>() / n.max(1) as f64;
+
+    (0..n)
+        .map(|i| {
+            // numerical second derivative of the log-likelihood in the rating of player i
+            let h = 0.1;
+            let mut plus = elo.clone();
+            plus[i] += h;
+            let mut minus = elo.clone();
+            minus[i] -= h;
+            let curvature = (model.gradient(&plus)[i] - model.gradient(&minus)[i]) / (2.0 * h);
+
+            let margin = if curvature < 0.0 {
+                CONFIDENCE_Z / (-curvature as f32).sqrt()
+            } else {
+                f32::INFINITY
+            };
+
+            Rating {
+                elo: (elo[i] - mean) as f32 + 0.0,
+                margin,
+            }
+        })
+        .collect()
+}
+
+/// The derivative of `ln(logistic(x))` to `x` is `ELO_SCALE * (1 - logistic(x))`.
+const ELO_SCALE: f64 = std::f64::consts::LN_10 / 400.0;
+
+/// The logistic function used by Elo, mapping a rating difference to an expected score.
+fn logistic(delta: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-delta / 400.0))
+}
+
+/// The observed results for each pair of players as seen by the model, see [Model::observed].
+struct Model {
+    method: RatingMethod,
+    /// `(i, j, wdl)` for each pair with `i < j` that played at least one game.
+    pairs: Vec<(usize, usize, WDL<f64>)>,
+}
+
+impl Model {
+    fn new(cross_table: &[Vec<WDL<u32>>], method: RatingMethod) -> Self {
+        let mut pairs = vec![];
+
+        for (i, row) in cross_table.iter().enumerate() {
+            for (j, wdl) in row.iter().enumerate().skip(i + 1) {
+                let wdl = wdl.cast::<f64>();
+                if wdl.sum() > 0.0 {
+                    pairs.push((i, j, Model::observed(wdl, method)));
+                }
+            }
+        }
+
+        Model { method, pairs }
+    }
+
+    /// The results including virtual draws for BayesElo, or with draws split into half points otherwise.
+    fn observed(wdl: WDL<f64>, method: RatingMethod) -> WDL<f64> {
+        match method {
+            RatingMethod::MaximumLikelihood => WDL::new(wdl.win + wdl.draw / 2.0, 0.0, wdl.loss + wdl.draw / 2.0),
+            RatingMethod::BayesElo { prior, .. } => WDL::new(wdl.win, wdl.draw + prior as f64, wdl.loss),
+        }
+    }
+
+    /// The probabilities of win, draw and loss of a player with a rating advantage `delta`.
+    fn predict(&self, delta: f64) -> WDL<f64> {
+        match self.method {
+            RatingMethod::MaximumLikelihood => {
+                let p = logistic(delta);
+                WDL::new(p, 0.0, 1.0 - p)
+            }
+            RatingMethod::BayesElo { draw_elo, .. } => {
+                let win = logistic(delta - draw_elo as f64);
+                let loss = logistic(-delta - draw_elo as f64);
+                WDL::new(win, 1.0 - win - loss, loss)
+            }
+        }
+    }
+
+    fn log_likelihood(&self, elo: &[f64]) -> f64 {
+        let mut total = 0.0;
+        for &(i, j, obs) in &self.pairs {
+            let p = self.predict(elo[i] - elo[j]);
+            for (count, prob) in [(obs.win, p.win), (obs.draw, p.draw), (obs.loss, p.loss)] {
+                if count > 0.0 {
+                    total += count * prob.max(f64::MIN_POSITIVE).ln();
+                }
+            }
+        }
+        total
+    }
+
+    fn gradient(&self, elo: &[f64]) -> Vec<f64> {
+        let mut grad = vec![0.0; elo.len()];
+        for &(i, j, obs) in &self.pairs {
+            let p = self.predict(elo[i] - elo[j]);
+
+            // derivative of the log-likelihood of this pair to the rating difference
+            let d_win = ELO_SCALE * (1.0 - p.win);
+            let d_loss = -ELO_SCALE * (1.0 - p.loss);
+            let mut d = obs.win * d_win + obs.loss * d_loss;
+            if obs.draw > 0.0 {
+                let d_draw = -ELO_SCALE * (p.win * (1.0 - p.win) - p.loss * (1.0 - p.loss));
+                d += obs.draw * d_draw / p.draw.max(f64::MIN_POSITIVE);
+            }
+
+            grad[i] += d;
+            grad[j] -= d;
+        }
+        grad
+    }
+}
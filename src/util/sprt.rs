@@ -0,0 +1,153 @@
+//! Sequential probability ratio tests to stop matches as soon as the result is clear, see [Sprt].
+//!
+//! The log-likelihood ratio is computed with the same normal approximation of the generalized SPRT as fishtest,
+//! with the hypotheses expressed in logistic elo.
+use std::fmt::{Debug, Formatter};
+
+use crate::board::Board;
+use crate::util::bot_game::BotGameResult;
+use crate::wdl::{OutcomeWDL, WDL};
+
+/// The settings of a sequential probability ratio test between the hypotheses `H0: elo = elo0`
+/// and `H1: elo = elo1`, with false positive rate `alpha` and false negative rate `beta`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Sprt {
+    pub elo0: f64,
+    pub elo1: f64,
+    pub alpha: f64,
+    pub beta: f64,
+    /// Use the pentanomial statistics of game pairs instead of the trinomial statistics of single games.
+    /// This takes the correlation between the two games played from the same start position into account.
+    pub pentanomial: bool,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum SprtStatus {
+    Continue,
+    AcceptH0,
+    AcceptH1,
+}
+
+/// The number of game pairs for each total score of the first bot, from two losses to two wins.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub struct Pentanomial {
+    pub counts: [u32; 5],
+}
+
+/// The result of [run_sprt](crate::util::bot_game::run_sprt).
+pub struct SprtResult<B: Board> {
+    pub result: BotGameResult<B>,
+    /// The log-likelihood ratio over all games that were played.
+    pub llr: f64,
+    pub status: SprtStatus,
+    /// The pair statistics, only present if [Sprt::pentanomial] was set.
+    pub pentanomial: Option<Pentanomial>,
+}
+
+impl Sprt {
+    pub fn new(elo0: f64, elo1: f64, alpha: f64, beta: f64) -> Self {
+        assert!(elo0 < elo1, "elo0 must be smaller than elo1, got {} and {}", elo0, elo1);
+        assert!(
+            0.0 < alpha && alpha < 1.0 && 0.0 < beta && beta < 1.0,
+            "alpha and beta must be in (0, 1), got {} and {}",
+            alpha,
+            beta
+        );
+        Sprt {
+            elo0,
+            elo1,
+            alpha,
+            beta,
+            pentanomial: false,
+        }
+    }
+
+    pub fn with_pentanomial(self, pentanomial: bool) -> Self {
+        Sprt { pentanomial, ..self }
+    }
+
+    /// The lower and upper bounds for the log-likelihood ratio, the test resolves once either is crossed.
+    pub fn bounds(&self) -> (f64, f64) {
+        let lower = (self.beta / (1.0 - self.alpha)).ln();
+        let upper = ((1.0 - self.beta) / self.alpha).ln();
+        (lower, upper)
+    }
+
+    pub fn status(&self, llr: f64) -> SprtStatus {
+        let (lower, upper) = self.bounds();
+        if llr <= lower {
+            SprtStatus::AcceptH0
+        } else if llr >= upper {
+            SprtStatus::AcceptH1
+        } else {
+            SprtStatus::Continue
+        }
+    }
+
+    /// The log-likelihood ratio of the game results `wdl` from the POV of the first bot.
+    pub fn llr_trinomial(&self, wdl: WDL<u32>) -> f64 {
+        self.llr(&[(1.0, wdl.win), (0.5, wdl.draw), (0.0, wdl.loss)])
+    }
+
+    /// The log-likelihood ratio of the game pair results `pentanomial` from the POV of the first bot.
+    pub fn llr_pentanomial(&self, pentanomial: Pentanomial) -> f64 {
+        let c = pentanomial.counts;
+        self.llr(&[(0.0, c[0]), (0.25, c[1]), (0.5, c[2]), (0.75, c[3]), (1.0, c[4])])
+    }
+
+    /// The normal approximation of the log-likelihood ratio for samples with the given scores and counts.
+    fn llr(&self, samples: &[(f64, u32)]) -> f64 {
+        let n = samples.iter().map(|&(_, c)| c as f64).sum::<f64>();
+        if n == 0.0 {
+            return 0.0;
+        }
+
+        let mean = samples.iter().map(|&(s, c)| s * c as f64).sum::<f64>() / n;
+        let var = samples.iter().map(|&(s, c)| (s - mean).powi(2) * c as f64).sum::<f64>() / n;
+        if var == 0.0 {
+            // all samples are equal, there is no information about the spread yet
+            return 0.0;
+        }
+
+        let s0 = score_from_elo(self.elo0);
+        let s1 = score_from_elo(self.elo1);
+        n * (s1 - s0) * (2.0 * mean - s0 - s1) / (2.0 * var)
+    }
+}
+
+impl SprtStatus {
+    pub fn is_resolved(self) -> bool {
+        self != SprtStatus::Continue
+    }
+}
+
+impl Pentanomial {
+    /// Add a pair of game outcomes, both from the POV of the first bot.
+    pub fn add_pair(&mut self, first: OutcomeWDL, second: OutcomeWDL) {
+        let points = |outcome| match outcome {
+            OutcomeWDL::Win => 2,
+            OutcomeWDL::Draw => 1,
+            OutcomeWDL::Loss => 0,
+        };
+        self.counts[points(first) + points(second)] += 1;
+    }
+
+    pub fn pair_count(&self) -> u32 {
+        self.counts.iter().sum()
+    }
+}
+
+/// The expected score for the given logistic elo difference.
+fn score_from_elo(elo: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-elo / 400.0))
+}
+
+impl<B: Board> Debug for SprtResult<B> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SprtResult {{ status: {:?}, llr: {:.3}, ", self.status, self.llr)?;
+        if let Some(pentanomial) = &self.pentanomial {
+            write!(f, "pentanomial: {:?}, ", pentanomial.counts)?;
+        }
+        write!(f, "result: {:?} }}", self.result)
+    }
+}
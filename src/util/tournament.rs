@@ -0,0 +1,167 @@
+//! Run tournaments between many bots and rate them jointly, see [round_robin] and [gauntlet].
+use std::fmt::{Debug, Display, Formatter};
+
+use crate::ai::Bot;
+use crate::board::Board;
+use crate::pov::Pov;
+use crate::util::bot_game::{run_with_time_control, BotGameResult, TimeControl};
+use crate::util::rating::{ratings_from_cross_table, Rating, RatingMethod};
+use crate::wdl::WDL;
+
+/// A named bot participating in a tournament, a new bot is constructed for each game.
+pub struct Entrant<B: Board> {
+    pub name: String,
+    factory: Box<dyn Fn() -> Box<dyn Bot<B> + Send> + Sync>,
+}
+
+/// The results of a tournament.
+#[derive(Debug, Clone)]
+pub struct TournamentResult {
+    pub names: Vec<String>,
+    /// `cross_table[i][j]` is the WDL of entrant `i` in games against entrant `j`.
+    pub cross_table: Vec<Vec<WDL<u32>>>,
+}
+
+/// The settings shared by all matches in a tournament, see [run_with_time_control].
+#[derive(Debug, Copy, Clone)]
+pub struct MatchSettings {
+    pub games_per_side: u32,
+    pub both_sides: bool,
+    pub time_control: TimeControl,
+}
+
+impl<B: Board> Entrant<B> {
+    pub fn new<T: Bot<B> + Send + 'static>(name: &str, factory: impl Fn() -> T + Sync + 'static) -> Self {
+        Entrant {
+            name: name.to_owned(),
+            factory: Box::new(move || -> Box<dyn Bot<B> + Send> { Box::new(factory()) }),
+        }
+    }
+
+    fn build(&self) -> Box<dyn Bot<B> + Send> {
+        (self.factory)()
+    }
+}
+
+/// Play a match between every pair of entrants.
+///
+/// `callback` is called after each match with the indices of both entrants and the result,
+/// from the POV of the first entrant.
+pub fn round_robin<B: Board>(
+    start: impl Fn() -> B + Sync,
+    entrants: &[Entrant<B>],
+    settings: MatchSettings,
+    callback: impl FnMut(usize, usize, &BotGameResult<B>),
+) -> TournamentResult {
+    let n = entrants.len();
+    let pairs = (0..n).flat_map(|i| ((i + 1)..n).map(move |j| (i, j))).collect();
+    run_pairs(start, entrants, pairs, settings, callback)
+}
+
+/// Play a match between the first entrant and each of the other entrants.
+/// The other entrants don't play each other, so their relative ratings are only determined indirectly.
+///
+/// `callback` is called after each match, see [round_robin].
+pub fn gauntlet<B: Board>(
+    start: impl Fn() -> B + Sync,
+    entrants: &[Entrant<B>],
+    settings: MatchSettings,
+    callback: impl FnMut(usize, usize, &BotGameResult<B>),
+) -> TournamentResult {
+    let pairs = (1..entrants.len()).map(|j| (0, j)).collect();
+    run_pairs(start, entrants, pairs, settings, callback)
+}
+
+fn run_pairs<B: Board>(
+    start: impl Fn() -> B + Sync,
+    entrants: &[Entrant<B>],
+    pairs: Vec<(usize, usize)>,
+    settings: MatchSettings,
+    mut callback: impl FnMut(usize, usize, &BotGameResult<B>),
+) -> TournamentResult {
+    let n = entrants.len();
+    let mut cross_table = vec![vec![WDL::default(); n]; n];
+
+    for (i, j) in pairs {
+        let result = run_with_time_control(
+            &start,
+            || entrants[i].build(),
+            || entrants[j].build(),
+            settings.games_per_side,
+            settings.both_sides,
+            settings.time_control,
+            |_, _| {},
+        );
+
+        cross_table[i][j] += result.wdl_l;
+        cross_table[j][i] += result.wdl_l.flip();
+        callback(i, j, &result);
+    }
+
+    TournamentResult {
+        names: entrants.iter().map(|e| e.name.clone()).collect(),
+        cross_table,
+    }
+}
+
+impl TournamentResult {
+    /// The total WDL of each entrant over all of its games.
+    pub fn totals(&self) -> Vec<WDL<u32>> {
+        self.cross_table
+            .iter()
+            .map(|row| row.iter().sum::<WDL<u32>>())
+            .collect()
+    }
+
+    /// Estimate the rating of each entrant, see [ratings_from_cross_table].
+    pub fn ratings(&self, method: RatingMethod) -> Vec<Rating> {
+        ratings_from_cross_table(&self.cross_table, method)
+    }
+
+    /// The entrant indices and ratings, sorted from best to worst.
+    pub fn ranking(&self, method: RatingMethod) -> Vec<(usize, Rating)> {
+        let mut ranking = self.ratings(method).into_iter().enumerate().collect::<Vec<_>>();
+        ranking.sort_by(|(_, a), (_, b)| b.elo.partial_cmp(&a.elo).unwrap());
+        ranking
+    }
+}
+
+/// Formats as a cross table, with on each row the wins, draws and losses against every other entrant.
+impl Display for TournamentResult {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name_width = self.names.iter().map(|name| name.len()).max().unwrap_or(0);
+        let cells = self
+            .cross_table
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|wdl| format!("{}/{}/{}", wdl.win, wdl.draw, wdl.loss))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let cell_width = cells.iter().flatten().map(|cell| cell.len()).max().unwrap_or(0).max(3);
+
+        write!(f, "{:name_width$}", "", name_width = name_width)?;
+        for i in 0..self.names.len() {
+            write!(f, " | {:>cell_width$}", i, cell_width = cell_width)?;
+        }
+        writeln!(f)?;
+
+        for (i, (name, row)) in self.names.iter().zip(&cells).enumerate() {
+            write!(f, "{:name_width$}", name, name_width = name_width)?;
+            for (j, cell) in row.iter().enumerate() {
+                let cell = if i == j { "-" } else { cell };
+                write!(f, " | {:>cell_width$}", cell, cell_width = cell_width)?;
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<B: Board> Debug for Entrant<B> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Entrant {{ name: {} }}", self.name)
+    }
+}
@@ -0,0 +1,27 @@
+//! Key generation for [Zobrist hashing](https://en.wikipedia.org/wiki/Zobrist_hashing),
+//! used by boards that implement [ZobristHash](crate::board::ZobristHash).
+//!
+//! Keys are derived from an index with a fixed mixing function instead of a random generator,
+//! so they can be computed in `const` context and are the same across runs.
+
+/// The pseudo-random key for `index`, different indices always give different keys.
+///
+/// This is the [SplitMix64](https://prng.di.unimi.it/splitmix64.c) output function, which is a bijection.
+pub const fn zobrist_key(index: u64) -> u64 {
+    let mut z = index.wrapping_add(1).wrapping_mul(0x9E3779B97F4A7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    z ^ (z >> 31)
+}
+
+/// A table with the keys for indices `offset..offset+N`.
+/// Tables with non-overlapping index ranges don't share any keys.
+pub const fn zobrist_table<const N: usize>(offset: u64) -> [u64; N] {
+    let mut result = [0; N];
+    let mut i = 0;
+    while i < N {
+        result[i] = zobrist_key(offset + i as u64);
+        i += 1;
+    }
+    result
+}
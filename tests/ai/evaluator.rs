@@ -0,0 +1,90 @@
+use internal_iterator::InternalIterator;
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+
+use board_game::ai::evaluator::{Evaluation, Evaluator, HeuristicEvaluator, RandomRollout};
+use board_game::ai::mcts::{mcts_build_tree_with_evaluator, MCTSBot, Puct, Uct};
+use board_game::ai::Bot;
+use board_game::board::{Board, BoardMoves};
+use board_game::games::ataxx::{AtaxxBoard, Move};
+use board_game::games::sttt::STTTBoard;
+use board_game::games::ttt::TTTBoard;
+use board_game::heuristic::ataxx::AtaxxTileHeuristic;
+use board_game::util::coord::Coord3;
+use board_game::wdl::WDL;
+
+/// Evaluator that puts all prior mass on the first available move.
+#[derive(Debug)]
+struct FirstMoveEvaluator;
+
+impl<B: Board> Evaluator<B> for FirstMoveEvaluator {
+    fn evaluate(&self, board: &B, _: &mut impl Rng) -> Evaluation {
+        let count = board.available_moves().count();
+        let policy = (0..count).map(|i| if i == 0 { 1.0 } else { 0.0 }).collect();
+
+        Evaluation {
+            wdl: WDL::new(0.0, 1.0, 0.0),
+            policy: Some(policy),
+        }
+    }
+}
+
+#[test]
+fn random_rollout_forced() {
+    // only a single move is left, which finishes the game in a draw
+    let mut board = TTTBoard::default();
+    for (x, y) in [(0, 0), (1, 1), (2, 2), (0, 1), (2, 1), (2, 0), (0, 2), (1, 2)] {
+        board.play(Coord3::from_xy(x, y));
+    }
+
+    let evaluation = RandomRollout.evaluate(&board, &mut SmallRng::seed_from_u64(0));
+    assert_eq!(evaluation.wdl, WDL::new(0.0, 1.0, 0.0));
+    assert!(evaluation.policy.is_none());
+}
+
+#[test]
+fn heuristic_evaluator_ataxx() {
+    let evaluator = HeuristicEvaluator::new(AtaxxTileHeuristic::default(), 100.0);
+    let mut rng = SmallRng::seed_from_u64(0);
+
+    let start = AtaxxBoard::default();
+    let evaluation = evaluator.evaluate(&start, &mut rng);
+    assert!((evaluation.wdl.sum() - 1.0).abs() < 1e-6);
+    assert!((evaluation.wdl.win - evaluation.wdl.loss).abs() < 1e-6);
+
+    // copying gains a tile, so the opponent is now behind
+    let copy = start.available_moves().find(|mv| matches!(mv, Move::Copy { .. }));
+    let board = start.clone_and_play(copy.unwrap());
+    let evaluation = evaluator.evaluate(&board, &mut rng);
+    assert!(evaluation.wdl.loss > evaluation.wdl.win);
+}
+
+#[test]
+fn heuristic_evaluator_bot() {
+    let evaluator = HeuristicEvaluator::new(AtaxxTileHeuristic::default(), 100.0);
+    let mut bot = MCTSBot::new_with_evaluator(200, Uct::new(2.0), evaluator, SmallRng::seed_from_u64(0));
+
+    let board = AtaxxBoard::default();
+    let mv = bot.select_move(&board);
+    assert!(board.is_available_move(mv));
+}
+
+#[test]
+fn evaluator_priors() {
+    let board = STTTBoard::default();
+    let tree = mcts_build_tree_with_evaluator(
+        &board,
+        100,
+        &Puct::new(1.0),
+        &FirstMoveEvaluator,
+        &mut SmallRng::seed_from_u64(0),
+    );
+
+    let children = tree[0].children.unwrap();
+    let priors: Vec<f32> = children.iter().map(|c| tree[c].prior).collect();
+    assert_eq!(priors[0], 1.0);
+    assert!(priors[1..].iter().all(|&p| p == 0.0));
+
+    // with PUCT all visits go to the only child with a non-zero prior
+    assert_eq!(tree[children.get(0)].visits, 99);
+}
@@ -0,0 +1,86 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use internal_iterator::InternalIterator;
+use rand::rngs::SmallRng;
+use rand::SeedableRng;
+
+use board_game::ai::info::{Score, SearchInfo};
+use board_game::ai::mcts::MCTSBot;
+use board_game::ai::minimax::MiniMaxBot;
+use board_game::ai::Bot;
+use board_game::board::{Board, BoardMoves};
+use board_game::games::ataxx::AtaxxBoard;
+use board_game::games::sttt::{Coord, STTTBoard};
+use board_game::heuristic::ataxx::AtaxxTileHeuristic;
+use board_game::wdl::WDL;
+
+#[test]
+fn mcts_info() {
+    let board = STTTBoard::default();
+    let infos = Arc::new(Mutex::new(vec![]));
+
+    let infos_clone = infos.clone();
+    let mut bot = MCTSBot::new(1000, 2.0, SmallRng::seed_from_u64(0))
+        .with_info_callback(move |info: &SearchInfo<Coord>| infos_clone.lock().unwrap().push(info.clone()));
+    let mv = bot.select_move(&board);
+
+    let infos = infos.lock().unwrap();
+    let info = infos.last().unwrap();
+
+    assert_eq!(info.nodes, 1000);
+    assert_eq!(info.best_move(), Some(&mv));
+    assert_eq!(info.depth as usize, info.pv.len());
+    assert!(info.score.wdl.is_some());
+
+    let mut replay = board.clone();
+    for &mv in &info.pv {
+        assert!(replay.is_available_move(mv));
+        replay.play(mv);
+    }
+
+    assert_eq!(info.moves.len(), board.available_moves().count());
+    let visits: u64 = info.moves.iter().map(|m| m.visits.unwrap()).sum();
+    assert_eq!(visits, 1000 - 1);
+    let prior: f32 = info.moves.iter().map(|m| m.prior.unwrap()).sum();
+    assert!((prior - 1.0).abs() < 1e-3);
+}
+
+#[test]
+fn minimax_info() {
+    let board = AtaxxBoard::default();
+    let infos = Arc::new(Mutex::new(vec![]));
+
+    let infos_clone = infos.clone();
+    let mut bot = MiniMaxBot::new(3, AtaxxTileHeuristic::default(), SmallRng::seed_from_u64(0))
+        .with_info_callback(move |info| infos_clone.lock().unwrap().push(info.clone()));
+    let mv = bot.select_move(&board);
+
+    let infos = infos.lock().unwrap();
+    assert_eq!(infos.iter().map(|info| info.depth).collect::<Vec<_>>(), vec![1, 2, 3]);
+    assert!(infos.windows(2).all(|w| w[0].nodes < w[1].nodes));
+    assert_eq!(infos.last().unwrap().best_move(), Some(&mv));
+    assert!(infos.iter().all(|info| info.score.wdl.is_none()));
+}
+
+#[test]
+fn info_display() {
+    let info = SearchInfo {
+        depth: 2,
+        nodes: 100,
+        time: Duration::from_millis(500),
+        score: Score::from_wdl(WDL::new(0.5, 0.25, 0.25)),
+        pv: vec![Coord::from_xy(0, 0), Coord::from_xy(1, 1)],
+        moves: vec![],
+    };
+
+    assert_eq!(info.nps(), 200.0);
+    assert_eq!(
+        info.to_string(),
+        format!(
+            "depth 2 nodes 100 nps 200 time 500 score 0.25 wdl 0.500 0.250 0.250 pv {} {}",
+            Coord::from_xy(0, 0),
+            Coord::from_xy(1, 1)
+        )
+    );
+}
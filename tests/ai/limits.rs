@@ -0,0 +1,151 @@
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use rand::rngs::SmallRng;
+use rand::SeedableRng;
+
+use board_game::ai::limits::{Clock, SearchLimits};
+use board_game::ai::mcts::{MCTSBot, ParallelMCTSBot, Parallelism};
+use board_game::ai::minimax::{MiniMaxBot, MinimaxBudget};
+use board_game::ai::simple::{RandomBot, RolloutBot};
+use board_game::ai::solver::SolverBot;
+use board_game::ai::Bot;
+use board_game::board::{Board, Outcome};
+use board_game::games::ataxx::AtaxxBoard;
+use board_game::games::sttt::STTTBoard;
+use board_game::games::ttt::TTTBoard;
+use board_game::heuristic::ataxx::AtaxxTileHeuristic;
+use board_game::util::bot_game;
+use board_game::util::bot_game::TimeControl;
+use board_game::util::coord::Coord3;
+
+#[test]
+fn clock_allocate() {
+    let clock = Clock {
+        remaining: Duration::from_secs(60),
+        increment: Duration::from_secs(2),
+        moves_to_go: None,
+    };
+    assert_eq!(clock.allocate(), Duration::from_secs(3));
+
+    let clock = Clock {
+        moves_to_go: Some(1),
+        ..clock
+    };
+    assert_eq!(clock.allocate(), Duration::from_secs(30));
+
+    let limits = SearchLimits {
+        move_time: Some(Duration::from_secs(1)),
+        ..SearchLimits::clock(Duration::from_secs(60), Duration::ZERO)
+    };
+    assert_eq!(limits.time_budget(), Some(Duration::from_secs(1)));
+    assert_eq!(SearchLimits::nodes(100).time_budget(), None);
+}
+
+#[test]
+fn mcts_nodes_limit() {
+    let board = STTTBoard::default();
+    let mut bot = MCTSBot::new(100_000, 2.0, SmallRng::seed_from_u64(0));
+
+    bot.select_move_limited(&board, &SearchLimits::nodes(500));
+    assert_eq!(bot.tree::<STTTBoard>().unwrap()[0].visits, 500);
+}
+
+#[test]
+fn parallel_mcts_nodes_limit() {
+    let board = STTTBoard::default();
+
+    for parallelism in [Parallelism::Root, Parallelism::SharedTree] {
+        let mut bot = ParallelMCTSBot::new(100_000, 2.0, 4, parallelism, SmallRng::seed_from_u64(0));
+        let tree = bot.build_tree_limited(&board, &SearchLimits::nodes(500));
+        assert_eq!(tree[0].visits, 500, "{:?}", parallelism);
+    }
+}
+
+#[test]
+fn move_time_limit() {
+    let board = STTTBoard::default();
+    let limits = SearchLimits::move_time(Duration::from_millis(50));
+
+    assert_returns_within(&mut MCTSBot::new(1, 2.0, SmallRng::seed_from_u64(0)), &board, &limits);
+    assert_returns_within(&mut RolloutBot::new(1, SmallRng::seed_from_u64(0)), &board, &limits);
+    assert_returns_within(&mut SolverBot::new(1, SmallRng::seed_from_u64(0)), &board, &limits);
+    for parallelism in [Parallelism::Root, Parallelism::SharedTree] {
+        let mut bot = ParallelMCTSBot::new(1, 2.0, 4, parallelism, SmallRng::seed_from_u64(0));
+        assert_returns_within(&mut bot, &board, &limits);
+    }
+
+    let board = AtaxxBoard::default();
+    let mut bot = MiniMaxBot::new(1, AtaxxTileHeuristic::default(), SmallRng::seed_from_u64(0));
+    assert_returns_within(&mut bot, &board, &limits);
+}
+
+#[test]
+fn stop_flag() {
+    let board = STTTBoard::default();
+    let stop = Arc::new(AtomicBool::new(true));
+    let limits = SearchLimits::default().with_stop(stop);
+
+    // the searches would take forever without the stop flag
+    let mut bot = MCTSBot::new(u64::MAX, 2.0, SmallRng::seed_from_u64(0));
+    bot.select_move_limited(&board, &limits);
+    assert_eq!(bot.tree::<STTTBoard>().unwrap()[0].visits, 1);
+
+    for parallelism in [Parallelism::Root, Parallelism::SharedTree] {
+        let mut bot = ParallelMCTSBot::new(u64::MAX, 2.0, 4, parallelism, SmallRng::seed_from_u64(0));
+        let tree = bot.build_tree_limited(&board, &limits);
+        assert!(tree[0].visits <= 4, "{:?}", parallelism);
+    }
+
+    let budget = MinimaxBudget::depth(100);
+    let mut bot = MiniMaxBot::new_iterative(budget, AtaxxTileHeuristic::default(), SmallRng::seed_from_u64(0));
+    bot.select_move_limited(&AtaxxBoard::default(), &limits);
+}
+
+#[test]
+fn depth_limit_finds_win() {
+    let mut board = TTTBoard::default();
+    for (x, y) in [(0, 0), (0, 1), (1, 0), (1, 1)] {
+        board.play(Coord3::from_xy(x, y));
+    }
+
+    let mut bot = SolverBot::new(9, SmallRng::seed_from_u64(0));
+    let mv = bot.select_move_limited(&board, &SearchLimits::depth(1));
+    assert_eq!(mv, Coord3::from_xy(2, 0));
+}
+
+#[test]
+fn bot_game_clock() {
+    let time_control = TimeControl::Clock {
+        initial: Duration::from_millis(200),
+        increment: Duration::from_millis(5),
+    };
+
+    let result = bot_game::run_with_time_control(
+        TTTBoard::default,
+        || MCTSBot::new(100, 2.0, SmallRng::seed_from_u64(0)),
+        || RandomBot::new(SmallRng::seed_from_u64(1)),
+        2,
+        true,
+        time_control,
+        |_, _| {},
+    );
+
+    assert_eq!(result.game_count, 4);
+    for replay in &result.replays {
+        if let Some(player) = replay.lost_on_time {
+            assert_eq!(replay.outcome, Outcome::WonBy(player.other()));
+        }
+    }
+}
+
+fn assert_returns_within<B: Board>(bot: &mut impl Bot<B>, board: &B, limits: &SearchLimits) {
+    let start = Instant::now();
+    let mv = bot.select_move_limited(board, limits);
+    assert!(board.is_available_move(mv));
+
+    // leave plenty of room for slow test machines
+    let budget = limits.time_budget().unwrap();
+    assert!(start.elapsed() < budget * 20, "took {:?}", start.elapsed());
+}
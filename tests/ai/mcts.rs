@@ -0,0 +1,218 @@
+use rand::rngs::SmallRng;
+use rand::SeedableRng;
+
+use board_game::ai::evaluator::RandomRollout;
+use board_game::ai::mcts::{
+    mcts_build_tree_root_parallel, mcts_build_tree_shared, mcts_build_tree_with_policy, MCTSBot, ParallelMCTSBot,
+    Parallelism, Puct, SelectionPolicy, Tree, Ucb1Tuned, Uct,
+};
+use board_game::ai::Bot;
+use board_game::board::{AltBoard, Board};
+use board_game::games::sttt::STTTBoard;
+use board_game::games::ttt::TTTBoard;
+use board_game::util::board_gen::random_board_with_moves;
+use board_game::util::coord::Coord3;
+use board_game::wdl::WDL;
+
+#[test]
+fn mcts_reuse_tree() {
+    let mut board = STTTBoard::default();
+    let mut bot = MCTSBot::new(1000, 2.0, SmallRng::seed_from_u64(0));
+
+    let mv = bot.select_move(&board);
+    let prev_tree = bot.tree::<STTTBoard>().unwrap();
+    let prev_visits = prev_tree[prev_tree.best_child()].visits;
+    assert!(prev_visits > 0);
+
+    board.play(mv);
+    bot.select_move(&board);
+
+    let tree = bot.tree::<STTTBoard>().unwrap();
+    assert_eq!(tree.root_board, board);
+    assert_eq!(tree[0].visits, prev_visits + 1000);
+    assert!(tree[0].last_move.is_none());
+
+    assert_children_consistent(tree);
+}
+
+#[test]
+fn mcts_build_tree_fresh() {
+    let board = STTTBoard::default();
+    let mut bot = MCTSBot::new(1000, 2.0, SmallRng::seed_from_u64(0));
+
+    bot.select_move(&board);
+    let tree = bot.build_tree(&board);
+    assert_eq!(tree[0].visits, 1000);
+
+    // the kept tree is not touched by build_tree
+    assert_eq!(bot.tree::<STTTBoard>().unwrap()[0].visits, 1000);
+    assert!(bot.tree::<TTTBoard>().is_none());
+}
+
+#[test]
+fn mcts_principal_variation() {
+    let board = STTTBoard::default();
+    let tree = mcts_build_tree_with_policy(&board, 1000, &Uct::new(2.0), &mut SmallRng::seed_from_u64(0));
+
+    let pv = tree.principal_variation();
+    assert!(pv.len() > 1);
+    assert_eq!(pv[0], tree.best_move());
+
+    let mut curr = board;
+    for mv in pv {
+        assert!(curr.is_available_move(mv));
+        curr.play(mv);
+    }
+
+    let (board, win) = ttt_immediate_win();
+    let tree = mcts_build_tree_with_policy(&board, 100, &Uct::new(2.0), &mut SmallRng::seed_from_u64(0));
+    assert_eq!(tree.principal_variation(), vec![win]);
+}
+
+#[test]
+fn mcts_unknown_board_fresh_tree() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let mut bot = MCTSBot::new(1000, 2.0, SmallRng::seed_from_u64(1));
+
+    bot.select_move(&STTTBoard::default());
+
+    let board = random_board_with_moves(&STTTBoard::default(), 10, &mut rng);
+    bot.select_move(&board);
+
+    let tree = bot.tree::<STTTBoard>().unwrap();
+    assert_eq!(tree.root_board, board);
+    assert_eq!(tree[0].visits, 1000);
+}
+
+#[test]
+fn mcts_reuse_tree_after_reply() {
+    let mut board = STTTBoard::default();
+    let mut bot = MCTSBot::new(1000, 2.0, SmallRng::seed_from_u64(0));
+
+    let mv = bot.select_move(&board);
+    let prev_tree = bot.tree::<STTTBoard>().unwrap();
+    let node = prev_tree.best_child();
+    let reply_node = prev_tree[node]
+        .children
+        .unwrap()
+        .iter()
+        .max_by_key(|&c| prev_tree[c].visits)
+        .unwrap();
+    let reply = prev_tree[reply_node].last_move.unwrap();
+    let prev_visits = prev_tree[reply_node].visits;
+
+    board.play(mv);
+    board.play(reply);
+    bot.select_move(&board);
+
+    let tree = bot.tree::<STTTBoard>().unwrap();
+    assert_eq!(tree.root_board, board);
+    assert_eq!(tree[0].visits, prev_visits + 1000);
+}
+
+#[test]
+fn mcts_root_parallel() {
+    let board = STTTBoard::default();
+    let tree = mcts_build_tree_root_parallel(
+        &board,
+        1001,
+        4,
+        &Uct::new(2.0),
+        &RandomRollout,
+        &mut SmallRng::seed_from_u64(0),
+    );
+
+    assert_eq!(tree[0].visits, 1001);
+    assert_eq!(tree.nodes.len(), 1 + 81);
+    assert_children_consistent(&tree);
+    assert!(board.is_available_move(tree.best_move()));
+}
+
+#[test]
+fn mcts_shared_tree() {
+    let board = STTTBoard::default();
+    let tree = mcts_build_tree_shared(
+        &board,
+        1000,
+        4,
+        &Uct::new(2.0),
+        &RandomRollout,
+        &mut SmallRng::seed_from_u64(0),
+    );
+
+    assert_eq!(tree[0].visits, 1000);
+    assert!(tree.nodes.iter().all(|node| node.virtual_loss == 0));
+    assert_children_consistent(&tree);
+    assert!(board.is_available_move(tree.best_move()));
+}
+
+#[test]
+fn mcts_parallel_finds_win() {
+    let (board, winning_move) = ttt_immediate_win();
+
+    for parallelism in [Parallelism::Root, Parallelism::SharedTree] {
+        let mut bot = ParallelMCTSBot::new(100, 2.0, 4, parallelism, SmallRng::seed_from_u64(0));
+        assert_eq!(bot.select_move(&board), winning_move, "{:?}", parallelism);
+    }
+}
+
+#[test]
+fn mcts_policies_find_win() {
+    let (board, winning_move) = ttt_immediate_win();
+
+    let mut uct = MCTSBot::new_with_policy(100, Uct::new(2.0), SmallRng::seed_from_u64(0));
+    assert_eq!(uct.select_move(&board), winning_move);
+    let mut tuned = MCTSBot::new_with_policy(100, Ucb1Tuned::new(1.0), SmallRng::seed_from_u64(0));
+    assert_eq!(tuned.select_move(&board), winning_move);
+    let mut puct = MCTSBot::new_with_policy(100, Puct::new(1.0), SmallRng::seed_from_u64(0));
+    assert_eq!(puct.select_move(&board), winning_move);
+}
+
+#[test]
+fn mcts_policies_build_tree() {
+    fn check(policy: impl SelectionPolicy) {
+        let board = STTTBoard::default();
+        let tree = mcts_build_tree_with_policy(&board, 1000, &policy, &mut SmallRng::seed_from_u64(0));
+
+        assert_eq!(tree[0].visits, 1000, "{:?}", policy);
+        assert_children_consistent(&tree);
+        assert!(board.is_available_move(tree.best_move()));
+
+        let children = tree[0].children.unwrap();
+        let prior_sum: f32 = children.iter().map(|c| tree[c].prior).sum();
+        assert!((prior_sum - 1.0).abs() < 1e-3, "{:?}", policy);
+    }
+
+    check(Uct::new(2.0));
+    check(Ucb1Tuned::new(1.0));
+    check(Puct::new(1.0));
+}
+
+#[test]
+fn puct_scores_unvisited() {
+    let puct = Puct::new(1.0);
+    assert!(!puct.unvisited_first());
+
+    // unvisited children are scored as a draw, children with a higher prior are explored first
+    let unvisited = WDL::default();
+    assert_eq!(puct.score(0, unvisited, 0.5), 0.5);
+    assert!(puct.score(4, unvisited, 0.5) > puct.score(4, unvisited, 0.25));
+}
+
+fn ttt_immediate_win() -> (TTTBoard, Coord3) {
+    // X can win immediately by playing in the top right corner
+    let mut board = TTTBoard::default();
+    for (x, y) in [(0, 0), (0, 1), (1, 0), (1, 1)] {
+        board.play(Coord3::from_xy(x, y));
+    }
+    (board, Coord3::from_xy(2, 0))
+}
+
+fn assert_children_consistent<B: AltBoard>(tree: &Tree<B>) {
+    for (i, node) in tree.nodes.iter().enumerate() {
+        if let Some(children) = node.children {
+            assert!(children.start.get() > i);
+            assert!(children.start.get() + children.length <= tree.nodes.len());
+        }
+    }
+}
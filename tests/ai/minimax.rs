@@ -0,0 +1,201 @@
+use std::cmp::{max, Ordering};
+use std::str::FromStr;
+use std::sync::atomic::{self, AtomicUsize};
+use std::time::Duration;
+
+use chess::ChessMove;
+use internal_iterator::InternalIterator;
+use rand::rngs::SmallRng;
+use rand::SeedableRng;
+
+use board_game::ai::minimax::{minimax_iterative, minimax_pv, minimax_value, Heuristic, MiniMaxBot, MinimaxBudget};
+use board_game::ai::solver::{SolverHeuristic, SolverValue};
+use board_game::ai::transposition::TranspositionTable;
+use board_game::ai::Bot;
+use board_game::board::{Board, BoardMoves};
+use board_game::games::ataxx;
+use board_game::games::ataxx::AtaxxBoard;
+use board_game::games::chess::{ChessBoard, Rules};
+use board_game::games::ttt::TTTBoard;
+use board_game::heuristic::ataxx::AtaxxTileHeuristic;
+use board_game::heuristic::chess::ChessPieceValueHeuristic;
+use board_game::util::board_gen::random_board_with_moves;
+use board_game::util::coord::Coord3;
+
+#[test]
+fn iterative_matches_fixed_depth() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let heuristic = AtaxxTileHeuristic::default();
+
+    for _ in 0..10 {
+        let board = random_board_with_moves(&AtaxxBoard::default(), 4, &mut rng);
+        if board.is_done() {
+            continue;
+        }
+
+        let results = minimax_iterative(&board, &heuristic, MinimaxBudget::depth(3), None, &mut rng);
+        assert_eq!(results.iter().map(|r| r.depth).collect::<Vec<_>>(), vec![1, 2, 3]);
+
+        for result in &results {
+            assert_eq!(minimax_value(&board, &heuristic, result.depth), result.value);
+            assert!(board.is_available_move(result.best_move));
+        }
+    }
+}
+
+#[test]
+fn pv_leaf_matches_value() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let heuristic = AtaxxTileHeuristic::default();
+
+    for _ in 0..10 {
+        let board = random_board_with_moves(&AtaxxBoard::default(), 4, &mut rng);
+        if board.is_done() {
+            continue;
+        }
+
+        let result = minimax_pv(&board, &heuristic, 3);
+        assert_eq!(result.value, minimax_value(&board, &heuristic, 3));
+
+        // the value of the board at the end of the pv is the minimax value
+        let mut leaf = board.clone();
+        for &mv in &result.pv {
+            assert!(leaf.is_available_move(mv));
+            leaf.play(mv);
+        }
+        assert!(result.pv.len() == 3 || leaf.is_done());
+
+        let leaf_value = heuristic.value(&leaf, result.pv.len() as u32);
+        let leaf_value = if result.pv.len() % 2 == 0 {
+            leaf_value
+        } else {
+            -leaf_value
+        };
+        assert_eq!(leaf_value, result.value);
+
+        let results = minimax_iterative(&board, &heuristic, MinimaxBudget::depth(3), None, &mut rng);
+        for result in &results {
+            assert_eq!(result.pv.first(), Some(&result.best_move));
+            assert!(result.pv.len() <= result.depth as usize);
+        }
+    }
+}
+
+#[test]
+fn iterative_stops_at_game_end() {
+    let board = TTTBoard::default();
+    let mut tt = TranspositionTable::new(1 << 12, false);
+    let mut rng = SmallRng::seed_from_u64(0);
+
+    let results = minimax_iterative(
+        &board,
+        &SolverHeuristic,
+        MinimaxBudget::nodes(u64::MAX),
+        Some(&mut tt),
+        &mut rng,
+    );
+
+    let last = results.last().unwrap();
+    assert_eq!(last.value, SolverValue::Draw);
+    assert!(last.depth <= 9, "search continued after reaching the end of the game");
+}
+
+#[test]
+fn iterative_budgets() {
+    let board = AtaxxBoard::default();
+    let heuristic = AtaxxTileHeuristic::default();
+    let mut rng = SmallRng::seed_from_u64(0);
+
+    // the first iteration is always completed
+    let results = minimax_iterative(&board, &heuristic, MinimaxBudget::nodes(1), None, &mut rng);
+    assert_eq!(results.len(), 1);
+
+    let results = minimax_iterative(&board, &heuristic, MinimaxBudget::nodes(10_000), None, &mut rng);
+    assert!(results.iter().map(|r| r.nodes).sum::<u64>() <= 10_000);
+
+    let results = minimax_iterative(
+        &board,
+        &heuristic,
+        MinimaxBudget::time(Duration::from_millis(50)),
+        None,
+        &mut rng,
+    );
+    assert!(!results.is_empty());
+}
+
+#[test]
+fn iterative_bot() {
+    let budget = MinimaxBudget::time(Duration::from_millis(20));
+    let mut bot = MiniMaxBot::new_iterative(budget, AtaxxTileHeuristic::default(), SmallRng::seed_from_u64(0))
+        .with_tt(TranspositionTable::new(1 << 16, false));
+
+    let mut board = AtaxxBoard::default();
+    for _ in 0..4 {
+        let mv = bot.select_move(&board);
+        assert!(board.is_available_move(mv));
+        board.play(mv);
+    }
+}
+
+#[test]
+fn chess_move_order_mvv_lva() {
+    let board = ChessBoard::new_without_history_fen("4k3/8/8/3q4/2P1r3/3Q4/8/K7 w - - 0 1", Rules::default());
+    let score = |mv: &str| {
+        let mv = ChessMove::from_str(mv).unwrap();
+        assert!(board.is_available_move(mv));
+        ChessPieceValueHeuristic.move_order_score(&board, mv)
+    };
+
+    let pawn_takes_queen = score("c4d5");
+    let queen_takes_queen = score("d3d5");
+    let queen_takes_rook = score("d3e4");
+    let quiet = score("a1a2");
+
+    assert!(pawn_takes_queen > queen_takes_queen);
+    assert!(queen_takes_queen > queen_takes_rook);
+    assert!(queen_takes_rook > quiet);
+}
+
+#[test]
+fn ataxx_move_order_prefers_copies() {
+    let board = AtaxxBoard::default();
+    let heuristic = AtaxxTileHeuristic::default();
+
+    board.available_moves().for_each(|mv| {
+        let expected = match mv {
+            ataxx::Move::Copy { .. } => 1,
+            _ => 0,
+        };
+        assert_eq!(expected, heuristic.move_order_score(&board, mv));
+    });
+}
+
+#[test]
+fn value_update_is_called() {
+    /// A heuristic that overrides `value_update` without setting `VALUE_FROM_CHILD`.
+    #[derive(Debug, Default)]
+    struct CountingHeuristic {
+        updates: AtomicUsize,
+    }
+
+    impl Heuristic<TTTBoard> for CountingHeuristic {
+        type V = i32;
+
+        fn value(&self, board: &TTTBoard, length: u32) -> i32 {
+            SolverHeuristic.value(board, length).to_i32()
+        }
+
+        fn value_update(&self, _: &TTTBoard, _: i32, board_length: u32, _: Coord3, child: &TTTBoard) -> i32 {
+            self.updates.fetch_add(1, atomic::Ordering::Relaxed);
+            self.value(child, board_length + 1)
+        }
+
+        fn merge(old: i32, new: i32) -> (i32, Ordering) {
+            (max(old, new), new.cmp(&old))
+        }
+    }
+
+    let heuristic = CountingHeuristic::default();
+    minimax_value(&TTTBoard::default(), &heuristic, 2);
+    assert!(heuristic.updates.load(atomic::Ordering::Relaxed) > 0);
+}
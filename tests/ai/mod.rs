@@ -1,2 +1,8 @@
+pub mod evaluator;
+pub mod info;
 pub mod is_double_forced_draw;
+pub mod limits;
+pub mod mcts;
+pub mod minimax;
 pub mod solver;
+pub mod transposition;
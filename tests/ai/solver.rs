@@ -1,11 +1,14 @@
 use internal_iterator::InternalIterator;
 
-use board_game::ai::solver::{solve_all_moves, solve_value, SolverValue};
-use board_game::board::{Board, BoardMoves};
+use board_game::ai::solver::{solve, solve_all_moves, solve_pv, solve_value, SolverValue};
+use board_game::board::{Board, BoardMoves, Outcome, Player};
 use board_game::games::ttt::TTTBoard;
+use board_game::pov::NonPov;
 use board_game::util::coord::Coord3;
 use board_game::util::game_stats::all_possible_boards;
 use board_game::wdl::OutcomeWDL;
+use rand::rngs::SmallRng;
+use rand::SeedableRng;
 
 #[test]
 fn solver_ttt_root() {
@@ -27,8 +30,6 @@ fn solver_ttt_win() {
     println!("{}", board);
 
     let root_eval = solve_all_moves(&board, 20);
-    println!("{:?}", root_eval);
-
     assert_eq!(Some(OutcomeWDL::Win), root_eval.value.to_outcome_wdl());
 }
 
@@ -41,11 +42,32 @@ fn solver_ttt_loss() {
     println!("{}", board);
 
     let root_eval = solve_all_moves(&board, 20);
-    println!("{:?}", root_eval);
-
     assert_eq!(Some(OutcomeWDL::Loss), root_eval.value.to_outcome_wdl());
 }
 
+#[test]
+fn solver_ttt_all_moves_exact() {
+    // after a corner opening every reply except the center loses, they must not be reported as ties
+    let mut board = TTTBoard::default();
+    board.play(Coord3::from_xy(0, 0));
+
+    let root_eval = solve_all_moves(&board, 20);
+    assert_eq!(Some(OutcomeWDL::Draw), root_eval.value.to_outcome_wdl());
+    assert_eq!(Some(vec![Coord3::from_xy(1, 1)]), root_eval.best_move);
+}
+
+#[test]
+fn solver_ttt_random_move_exact() {
+    // the root keeps its alpha bound here, losing replies that only look like ties must not be picked
+    let mut board = TTTBoard::default();
+    board.play(Coord3::from_xy(0, 0));
+
+    for seed in 0..20 {
+        let result = solve(&board, 20, &mut SmallRng::seed_from_u64(seed));
+        assert_eq!(Some(Coord3::from_xy(1, 1)), result.best_move);
+    }
+}
+
 #[test]
 fn solver_ttt_consistent() {
     let boards = all_possible_boards(&TTTBoard::default(), 20, false);
@@ -74,3 +96,48 @@ fn solver_ttt_consistent() {
         println!();
     }
 }
+
+#[test]
+fn solver_ttt_pv() {
+    let mut board = TTTBoard::default();
+    board.play(Coord3::from_xy(0, 0));
+    board.play(Coord3::from_xy(1, 0));
+
+    let result = solve_pv(&board, 20);
+    let length = match result.value {
+        SolverValue::WinIn(length) => length,
+        value => panic!("expected a win, got {:?}", value),
+    };
+    assert_eq!(result.pv.len() as u32, length);
+
+    let mut end = board.clone();
+    for &mv in &result.pv {
+        end.play(mv);
+    }
+    assert_eq!(end.outcome(), Some(Outcome::WonBy(Player::A)));
+}
+
+#[test]
+fn solver_ttt_pv_consistent() {
+    let boards = all_possible_boards(&TTTBoard::default(), 20, false);
+
+    for board in boards {
+        let result = solve_pv(&board, 20);
+        assert_eq!(result.value, solve_value(&board, 20));
+
+        // the game is solved completely, so the pv always goes until the end of the game
+        let mut end = board.clone();
+        for &mv in &result.pv {
+            assert!(end.is_available_move(mv));
+            end.play(mv);
+        }
+        let outcome = end.outcome().map(|outcome| outcome.pov(board.next_player()));
+        assert_eq!(
+            outcome,
+            result.value.to_outcome_wdl(),
+            "board {} pv {:?}",
+            board,
+            result.pv
+        );
+    }
+}
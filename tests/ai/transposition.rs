@@ -0,0 +1,73 @@
+use rand::rngs::SmallRng;
+use rand::SeedableRng;
+
+use board_game::ai::minimax::{minimax_value, minimax_value_with_tt};
+use board_game::ai::solver::{solve_all_moves, solve_all_moves_with_tt, solve_value, solve_value_with_tt};
+use board_game::ai::transposition::TranspositionTable;
+use board_game::games::ataxx::AtaxxBoard;
+use board_game::games::connect4::Connect4;
+use board_game::games::ttt::TTTBoard;
+use board_game::heuristic::ataxx::AtaxxTileHeuristic;
+use board_game::util::board_gen::random_board_with_moves;
+use board_game::util::game_stats::all_possible_boards;
+
+#[test]
+fn solver_tt_ttt_consistent() {
+    let boards = all_possible_boards(&TTTBoard::default(), 20, false);
+
+    for canonicalize in [false, true] {
+        let mut tt = TranspositionTable::new(1024, canonicalize);
+
+        for board in &boards {
+            let expected = solve_all_moves(board, 20);
+            let actual = solve_all_moves_with_tt(board, 20, &mut tt);
+
+            assert_eq!(expected.value, actual.value, "value mismatch for {:?}", board);
+            assert!(!actual.best_move.unwrap().is_empty());
+        }
+    }
+}
+
+#[test]
+fn solver_tt_connect4() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let mut tt = TranspositionTable::new(1 << 16, true);
+
+    for _ in 0..20 {
+        let board = random_board_with_moves(&Connect4::default(), 10, &mut rng);
+
+        let expected = solve_value(&board, 6);
+        let actual = solve_value_with_tt(&board, 6, &mut tt);
+        assert_eq!(expected, actual, "value mismatch for {:?}", board);
+    }
+}
+
+#[test]
+fn solver_tt_connect4_zobrist() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let mut tt = TranspositionTable::new(1 << 16, true).with_zobrist();
+
+    for _ in 0..20 {
+        let board = random_board_with_moves(&Connect4::default(), 10, &mut rng);
+
+        let expected = solve_value(&board, 6);
+        let actual = solve_value_with_tt(&board, 6, &mut tt);
+        assert_eq!(expected, actual, "value mismatch for {:?}", board);
+    }
+}
+
+#[test]
+fn minimax_tt_ataxx() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let heuristic = AtaxxTileHeuristic::default();
+
+    for _ in 0..10 {
+        let board = random_board_with_moves(&AtaxxBoard::default(), 6, &mut rng);
+
+        // use a fresh table every time, entries from deeper searches would make the results diverge
+        let mut tt = TranspositionTable::new(1 << 16, false);
+        let expected = minimax_value(&board, &heuristic, 3);
+        let actual = minimax_value_with_tt(&board, &heuristic, 3, &mut tt);
+        assert_eq!(expected, actual, "value mismatch for {:?}", board);
+    }
+}
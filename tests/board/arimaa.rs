@@ -3,7 +3,7 @@ use std::str::FromStr;
 use arimaa_engine_step::Action;
 use internal_iterator::InternalIterator;
 
-use board_game::board::{Board, BoardMoves, Outcome, Player};
+use board_game::board::{Board, BoardMoves, BoardNotation, Outcome, Player};
 use board_game::games::arimaa::ArimaaBoard;
 
 use crate::board::board_test_main;
@@ -38,6 +38,26 @@ fn gold_goal() {
     board_test_main(&board);
 }
 
+#[test]
+fn notation() {
+    let board = ArimaaBoard::from_str(BASIC_SETUP).unwrap();
+
+    let position = board.format_position();
+    let parsed = ArimaaBoard::parse_position(&position).unwrap();
+    assert_eq!(position, parsed.format_position());
+    assert_eq!(board.next_player(), parsed.next_player());
+
+    board.available_moves().for_each(|mv| {
+        let mv_str = board.format_move(mv);
+        assert_eq!(
+            mv,
+            board.parse_move(&mv_str).unwrap(),
+            "round trip failed for {}",
+            mv_str
+        );
+    });
+}
+
 const BASIC_SETUP: &str = "
      +-----------------+
     8| r r r r r r r r |
@@ -3,12 +3,12 @@ use itertools::Itertools;
 use rand::rngs::SmallRng;
 use rand::{Rng, SeedableRng};
 
-use board_game::board::{Board, BoardMoves, BoardSymmetry, Outcome, Player};
-use board_game::games::ataxx::{AtaxxBoard, Move};
+use board_game::board::{Board, BoardMoves, BoardSymmetry, Outcome, Player, ZobristHash};
+use board_game::games::ataxx::{ataxx_game_to_pgn, parse_ataxx_pgn, parse_ataxx_pgns, AtaxxBoard, Move};
 use board_game::symmetry::D4Symmetry;
 use board_game::util::board_gen::random_board_with_moves;
 
-use crate::board::{board_perft_main, board_test_main};
+use crate::board::{board_notation_test, board_perft_main, board_test_main, board_zobrist_test};
 
 #[test]
 fn ataxx_empty() {
@@ -224,3 +224,96 @@ fn ataxx_perft() {
         ],
     );
 }
+
+#[test]
+fn ataxx_zobrist() {
+    board_zobrist_test(&AtaxxBoard::default());
+    board_zobrist_test(&AtaxxBoard::from_fen("x5o/7/2-1-2/7/2-1-2/7/o5x x 0 1").unwrap());
+    board_zobrist_test(&AtaxxBoard::diagonal(4));
+
+    let mut rng = SmallRng::seed_from_u64(0);
+    for _ in 0..100 {
+        let board = random_board_with_moves(&AtaxxBoard::default(), rng.gen_range(0..40), &mut rng);
+        let parsed = AtaxxBoard::from_fen(&board.to_fen()).unwrap();
+        assert_eq!(
+            board.zobrist(),
+            parsed.zobrist(),
+            "key mismatch after fen round trip for {:?}",
+            board
+        );
+    }
+}
+
+#[test]
+fn ataxx_notation() {
+    board_notation_test(&AtaxxBoard::default());
+    board_notation_test(&AtaxxBoard::diagonal(4));
+}
+
+#[test]
+fn ataxx_pgn_round_trip() {
+    let mut rng = SmallRng::seed_from_u64(0);
+
+    for _ in 0..10 {
+        let start = random_board_with_moves(&AtaxxBoard::default(), rng.gen_range(0..4), &mut rng);
+        let mut board = start.clone();
+        let mut moves = vec![];
+        while !board.is_done() {
+            let mv = board.random_available_move(&mut rng);
+            board.play(mv);
+            moves.push(mv);
+        }
+
+        let pgn = ataxx_game_to_pgn("x \"bot\"", "o", &start, &moves, board.outcome());
+        println!("{}", pgn);
+
+        let game = parse_ataxx_pgn(&pgn).unwrap();
+        assert_eq!(game.start, start);
+        assert_eq!(game.moves, moves);
+        assert_eq!(game.outcome, board.outcome());
+        assert_eq!(game.tag("Black"), Some("x \"bot\""));
+        assert_eq!(game.final_board(), board);
+    }
+}
+
+#[test]
+fn ataxx_pgn_parse() {
+    let pgn = "\
+[Event \"test\"]
+[FEN \"x5o/7/7/7/7/7/o5x o 0 1\"]
+
+1... a2 {comment} 2. g2 ; line comment
+b3 $1 3.f1 *
+
+[Result \"1/2-1/2\"]
+[FEN \"x5o/7/7/7/7/7/o5x x 100 1\"]
+
+1/2-1/2
+";
+
+    let games = parse_ataxx_pgns(pgn).unwrap();
+    assert_eq!(games.len(), 2);
+
+    let expected = ["a2", "g2", "b3", "f1"]
+        .iter()
+        .map(|mv| Move::from_uai(mv).unwrap())
+        .collect_vec();
+    assert_eq!(games[0].moves, expected);
+    assert_eq!(games[0].outcome, None);
+    assert_eq!(games[0].tag("Event"), Some("test"));
+
+    assert!(games[1].moves.is_empty());
+    assert_eq!(games[1].outcome, Some(Outcome::Draw));
+}
+
+#[test]
+fn ataxx_pgn_invalid() {
+    // unavailable move
+    assert!(parse_ataxx_pgn("1. f1 f1 *").is_err());
+    // invalid move
+    assert!(parse_ataxx_pgn("1. z9 *").is_err());
+    // result does not match the final board
+    assert!(parse_ataxx_pgn("[FEN \"x5o/7/7/7/7/7/o5x x 100 1\"]\n\n1-0").is_err());
+    // result tag does not match the movetext
+    assert!(parse_ataxx_pgn("[Result \"1-0\"]\n\n1. f1 0-1").is_err());
+}
@@ -5,7 +5,7 @@ use chess::ChessMove;
 use board_game::board::Board;
 use board_game::games::chess::{ChessBoard, Rules};
 
-use crate::board::{board_perft_main, board_test_main};
+use crate::board::{board_notation_test, board_perft_main, board_test_main, board_zobrist_test};
 
 //TODO add tests for 50 move and 3-move rule
 
@@ -65,3 +65,13 @@ fn chess_perft() {
         ],
     );
 }
+
+#[test]
+fn chess_zobrist() {
+    board_zobrist_test(&ChessBoard::default());
+}
+
+#[test]
+fn chess_notation() {
+    board_notation_test(&ChessBoard::default());
+}
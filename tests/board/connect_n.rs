@@ -0,0 +1,201 @@
+use board_game::board::Outcome::WonBy;
+use board_game::board::{Board, BoardNotation, BoardSymmetry, Outcome, Player, ZobristHash};
+use board_game::games::connect4::Connect4;
+use board_game::games::connect_n::ConnectN;
+use board_game::symmetry::D1Symmetry;
+use board_game::util::board_gen::{board_with_moves, random_board_with_moves};
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+
+use crate::board::{board_notation_test, board_perft_main, board_test_main, board_zobrist_test};
+
+type Connect3 = ConnectN<4, 4, 3>;
+type Connect5 = ConnectN<9, 7, 5>;
+
+#[test]
+fn empty() {
+    board_test_main(&Connect4::default());
+    board_test_main(&ConnectN::<6, 7, 4>::default());
+    board_test_main(&ConnectN::<8, 7, 4>::default());
+    board_test_main(&Connect3::default());
+    board_test_main(&Connect5::default());
+    board_test_main(&ConnectN::<1, 1, 1>::default());
+}
+
+#[test]
+fn basic() {
+    board_test_main(&board_with_moves(Connect4::default(), &[1]));
+    board_test_main(&board_with_moves(Connect4::default(), &[1, 2]));
+    board_test_main(&board_with_moves(Connect4::default(), &[1, 2, 3]));
+
+    board_test_main(&board_with_moves(Connect5::default(), &[1]));
+    board_test_main(&board_with_moves(Connect5::default(), &[1, 8]));
+    board_test_main(&board_with_moves(Connect5::default(), &[1, 8, 8]));
+}
+
+#[test]
+fn draw() {
+    let moves = vec![
+        1, 0, 3, 0, 5, 4, 4, 4, 0, 6, 2, 0, 3, 0, 2, 6, 4, 1, 0, 3, 6, 5, 3, 1, 1, 6, 3, 5, 6, 3, 1, 4, 5, 4, 5, 1, 2,
+        2, 5, 2, 2, 6,
+    ];
+    check_outcome::<7, 6, 4>(&moves, Some(Outcome::Draw));
+
+    let board = ConnectN::<2, 2, 3>::from_moves("0011").unwrap();
+    assert_eq!(board.outcome(), Some(Outcome::Draw));
+    board_test_main(&board);
+}
+
+#[test]
+fn wins() {
+    check_outcome::<7, 6, 4>(&[1, 1, 2, 2, 3, 3, 4], Some(WonBy(Player::A)));
+    check_outcome::<7, 6, 4>(&[1, 2, 1, 2, 1, 2, 1], Some(WonBy(Player::A)));
+    check_outcome::<7, 6, 4>(&[1, 2, 2, 3, 6, 3, 3, 4, 6, 4, 6, 4, 4], Some(WonBy(Player::A)));
+    check_outcome::<7, 6, 4>(&[4, 3, 3, 2, 6, 2, 2, 1, 6, 1, 6, 1, 1], Some(WonBy(Player::A)));
+
+    check_outcome::<4, 4, 3>(&[0, 0, 1, 1, 2], Some(WonBy(Player::A)));
+    check_outcome::<4, 4, 3>(&[0, 1, 0, 1, 3, 1], Some(WonBy(Player::B)));
+    check_outcome::<4, 4, 3>(&[0, 1, 1, 2, 3, 2], None);
+    check_outcome::<4, 4, 3>(&[0, 1, 1, 2, 3, 2, 2], Some(WonBy(Player::A)));
+    check_outcome::<4, 4, 3>(&[3, 2, 2, 1, 0, 1, 1], Some(WonBy(Player::A)));
+
+    check_outcome::<9, 7, 5>(&[0, 0, 1, 1, 2, 2, 3, 3], None);
+    check_outcome::<9, 7, 5>(&[0, 0, 1, 1, 2, 2, 3, 3, 4], Some(WonBy(Player::A)));
+    check_outcome::<9, 7, 5>(&[8, 0, 8, 0, 8, 0, 8, 0, 7, 0], Some(WonBy(Player::B)));
+}
+
+#[test]
+fn mirror() {
+    let board = board_with_moves(Connect4::default(), &[0, 1, 1]);
+    let mirrored = board_with_moves(Connect4::default(), &[6, 5, 5]);
+    assert_eq!(board.map(D1Symmetry::new(true)), mirrored);
+    board_test_main(&mirrored);
+
+    let board = board_with_moves(Connect5::default(), &[0, 1, 1]);
+    let mirrored = board_with_moves(Connect5::default(), &[8, 7, 7]);
+    assert_eq!(board.map(D1Symmetry::new(true)), mirrored);
+}
+
+fn check_outcome<const W: u8, const H: u8, const N: u8>(moves: &[u8], outcome: Option<Outcome>) {
+    let board = board_with_moves(ConnectN::<W, H, N>::default(), moves);
+    println!("moves: {:?}", moves);
+    println!("{}", board);
+
+    assert_eq!(board.outcome(), outcome);
+
+    board_test_main(&board);
+}
+
+#[test]
+fn perft() {
+    board_perft_main(
+        |s: &str| Connect4::from_fen(s).unwrap(),
+        Some(|b: &Connect4| b.to_fen()),
+        vec![(
+            "......./......./......./......./......./.......",
+            vec![1, 7, 49, 343, 2401, 16807, 117649, 823536, 5673234],
+        )],
+    );
+}
+
+#[test]
+fn perft_sizes() {
+    board_perft_main(
+        |s: &str| Connect3::from_moves(s).unwrap(),
+        None::<fn(&Connect3) -> String>,
+        vec![("", vec![1, 4, 16, 64, 256, 1020, 3588, 13148, 40520, 122884, 293850])],
+    );
+    board_perft_main(
+        |s: &str| ConnectN::<5, 4, 3>::from_moves(s).unwrap(),
+        None::<fn(&ConnectN<5, 4, 3>) -> String>,
+        vec![("", vec![1, 5, 25, 125, 625, 3120, 14020, 65330, 269032])],
+    );
+    board_perft_main(
+        |s: &str| ConnectN::<6, 5, 5>::from_moves(s).unwrap(),
+        None::<fn(&ConnectN<6, 5, 5>) -> String>,
+        vec![("", vec![1, 6, 36, 216, 1296, 7776, 46650, 279720, 1675170])],
+    );
+    board_perft_main(
+        |s: &str| ConnectN::<8, 7, 4>::from_moves(s).unwrap(),
+        None::<fn(&ConnectN<8, 7, 4>) -> String>,
+        vec![("", vec![1, 8, 64, 512, 4096, 32768, 262144, 2097152, 16553656])],
+    );
+    board_perft_main(
+        |s: &str| Connect5::from_moves(s).unwrap(),
+        None::<fn(&Connect5) -> String>,
+        vec![("", vec![1, 9, 81, 729, 6561, 59049, 531441, 4782969])],
+    );
+}
+
+#[test]
+fn zobrist() {
+    board_zobrist_test(&Connect4::default());
+    board_zobrist_test(&board_with_moves(Connect4::default(), &[3, 3, 2]));
+    board_zobrist_test(&Connect3::default());
+    board_zobrist_test(&Connect5::default());
+
+    // the same position reached with a different move order
+    let board_a = board_with_moves(Connect4::default(), &[1, 2, 3, 4]);
+    let board_b = board_with_moves(Connect4::default(), &[3, 4, 1, 2]);
+    assert_eq!(board_a, board_b);
+    assert_eq!(board_a.zobrist(), board_b.zobrist());
+}
+
+#[test]
+fn notation() {
+    board_notation_test(&Connect4::default());
+    board_notation_test(&Connect5::default());
+    board_notation_test(&ConnectN::<12, 6, 4>::default());
+
+    let board = board_with_moves(Connect4::default(), &[3, 3, 2]);
+    assert_eq!(
+        board.format_position(),
+        "......./......./......./......./...o.../..xx..."
+    );
+    assert_eq!(Connect4::parse_position(&board.format_position()), Ok(board));
+
+    let board = Connect3::from_moves("0112").unwrap();
+    assert_eq!(board.to_fen(), "..../..../.x../xoo.");
+    assert_eq!(Connect3::parse_position("..../..../.x../xoo."), Ok(board));
+
+    let wide = ConnectN::<12, 6, 4>::from_moves("ab").unwrap();
+    assert_eq!(wide.format_move(11), "b");
+    assert_eq!(wide.parse_move("a"), Ok(10));
+
+    // floating tile
+    assert!(Connect4::parse_position("......./......./......./...x.../......./.......").is_err());
+    // too many tiles for player A
+    assert!(Connect4::parse_position("......./......./......./......./......./..xx...").is_err());
+    // A has four in a row but B moved last
+    assert!(Connect4::parse_position("......./......./......./......./o....../xxxxooo").is_err());
+    // A has three in a row but B moved last
+    assert!(Connect3::parse_position("..../o.../oo../xxx.").is_err());
+}
+
+#[test]
+fn fen() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for _ in 0..100 {
+        let board = random_board_with_moves(&Connect4::default(), rng.gen_range(0..42), &mut rng);
+        assert_eq!(Connect4::from_fen(&board.to_fen()), Ok(board));
+    }
+}
+
+#[test]
+fn from_moves() {
+    assert_eq!(Connect4::from_moves(""), Ok(Connect4::default()));
+    assert_eq!(
+        Connect4::from_moves("332"),
+        Ok(board_with_moves(Connect4::default(), &[3, 3, 2]))
+    );
+    assert_eq!(Connect4::parse_position("332"), Connect4::from_moves("332"));
+
+    let won = Connect4::from_moves("0101010").unwrap();
+    assert_eq!(won.outcome(), Some(WonBy(Player::A)));
+
+    // invalid column, full column and moves after the game ended
+    assert!(Connect4::from_moves("7").is_err());
+    assert!(Connect4::from_moves("0000000").is_err());
+    assert!(Connect4::from_moves("01010101").is_err());
+    assert!(Connect3::from_moves("4").is_err());
+}
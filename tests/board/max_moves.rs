@@ -2,10 +2,14 @@ use std::str::FromStr;
 
 use internal_iterator::InternalIterator;
 
-use board_game::board::{Board, BoardMoves, Outcome, Player};
+use board_game::board::{Board, BoardMoves, BoardNotation, Outcome, Player};
+use board_game::games::ataxx::AtaxxBoard;
+use board_game::games::chess::ChessBoard;
 use board_game::games::dummy::DummyGame;
 use board_game::games::max_length::MaxMovesBoard;
 
+use crate::board::{board_notation_test, board_test_main};
+
 #[test]
 fn basic_draw() {
     let dummy = DummyGame::from_str("((((A))))").unwrap();
@@ -22,6 +26,38 @@ fn basic_finished() {
     test_outcomes(board, &[None, None, None, None, Some(Outcome::WonBy(Player::A))])
 }
 
+#[test]
+fn undo_in_place() {
+    // chess boards undo moves in place, the move counter has to be restored as well
+    let mut board = MaxMovesBoard::new(ChessBoard::default(), 2);
+    board_test_main(&board);
+
+    let mv = board.available_moves().next().unwrap();
+    board.play(mv);
+    board_test_main(&board);
+
+    let mv = board.available_moves().next().unwrap();
+    board.play_then_undo(mv, |child| assert_eq!(Some(Outcome::Draw), child.outcome()));
+    assert_eq!(None, board.outcome());
+}
+
+#[test]
+fn notation() {
+    let board = MaxMovesBoard::new(AtaxxBoard::default(), 10);
+    board_notation_test(&board);
+
+    let mut board = board;
+    let mv = board.available_moves().next().unwrap();
+    board.play(mv);
+    let position = format!("{} 1/10", board.inner().format_position());
+    assert_eq!(board.format_position(), position);
+    assert_eq!(MaxMovesBoard::parse_position(&position), Ok(board));
+
+    let start = AtaxxBoard::default().format_position();
+    assert!(MaxMovesBoard::<AtaxxBoard>::parse_position(&format!("{} 11/10", start)).is_err());
+    assert!(MaxMovesBoard::<AtaxxBoard>::parse_position(&start).is_err());
+}
+
 fn test_outcomes(mut board: MaxMovesBoard<DummyGame>, outcomes: &[Option<Outcome>]) {
     for &outcome in outcomes {
         println!("{}", board);
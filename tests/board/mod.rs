@@ -1,5 +1,5 @@
 use std::collections::hash_map::RandomState;
-use std::collections::{BTreeMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt::Debug;
 use std::iter::FromIterator;
 use std::panic::catch_unwind;
@@ -9,14 +9,14 @@ use internal_iterator::InternalIterator;
 use rand::{Rng, SeedableRng};
 use rand_xoshiro::Xoroshiro64StarStar;
 
-use board_game::board::Board;
+use board_game::board::{Board, BoardNotation, ZobristHash};
 use board_game::symmetry::Symmetry;
 use board_game::util::game_stats;
 
 mod arimaa;
 mod ataxx;
 mod chess;
-mod connect4;
+mod connect_n;
 mod max_moves;
 mod oware;
 mod sttt;
@@ -30,6 +30,7 @@ pub fn board_test_main<B: Board>(board: &B) {
     } else {
         test_available_match(board);
         test_random_available_uniform(board);
+        test_play_then_undo(board);
     }
 
     test_symmetry(board);
@@ -70,6 +71,99 @@ pub fn board_perft_main<S: Debug + ?Sized, T: Debug, B: Board>(
     println!("Total: took {:?}", total_start.elapsed());
 }
 
+/// Play random games starting from `board` and check that the incrementally updated zobrist keys
+/// match the keys of the same boards rebuilt through symmetries, and that they distinguish the children of each board.
+pub fn board_zobrist_test<B: ZobristHash>(board: &B) {
+    println!("Testing zobrist keys starting from\n{}", board);
+
+    let mut rng = consistent_rng();
+    let mut keys: HashMap<B, u64> = HashMap::new();
+
+    for _ in 0..10 {
+        let mut curr = board.clone();
+
+        loop {
+            let key = curr.zobrist();
+            assert_eq!(
+                key,
+                *keys.entry(curr.clone()).or_insert(key),
+                "equal boards with different keys"
+            );
+
+            for &sym in B::Symmetry::all() {
+                let back = curr.map(sym).map(sym.inverse());
+                assert_eq!(
+                    key,
+                    back.zobrist(),
+                    "key mismatch after mapping with {:?} for {:?}",
+                    sym,
+                    curr
+                );
+            }
+
+            if curr.is_done() {
+                break;
+            }
+
+            let children: Vec<B> = curr.available_moves().map(|mv| curr.clone_and_play(mv)).collect();
+            let mut child_keys: HashMap<u64, &B> = HashMap::new();
+            for child in &children {
+                let prev = child_keys.entry(child.zobrist()).or_insert(child);
+                assert_eq!(*prev, child, "different children with the same key for {:?}", curr);
+                assert_ne!(key, child.zobrist(), "child with the same key as its parent {:?}", curr);
+            }
+
+            let mv = curr.random_available_move(&mut rng);
+            curr.play(mv);
+        }
+    }
+}
+
+/// Play random games starting from `board` and check that every position and available move
+/// survives a round trip through its notation. Games are cut off after 200 moves to keep long games fast.
+pub fn board_notation_test<B: BoardNotation>(board: &B) {
+    println!("Testing notation starting from\n{}", board);
+
+    let mut rng = consistent_rng();
+
+    for _ in 0..10 {
+        let mut curr = board.clone();
+
+        for _ in 0..200 {
+            let position = curr.format_position();
+            let parsed = B::parse_position(&position)
+                .unwrap_or_else(|e| panic!("failed to parse position {:?}: {:?}", position, e));
+            assert_eq!(position, parsed.format_position(), "position round trip mismatch");
+            assert_eq!(
+                curr.next_player(),
+                parsed.next_player(),
+                "next player mismatch for {:?}",
+                position
+            );
+            assert_eq!(curr.outcome(), parsed.outcome(), "outcome mismatch for {:?}", position);
+
+            if curr.is_done() {
+                break;
+            }
+
+            curr.available_moves().for_each(|mv: B::Move| {
+                let mv_str = curr.format_move(mv);
+                let parsed_mv = curr
+                    .parse_move(&mv_str)
+                    .unwrap_or_else(|e| panic!("failed to parse move {:?} in {:?}: {:?}", mv_str, position, e));
+                assert_eq!(
+                    mv, parsed_mv,
+                    "move round trip mismatch for {:?} in {:?}",
+                    mv_str, position
+                );
+            });
+
+            let mv = curr.random_available_move(&mut rng);
+            curr.play(mv);
+        }
+    }
+}
+
 fn test_done_board_panics<B: Board>(board: &B) {
     assert!(board.is_done(), "bug in test implementation");
 
@@ -138,6 +232,22 @@ fn test_available_match<B: Board>(board: &B) {
     }
 }
 
+fn test_play_then_undo<B: Board>(board: &B) {
+    println!("play_then_undo restores the board:");
+
+    let mut curr = board.clone();
+    let available: Vec<B::Move> = board.available_moves().collect();
+
+    for mv in available {
+        let expected = board.clone_and_play(mv);
+        curr.play_then_undo(mv, |child| {
+            assert_eq!(&expected, child, "play_then_undo child mismatch for move {:?}", mv);
+            assert_eq!(expected.outcome(), child.outcome());
+        });
+        assert_eq!(board, &curr, "board not restored after move {:?}", mv);
+    }
+}
+
 /// Test whether the random move distribution is uniform using
 /// [Pearson's chi-squared test](https://en.wikipedia.org/wiki/Pearson%27s_chi-squared_test).
 fn test_random_available_uniform<B: Board>(board: &B) {
@@ -1,8 +1,10 @@
-use board_game::board::Board;
+use board_game::board::{Board, BoardNotation, Outcome, Player};
 use board_game::games::oware::OwareBoard;
-use board_game::util::board_gen::board_with_moves;
+use board_game::util::board_gen::{board_with_moves, random_board_with_moves};
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
 
-use crate::board::board_test_main;
+use crate::board::{board_notation_test, board_test_main, board_zobrist_test};
 
 #[test]
 fn empty() {
@@ -26,3 +28,45 @@ fn done() {
     board_test_main(&board);
     assert!(board.is_done(), "Board should be done");
 }
+
+#[test]
+fn zobrist() {
+    board_zobrist_test(&OwareBoard::<6>::default());
+    board_zobrist_test(&OwareBoard::<4>::new(3));
+}
+
+#[test]
+fn notation() {
+    board_notation_test(&OwareBoard::<6>::default());
+    board_notation_test(&OwareBoard::<4>::new(3));
+
+    let board = OwareBoard::<6>::default();
+    assert_eq!(board.format_position(), "4,4,4,4,4,4/4,4,4,4,4,4 0 0 A");
+    assert_eq!(OwareBoard::<6>::parse_position(&board.format_position()), Ok(board));
+
+    // the total number of seeds does not divide evenly over the pits
+    assert!(OwareBoard::<6>::parse_position("4,4,4,4,4,4/4,4,4,4,4,5 0 0 A").is_err());
+    // wrong number of pits, invalid score and invalid next player
+    assert!(OwareBoard::<6>::parse_position("4,4,4,4,4/4,4,4,4,4,4,4 0 0 A").is_err());
+    assert!(OwareBoard::<6>::parse_position("4,4,4,4,4,4/4,4,4,4,4,4 x 0 A").is_err());
+    assert!(OwareBoard::<6>::parse_position("4,4,4,4,4,4/4,4,4,4,4,4 0 0 C").is_err());
+    // the game is not done but A has no seeds left
+    assert!(OwareBoard::<6>::parse_position("0,0,0,0,0,0/4,4,4,4,4,4 12 12 A").is_err());
+}
+
+#[test]
+fn fen() {
+    let board = OwareBoard::<6>::from_fen("0,0,0,0,0,1/2,0,0,0,0,0 24 21 A").unwrap();
+    assert_eq!(board.init_seeds(), 4);
+    assert_eq!(board.score(Player::A), 24);
+    assert!(!board.is_done());
+
+    let won = OwareBoard::<6>::from_fen("0,0,0,0,0,0/1,0,0,0,0,0 25 22 B").unwrap();
+    assert_eq!(won.outcome(), Some(Outcome::WonBy(Player::A)));
+
+    let mut rng = SmallRng::seed_from_u64(0);
+    for _ in 0..100 {
+        let board = random_board_with_moves(&OwareBoard::<6>::default(), rng.gen_range(0..40), &mut rng);
+        assert_eq!(OwareBoard::<6>::from_fen(&board.to_fen()), Ok(board));
+    }
+}
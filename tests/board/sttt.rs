@@ -1,7 +1,10 @@
-use board_game::board::{Board, Outcome};
-use board_game::games::sttt::{board_from_compact_string, STTTBoard};
+use board_game::board::{Board, BoardNotation, Outcome, Player, ZobristHash};
+use board_game::games::sttt::{board_from_compact_string, board_to_compact_string, Coord, STTTBoard};
+use board_game::util::board_gen::random_board_with_moves;
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
 
-use crate::board::board_test_main;
+use crate::board::{board_notation_test, board_test_main, board_zobrist_test};
 
 #[test]
 fn sttt_empty() {
@@ -29,3 +32,72 @@ fn sttt_draw() {
     assert_eq!(board.outcome(), Some(Outcome::Draw));
     board_test_main(&board)
 }
+
+#[test]
+fn sttt_zobrist() {
+    board_zobrist_test(&STTTBoard::default());
+
+    let board =
+        board_from_compact_string("x     ooo.Ooo.xx..o  o  oxoxxxoo     x  xo oxx  xo o  x xxooxx  oxox oox  xx xoxo");
+    board_zobrist_test(&board);
+
+    let parsed = board_from_compact_string(&board_to_compact_string(&board));
+    assert_eq!(board.zobrist(), parsed.zobrist());
+}
+
+#[test]
+fn sttt_notation() {
+    board_notation_test(&STTTBoard::default());
+
+    let board =
+        board_from_compact_string("x     ooo.Ooo.xx..o  o  oxoxxxoo     x  xo oxx  xo o  x xxooxx  oxox oox  xx xoxo");
+    board_notation_test(&board);
+
+    assert!(STTTBoard::parse_position("x").is_err());
+}
+
+#[test]
+fn sttt_fen() {
+    let empty = format!("{} -", ["........."; 9].join("/"));
+    assert_eq!(STTTBoard::default().to_fen(), empty);
+    assert_eq!(STTTBoard::from_fen(&empty), Ok(STTTBoard::default()));
+
+    let mut board = STTTBoard::default();
+    board.play(Coord::from_xy(4, 4));
+    let fen = "........./........./........./........./....x..../........./........./........./......... e5";
+    assert_eq!(board.to_fen(), fen);
+    assert_eq!(STTTBoard::from_fen(fen), Ok(board));
+
+    let mut rng = SmallRng::seed_from_u64(0);
+    for _ in 0..100 {
+        let board = random_board_with_moves(&STTTBoard::default(), rng.gen_range(0..60), &mut rng);
+        assert_eq!(STTTBoard::from_fen(&board.to_fen()), Ok(board));
+    }
+}
+
+#[test]
+fn sttt_fen_invalid() {
+    let with_tiles = |tiles: &[(u8, u8, Player)], last: &str| {
+        let mut rows = vec![vec!['.'; 9]; 9];
+        for &(x, y, player) in tiles {
+            rows[y as usize][x as usize] = if player == Player::A { 'x' } else { 'o' };
+        }
+        let rows = rows
+            .iter()
+            .map(|row| row.iter().collect::<String>())
+            .collect::<Vec<_>>();
+        format!("{} {}", rows.join("/"), last)
+    };
+
+    // missing last move
+    assert!(STTTBoard::from_fen(&with_tiles(&[(4, 4, Player::A)], "-")).is_err());
+    // last move on an empty tile
+    assert!(STTTBoard::from_fen(&with_tiles(&[(4, 4, Player::A)], "a1")).is_err());
+    // last move by the wrong player
+    assert!(STTTBoard::from_fen(&with_tiles(&[(4, 4, Player::A), (0, 0, Player::B)], "e5")).is_err());
+    // too many tiles for player B
+    assert!(STTTBoard::from_fen(&with_tiles(&[(4, 4, Player::B)], "e5")).is_err());
+    // invalid tile and last move
+    assert!(STTTBoard::from_fen(&with_tiles(&[], "-").replacen('.', "?", 1)).is_err());
+    assert!(STTTBoard::from_fen(&with_tiles(&[(4, 4, Player::A)], "j1")).is_err());
+}
@@ -1,8 +1,11 @@
-use board_game::board::{Board, Outcome, Player};
+use board_game::board::{Board, BoardNotation, Outcome, Player, ZobristHash};
 use board_game::games::ttt::TTTBoard;
+use board_game::util::board_gen::random_board_with_moves;
 use board_game::util::coord::Coord3;
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
 
-use crate::board::board_test_main;
+use crate::board::{board_notation_test, board_test_main, board_zobrist_test};
 
 #[test]
 fn empty() {
@@ -38,3 +41,50 @@ fn always_play_first() {
     board_test_main(&board);
     assert_eq!(board.outcome(), Some(Outcome::WonBy(Player::A)));
 }
+
+#[test]
+fn zobrist() {
+    board_zobrist_test(&TTTBoard::default());
+
+    // the same position reached with a different move order
+    let moves_a = [(0, 0), (1, 1), (2, 2)];
+    let moves_b = [(2, 2), (1, 1), (0, 0)];
+
+    let mut board_a = TTTBoard::default();
+    moves_a.iter().for_each(|&(x, y)| board_a.play(Coord3::from_xy(x, y)));
+    let mut board_b = TTTBoard::default();
+    moves_b.iter().for_each(|&(x, y)| board_b.play(Coord3::from_xy(x, y)));
+
+    assert_eq!(board_a, board_b);
+    assert_eq!(board_a.zobrist(), board_b.zobrist());
+    assert_ne!(board_a.zobrist(), TTTBoard::default().zobrist());
+}
+
+#[test]
+fn notation() {
+    board_notation_test(&TTTBoard::default());
+
+    let mut board = TTTBoard::default();
+    board.play(Coord3::from_xy(0, 0));
+    board.play(Coord3::from_xy(1, 1));
+    assert_eq!(board.format_position(), "x../.o./...");
+    assert_eq!(TTTBoard::parse_position(&board.format_position()), Ok(board.clone()));
+    assert_eq!(board.format_move(Coord3::from_xy(2, 1)), "c2");
+
+    // both players have a line
+    assert!(TTTBoard::parse_position("xxx/ooo/...").is_err());
+    // A has a line but B moved last
+    assert!(TTTBoard::parse_position("xxx/oo./o..").is_err());
+    // too many tiles for player A
+    assert!(TTTBoard::parse_position("xx./.../...").is_err());
+    assert!(TTTBoard::parse_position("x?./.../...").is_err());
+}
+
+#[test]
+fn fen() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    for _ in 0..100 {
+        let board = random_board_with_moves(&TTTBoard::default(), rng.gen_range(0..9), &mut rng);
+        assert_eq!(TTTBoard::from_fen(&board.to_fen()), Ok(board));
+    }
+}
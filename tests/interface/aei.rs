@@ -0,0 +1,144 @@
+use std::io::Cursor;
+use std::time::Duration;
+
+use rand::rngs::SmallRng;
+use rand::SeedableRng;
+
+use board_game::ai::simple::{RandomBot, RolloutBot};
+use board_game::ai::Bot;
+use board_game::board::{Board, Player};
+use board_game::games::arimaa::ArimaaBoard;
+use board_game::interface::aei::notation::{format_position, format_turn, parse_position, parse_turn};
+use board_game::interface::aei::server::{AeiServer, AeiTimeControl};
+use board_game::interface::aei::TCOptionName;
+
+const GOLD_SETUP: &str = "Ra1 Rb1 Rc1 Rd1 Re1 Rf1 Rg1 Rh1 Da2 Hb2 Cc2 Md2 Ee2 Cf2 Hg2 Dh2";
+const SILVER_SETUP: &str = "ra8 rb8 rc8 rd8 re8 rf8 rg8 rh8 da7 hb7 cc7 ed7 me7 cf7 hg7 dh7";
+const POSITION: &str = "g [rrrrrrrrdhcemchd                                DHCMECHDRRRRRRRR]";
+
+#[test]
+fn handshake() {
+    let output = run_aei(RandomBot::new(SmallRng::seed_from_u64(0)), "aei\nisready\nquit\n");
+    assert_eq!(
+        output,
+        vec![
+            "protocol-version 1",
+            "id name test",
+            "id author tester",
+            "aeiok",
+            "readyok"
+        ]
+    );
+}
+
+#[test]
+fn setup_turn() {
+    let output = run_aei(RandomBot::new(SmallRng::seed_from_u64(0)), "newgame\ngo\nquit\n");
+    assert_eq!(output.len(), 1);
+
+    let board = ArimaaBoard::default();
+    let actions = assert_bestmove(&board, &output[0]);
+    assert_eq!(actions.len(), 16);
+}
+
+#[test]
+fn makemove_go() {
+    let input = format!(
+        "newgame\nmakemove {}\nmakemove {}\ngo\nquit\n",
+        GOLD_SETUP, SILVER_SETUP
+    );
+    let output = run_aei(RolloutBot::new(100, SmallRng::seed_from_u64(0)), &input);
+    assert_eq!(output.len(), 1);
+
+    let mut board = ArimaaBoard::default();
+    for mv in [GOLD_SETUP, SILVER_SETUP] {
+        for action in parse_turn(&board, mv).unwrap() {
+            board.play(action);
+        }
+    }
+    assert_eq!(board.next_player(), Player::A);
+    assert!(board.state().is_play_phase());
+
+    let actions = assert_bestmove(&board, &output[0]);
+    assert!(!actions.is_empty() && actions.len() <= ArimaaBoard::MAX_STEPS_PER_TURN + 1);
+}
+
+#[test]
+fn setposition_go() {
+    let input = format!("setposition {}\ngo\nstop\nquit\n", POSITION);
+    let output = run_aei(RolloutBot::new(100, SmallRng::seed_from_u64(0)), &input);
+
+    assert_bestmove(&parse_position(POSITION).unwrap(), output.last().unwrap());
+}
+
+#[test]
+fn position_round_trip() {
+    let board = parse_position(POSITION).unwrap();
+    assert_eq!(board.next_player(), Player::A);
+    assert_eq!(format_position(&board), POSITION);
+}
+
+#[test]
+fn time_control_options() {
+    let input = format!(
+        "setposition {}\nsetoption name tcmove value 1\nsetoption name greserve value 0\nsetoption name tcmax value x\ngo\nquit\n",
+        POSITION
+    );
+    let output = run_aei(RolloutBot::new(u32::MAX, SmallRng::seed_from_u64(0)), &input);
+
+    assert_eq!(output.len(), 2);
+    assert!(output[0].starts_with("log Error"), "got {:?}", output[0]);
+    assert_bestmove(&parse_position(POSITION).unwrap(), &output[1]);
+}
+
+#[test]
+fn time_control_turn_time() {
+    let mut tc = AeiTimeControl::default();
+    assert_eq!(tc.turn_time(Player::A), None);
+
+    tc.set(TCOptionName::TcMove, 10);
+    tc.set(TCOptionName::MoveUsed, 2);
+    tc.set(TCOptionName::GReserve, 60);
+    tc.set(TCOptionName::SReserve, 0);
+    assert_eq!(tc.turn_time(Player::A), Some(Duration::from_secs(9)));
+    assert_eq!(tc.turn_time(Player::B), Some(Duration::from_secs(8) * 9 / 10));
+
+    tc.set(TCOptionName::TcTurnTime, 5);
+    assert_eq!(tc.turn_time(Player::A), Some(Duration::from_secs(3) * 9 / 10));
+}
+
+#[test]
+fn invalid_input() {
+    let input = "foo\nsetposition g [rr]\nmakemove Zz9n\ngo\nquit\n";
+    let output = run_aei(RandomBot::new(SmallRng::seed_from_u64(0)), input);
+
+    assert_eq!(output.len(), 4);
+    assert!(output[..3].iter().all(|line| line.starts_with("log Error")));
+    assert_bestmove(&ArimaaBoard::default(), &output[3]);
+}
+
+fn run_aei(bot: impl Bot<ArimaaBoard> + Send + 'static, input: &str) -> Vec<String> {
+    let mut output = vec![];
+    AeiServer::new("test", "tester")
+        .run(bot, Cursor::new(input.to_owned()), &mut output, std::io::sink())
+        .unwrap();
+
+    String::from_utf8(output).unwrap().lines().map(str::to_owned).collect()
+}
+
+/// Check that `line` is a valid `bestmove` for `board` that ends the turn, and return the parsed actions.
+fn assert_bestmove(board: &ArimaaBoard, line: &str) -> Vec<arimaa_engine_step::Action> {
+    let mv = line
+        .strip_prefix("bestmove ")
+        .unwrap_or_else(|| panic!("expected bestmove, got {:?}", line));
+    let actions = parse_turn(board, mv).unwrap();
+
+    let mut next = board.clone();
+    for &action in &actions {
+        next.play(action);
+    }
+    assert!(next.is_done() || next.next_player() != board.next_player());
+    assert_eq!(format_turn(board, &actions), mv);
+
+    actions
+}
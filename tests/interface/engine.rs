@@ -0,0 +1,133 @@
+use std::io::Cursor;
+
+use rand::rngs::SmallRng;
+use rand::SeedableRng;
+
+use board_game::ai::mcts::MCTSBot;
+use board_game::ai::simple::RandomBot;
+use board_game::ai::Bot;
+use board_game::board::Board;
+use board_game::games::ataxx::{AtaxxBoard, Move};
+use board_game::interface::engine::server::EngineServer;
+use board_game::interface::uai::codec::UaiCodec;
+
+use crate::interface::IgnoreLimits;
+
+#[test]
+fn handshake() {
+    let output = run_uai(RandomBot::new(SmallRng::seed_from_u64(0)), "uai\nisready\nquit\n");
+    assert_eq!(output, vec!["id name test", "id author tester", "uaiok", "readyok"]);
+}
+
+#[test]
+fn go_moves() {
+    let input = "uainewgame\nposition startpos moves g2\ngo nodes 100\nquit\n";
+    let output = run_uai(MCTSBot::new(1000, 2.0, SmallRng::seed_from_u64(0)), input);
+
+    let mut board = AtaxxBoard::default();
+    board.play(Move::from_uai("g2").unwrap());
+    assert_bestmove(&board, output.last().unwrap());
+}
+
+#[test]
+fn go_infinite_stop() {
+    let input = "position startpos\ngo infinite\nstop\nquit\n";
+    let output = run_uai(MCTSBot::new(1000, 2.0, SmallRng::seed_from_u64(0)), input);
+
+    assert_eq!(output.len(), 1);
+    assert_bestmove(&AtaxxBoard::default(), &output[0]);
+}
+
+#[test]
+fn go_infinite_waits_for_stop() {
+    // the random bot finishes immediately, but bestmove is only sent after stop
+    let input = "position startpos moves\ngo infinite\nisready\nstop\nquit\n";
+    let output = run_uai(RandomBot::new(SmallRng::seed_from_u64(0)), input);
+
+    assert_eq!(output.len(), 2);
+    assert_eq!(output[0], "readyok");
+    assert_bestmove(&AtaxxBoard::default(), &output[1]);
+}
+
+#[test]
+fn takeback() {
+    let input = "position startpos moves g2 a2\ntakeback\ngo\nquit\n";
+    let output = run_uai(RandomBot::new(SmallRng::seed_from_u64(0)), input);
+
+    let mut board = AtaxxBoard::default();
+    board.play(Move::from_uai("g2").unwrap());
+    assert_bestmove(&board, output.last().unwrap());
+}
+
+#[test]
+fn invalid_input() {
+    let input = "position startpos moves zz\nfoo\nposition fen bar\ngo\nquit\n";
+    let output = run_uai(RandomBot::new(SmallRng::seed_from_u64(0)), input);
+
+    assert_eq!(output.len(), 4);
+    assert!(output[..3].iter().all(|line| line.starts_with("info string error")));
+    assert_bestmove(&AtaxxBoard::default(), &output[3]);
+}
+
+#[test]
+fn search_info() {
+    let server = EngineServer::new(UaiCodec, "test", "tester");
+    let bot = MCTSBot::new(1000, 2.0, SmallRng::seed_from_u64(0)).with_info_callback(server.info_callback());
+
+    // the quit at the end of the input would stop the search right away
+    let output = run_server(server, IgnoreLimits(bot), "position startpos\ngo\nquit\n");
+    let info = &output[output.len() - 2];
+    assert!(info.starts_with("info depth"), "got {:?}", info);
+    assert!(info.contains(" nodes 1000 "), "got {:?}", info);
+    assert!(info.contains(" pv "), "got {:?}", info);
+    assert_bestmove(&AtaxxBoard::default(), output.last().unwrap());
+}
+
+#[test]
+fn bot_panic() {
+    let mut output = vec![];
+    let result = EngineServer::new(UaiCodec, "test", "tester").run(
+        PanicBot,
+        Cursor::new("position startpos\ngo\n".to_owned()),
+        &mut output,
+        std::io::sink(),
+    );
+
+    assert!(result.is_err());
+    let output = String::from_utf8(output).unwrap();
+    assert_eq!(output, "info string error: bot panicked during search: search failed\n");
+}
+
+#[derive(Debug)]
+struct PanicBot;
+
+impl Bot<AtaxxBoard> for PanicBot {
+    fn select_move(&mut self, _: &AtaxxBoard) -> Move {
+        panic!("search failed")
+    }
+}
+
+fn run_uai(bot: impl Bot<AtaxxBoard> + Send + 'static, input: &str) -> Vec<String> {
+    run_server(EngineServer::new(UaiCodec, "test", "tester"), bot, input)
+}
+
+fn run_server<T: Bot<AtaxxBoard> + Send + 'static>(
+    server: EngineServer<AtaxxBoard, UaiCodec, T>,
+    bot: T,
+    input: &str,
+) -> Vec<String> {
+    let mut output = vec![];
+    server
+        .run(bot, Cursor::new(input.to_owned()), &mut output, std::io::sink())
+        .unwrap();
+
+    String::from_utf8(output).unwrap().lines().map(str::to_owned).collect()
+}
+
+fn assert_bestmove(board: &AtaxxBoard, line: &str) {
+    let mv = line
+        .strip_prefix("bestmove ")
+        .unwrap_or_else(|| panic!("expected bestmove, got {:?}", line));
+    let mv = Move::from_uai(mv).unwrap();
+    assert!(board.is_available_move(mv), "{} is not available on {}", mv, board);
+}
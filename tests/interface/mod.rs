@@ -0,0 +1,18 @@
+use board_game::ai::Bot;
+use board_game::board::Board;
+
+pub mod aei;
+pub mod engine;
+pub mod process;
+pub mod uci;
+
+/// Wrapper that ignores the search limits, including the stop flag.
+/// Useful to get a complete search even though the input ends with `quit`.
+#[derive(Debug)]
+pub struct IgnoreLimits<T>(pub T);
+
+impl<B: Board, T: Bot<B>> Bot<B> for IgnoreLimits<T> {
+    fn select_move(&mut self, board: &B) -> B::Move {
+        self.0.select_move(board)
+    }
+}
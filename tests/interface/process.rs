@@ -0,0 +1,85 @@
+use std::process::Command;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+use rand::rngs::SmallRng;
+use rand::SeedableRng;
+
+use board_game::ai::limits::SearchLimits;
+use board_game::ai::simple::RandomBot;
+use board_game::ai::Bot;
+use board_game::board::Board;
+use board_game::games::ataxx::AtaxxBoard;
+use board_game::games::chess::ChessBoard;
+use board_game::interface::engine::process::ProcessBot;
+use board_game::interface::uai::codec::UaiCodec;
+use board_game::interface::uci::codec::UciCodec;
+use board_game::util::bot_game;
+
+fn stub_engine(protocol: &str) -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_stub_engine"));
+    command.arg(protocol);
+    command
+}
+
+#[test]
+fn uai_select_move() {
+    let mut bot = ProcessBot::new(stub_engine("uai"), UaiCodec, SearchLimits::nodes(10)).unwrap();
+    assert_eq!(bot.name(), Some("stub"));
+
+    let mut board = AtaxxBoard::default();
+    for _ in 0..10 {
+        if board.is_done() {
+            break;
+        }
+        let mv = bot.select_move(&board);
+        assert!(board.is_available_move(mv));
+        board.play(mv);
+    }
+}
+
+#[test]
+fn uci_select_move() {
+    let mut bot = ProcessBot::new(stub_engine("uci"), UciCodec::default(), SearchLimits::nodes(10)).unwrap();
+    bot.new_game().unwrap();
+
+    // play against another bot so the position is sent as the start position followed by both players' moves
+    let mut opponent = RandomBot::new(SmallRng::seed_from_u64(0));
+    let mut board = ChessBoard::default();
+    for _ in 0..10 {
+        if board.is_done() {
+            break;
+        }
+        let mv = bot.select_move_limited(&board, &SearchLimits::depth(1));
+        assert!(board.is_available_move(mv));
+        board.play(mv);
+
+        if board.is_done() {
+            break;
+        }
+        board.play(opponent.select_move(&board));
+    }
+}
+
+#[test]
+fn stop_flag() {
+    let mut bot = ProcessBot::new(stub_engine("uai"), UaiCodec, SearchLimits::nodes(10)).unwrap();
+
+    let board = AtaxxBoard::default();
+    let limits = SearchLimits::nodes(u64::MAX).with_stop(Arc::new(AtomicBool::new(true)));
+    let mv = bot.select_move_limited(&board, &limits);
+    assert!(board.is_available_move(mv));
+}
+
+#[test]
+fn bot_game_against_process() {
+    let result = bot_game::run(
+        AtaxxBoard::default,
+        || ProcessBot::new(stub_engine("uai"), UaiCodec, SearchLimits::nodes(10)).unwrap(),
+        || RandomBot::new(SmallRng::seed_from_u64(0)),
+        1,
+        true,
+        |_, _| {},
+    );
+    assert_eq!(result.replays.len(), 2);
+}
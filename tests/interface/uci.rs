@@ -0,0 +1,160 @@
+use std::io::Cursor;
+use std::sync::{Arc, Mutex};
+
+use chess::ChessMove;
+use rand::rngs::SmallRng;
+use rand::SeedableRng;
+
+use board_game::ai::info::Score;
+use board_game::ai::mcts::MCTSBot;
+use board_game::ai::minimax::MiniMaxBot;
+use board_game::ai::simple::RandomBot;
+use board_game::ai::Bot;
+use board_game::board::Board;
+use board_game::games::chess::ChessBoard;
+use board_game::heuristic::chess::ChessPieceValueHeuristic;
+use board_game::interface::engine::server::EngineServer;
+use board_game::interface::engine::{EngineOption, OptionKind};
+use board_game::interface::uci::codec::UciCodec;
+use board_game::wdl::WDL;
+
+use crate::interface::IgnoreLimits;
+
+#[test]
+fn handshake_options() {
+    let options = vec![
+        EngineOption::new(
+            "Depth",
+            OptionKind::Spin {
+                default: 3,
+                min: 1,
+                max: 10,
+            },
+        ),
+        EngineOption::new("Ponder", OptionKind::Check { default: false }),
+    ];
+
+    let received = Arc::new(Mutex::new(vec![]));
+    let received_clone = received.clone();
+    let server = EngineServer::new(UciCodec::default(), "test", "tester").with_options(
+        options,
+        move |_: &mut RandomBot<SmallRng>, name: &str, value: Option<&str>| {
+            let value = value.ok_or("missing value")?;
+            let value = value.parse::<i64>().map_err(|e| e.to_string())?;
+            received_clone.lock().unwrap().push((name.to_owned(), value));
+            Ok(())
+        },
+    );
+
+    let input = "uci\nsetoption name depth value 5\nsetoption name Depth value x\nsetoption name Hash value 16\nquit\n";
+    let output = run_server(server, RandomBot::new(SmallRng::seed_from_u64(0)), input);
+
+    assert_eq!(
+        output[..5],
+        [
+            "id name test",
+            "id author tester",
+            "option name Depth type spin default 3 min 1 max 10",
+            "option name Ponder type check default false",
+            "uciok",
+        ]
+    );
+    assert!(output[5].starts_with("info string error"), "got {:?}", output[5]);
+    assert!(output[6].starts_with("info string warning"), "got {:?}", output[6]);
+    assert_eq!(output.len(), 7);
+    assert_eq!(*received.lock().unwrap(), vec![("Depth".to_owned(), 5)]);
+}
+
+#[test]
+fn startpos_moves_depth() {
+    let server = EngineServer::new(UciCodec::default(), "test", "tester");
+    let bot = MiniMaxBot::new(2, ChessPieceValueHeuristic, SmallRng::seed_from_u64(0))
+        .with_info_callback(server.info_callback());
+
+    let input = "ucinewgame\nposition startpos moves e2e4 e7e5\ngo depth 2\nquit\n";
+    let output = run_server(server, IgnoreLimits(bot), input);
+
+    let mut board = ChessBoard::default();
+    board.play(board.parse_move("e2e4").unwrap());
+    board.play(board.parse_move("e7e5").unwrap());
+
+    let info = &output[output.len() - 2];
+    assert!(info.starts_with("info depth 2 "), "got {:?}", info);
+    assert!(info.contains(" score cp "), "got {:?}", info);
+    assert_bestmove(&board, output.last().unwrap());
+}
+
+#[test]
+fn fen_clock_nodes() {
+    // white can capture the undefended queen
+    let fen = "4k3/8/8/3q4/4P3/8/8/4K3 w - - 0 1";
+    let board = ChessBoard::new_without_history_fen(fen, Default::default());
+    let capture = board.parse_move("e4d5").unwrap();
+
+    let input = format!(
+        "position fen {}\ngo wtime 10000 btime 10000 winc 100 binc 100 movestogo 20\nquit\n",
+        fen
+    );
+    let output = run_uci(
+        MiniMaxBot::new(2, ChessPieceValueHeuristic, SmallRng::seed_from_u64(0)),
+        &input,
+    );
+    assert_eq!(output, vec![format!("bestmove {}", capture)]);
+
+    let input = format!("position fen {}\ngo nodes 200\nquit\n", fen);
+    let output = run_uci(MCTSBot::new(1000, 2.0, SmallRng::seed_from_u64(0)), &input);
+    assert_bestmove(&board, output.last().unwrap());
+}
+
+#[test]
+fn go_infinite_stop() {
+    let input = "position startpos\ngo infinite\nstop\nquit\n";
+    let output = run_uci(MCTSBot::new(1000, 2.0, SmallRng::seed_from_u64(0)), input);
+
+    assert_eq!(output.len(), 1);
+    assert_bestmove(&ChessBoard::default(), &output[0]);
+}
+
+#[test]
+fn invalid_position() {
+    let input = "position fen foo\nposition startpos moves e2e5\ngo\nquit\n";
+    let output = run_uci(RandomBot::new(SmallRng::seed_from_u64(0)), input);
+
+    assert_eq!(output.len(), 3);
+    assert!(output[..2].iter().all(|line| line.starts_with("info string error")));
+    assert_bestmove(&ChessBoard::default(), &output[2]);
+}
+
+#[test]
+fn centipawns() {
+    let codec = UciCodec::default();
+    assert_eq!(codec.centipawns(&Score::from_value(1.5)), 150);
+    assert_eq!(codec.centipawns(&Score::from_value(-1e9)), -20000);
+    assert_eq!(codec.centipawns(&Score::from_wdl(WDL::new(0.3, 0.4, 0.3))), 0);
+    assert!(codec.centipawns(&Score::from_wdl(WDL::new(0.6, 0.2, 0.2))) > 0);
+}
+
+fn run_uci(bot: impl Bot<ChessBoard> + Send + 'static, input: &str) -> Vec<String> {
+    run_server(EngineServer::new(UciCodec::default(), "test", "tester"), bot, input)
+}
+
+fn run_server<T: Bot<ChessBoard> + Send + 'static>(
+    server: EngineServer<ChessBoard, UciCodec, T>,
+    bot: T,
+    input: &str,
+) -> Vec<String> {
+    let mut output = vec![];
+    server
+        .run(bot, Cursor::new(input.to_owned()), &mut output, std::io::sink())
+        .unwrap();
+
+    String::from_utf8(output).unwrap().lines().map(str::to_owned).collect()
+}
+
+fn assert_bestmove(board: &ChessBoard, line: &str) {
+    let mv = line
+        .strip_prefix("bestmove ")
+        .unwrap_or_else(|| panic!("expected bestmove, got {:?}", line));
+    let mv: ChessMove = board.parse_move(mv).unwrap();
+    assert!(board.is_available_move(mv), "{} is not available on {}", mv, board);
+}
@@ -1,2 +1,4 @@
 pub mod ai;
 pub mod board;
+pub mod interface;
+pub mod util;
@@ -0,0 +1,103 @@
+use rand::rngs::SmallRng;
+use rand::SeedableRng;
+
+use board_game::ai::simple::RandomBot;
+use board_game::board::{Board, Outcome, Player};
+use board_game::games::ataxx::AtaxxBoard;
+use board_game::games::chess::ChessBoard;
+use board_game::interface::engine::{BoardCodec, NotationCodec};
+use board_game::interface::uai::codec::UaiCodec;
+use board_game::interface::uci::codec::UciCodec;
+use board_game::util::bot_game;
+use board_game::util::bot_game::Replay;
+use board_game::util::game_record::{format_replay, parse_replays, write_replays};
+
+#[test]
+fn ataxx_round_trip() {
+    round_trip(&UaiCodec, AtaxxBoard::default);
+}
+
+#[test]
+fn chess_round_trip() {
+    round_trip(&UciCodec::default(), ChessBoard::default);
+}
+
+#[test]
+fn notation_round_trip() {
+    round_trip(&NotationCodec::new("ataxx", AtaxxBoard::default()), AtaxxBoard::default);
+}
+
+#[test]
+fn escaped_tags() {
+    let replay = Replay {
+        start: AtaxxBoard::default(),
+        player_l: Player::B,
+        moves: vec![],
+        outcome: Outcome::WonBy(Player::A),
+        lost_on_time: Some(Player::B),
+        total_time_l: 0.25,
+        total_time_r: 1.0 / 3.0,
+        move_count_l: 0,
+        move_count_r: 1,
+        debug_l: "Bot { name: \"a\\b\" }".to_owned(),
+        debug_r: "multi\nline".to_owned(),
+    };
+
+    let text = format_replay(&UaiCodec, &replay);
+    assert!(text.contains(r#"[BotL "Bot { name: \"a\\b\" }"]"#));
+    assert!(text.contains(r#"[BotR "multi\nline"]"#));
+    assert!(text.contains(r#"[LostOnTime "B"]"#));
+
+    let parsed = parse_replays(&UaiCodec, &text).unwrap();
+    assert_eq!(parsed.len(), 1);
+    assert_same(&replay, &parsed[0]);
+}
+
+#[test]
+fn invalid_records() {
+    let valid = "[Start \"x5o/7/7/7/7/7/o5x x 0 1\"]\n[PlayerL \"A\"]\n[Outcome \"Draw\"]\n";
+    assert_eq!(parse_replays(&UaiCodec, valid).unwrap().len(), 1);
+
+    let missing = "[Start \"x5o/7/7/7/7/7/o5x x 0 1\"]\n[PlayerL \"A\"]\n";
+    assert_eq!(parse_replays(&UaiCodec, missing).unwrap_err().line, 1);
+
+    let bad_move = format!("{}f1 f1\n", valid);
+    assert_eq!(parse_replays(&UaiCodec, &bad_move).unwrap_err().line, 4);
+
+    let bad_outcome = "[Start \"x5o/7/7/7/7/7/o5x x 0 1\"]\n[PlayerL \"A\"]\n[Outcome \"C\"]\n";
+    assert_eq!(parse_replays(&UaiCodec, bad_outcome).unwrap_err().line, 3);
+}
+
+fn round_trip<B: Board, C: BoardCodec<B>>(codec: &C, start: impl Fn() -> B + Sync) {
+    let result = bot_game::run(
+        start,
+        || RandomBot::new(SmallRng::seed_from_u64(0)),
+        || RandomBot::new(SmallRng::seed_from_u64(1)),
+        2,
+        true,
+        |_, _| {},
+    );
+
+    let mut buffer = vec![];
+    write_replays(codec, &result.replays, &mut buffer).unwrap();
+    let text = String::from_utf8(buffer).unwrap();
+
+    let parsed = parse_replays(codec, &text).unwrap();
+    assert_eq!(parsed.len(), result.replays.len());
+    for (expected, actual) in result.replays.iter().zip(&parsed) {
+        assert_same(expected, actual);
+    }
+}
+
+fn assert_same<B: Board>(expected: &Replay<B>, actual: &Replay<B>) {
+    assert_eq!(expected.player_l, actual.player_l);
+    assert_eq!(expected.moves, actual.moves);
+    assert_eq!(expected.outcome, actual.outcome);
+    assert_eq!(expected.lost_on_time, actual.lost_on_time);
+    assert_eq!(expected.total_time_l, actual.total_time_l);
+    assert_eq!(expected.total_time_r, actual.total_time_r);
+    assert_eq!(expected.move_count_l, actual.move_count_l);
+    assert_eq!(expected.move_count_r, actual.move_count_r);
+    assert_eq!(expected.debug_l, actual.debug_l);
+    assert_eq!(expected.debug_r, actual.debug_r);
+}
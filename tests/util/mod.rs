@@ -0,0 +1,5 @@
+pub mod game_record;
+pub mod opening;
+pub mod rating;
+pub mod sprt;
+pub mod tournament;
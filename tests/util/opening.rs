@@ -0,0 +1,99 @@
+use rand::rngs::SmallRng;
+use rand::SeedableRng;
+
+use board_game::board::Board;
+use board_game::games::ataxx::{AtaxxBoard, Move};
+use board_game::games::chess::ChessBoard;
+use board_game::heuristic::ataxx::AtaxxTileHeuristic;
+use board_game::util::opening::{parse_ataxx_opening, parse_chess_opening, OpeningBook};
+
+#[test]
+fn ataxx_lines() {
+    let text = "\
+# comment
+x5o/7/7/7/7/7/o5x x 0 1
+
+f1 a2
+";
+    let book = OpeningBook::parse_lines(text, parse_ataxx_opening).unwrap();
+    assert_eq!(book.len(), 2);
+    assert_eq!(
+        book.positions()[0],
+        AtaxxBoard::from_fen("x5o/7/7/7/7/7/o5x x 0 1").unwrap()
+    );
+
+    let mut expected = AtaxxBoard::default();
+    for mv in ["f1", "a2"] {
+        expected.play(Move::from_uai(mv).unwrap());
+    }
+    assert_eq!(book.positions()[1], expected);
+}
+
+#[test]
+fn ataxx_invalid_line() {
+    let err = OpeningBook::parse_lines("f1\nf1 z9\n", parse_ataxx_opening).unwrap_err();
+    assert_eq!(err.line, 2);
+    assert_eq!(err.text, "f1 z9");
+
+    assert!(OpeningBook::parse_lines("# only a comment\n", parse_ataxx_opening).is_err());
+}
+
+#[test]
+fn chess_lines() {
+    let text = "\
+rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1
+1. e4 e5 2. Nf3
+e2e4 e7e5
+";
+    let book = OpeningBook::parse_lines(text, parse_chess_opening).unwrap();
+    assert_eq!(book.len(), 3);
+
+    let mut expected = ChessBoard::default();
+    for mv in ["e4", "e5", "Nf3"] {
+        expected.play(expected.parse_move(mv).unwrap());
+    }
+    assert_eq!(book.positions()[1], expected);
+
+    let mut expected = ChessBoard::default();
+    for mv in ["e2e4", "e7e5"] {
+        expected.play(expected.parse_move(mv).unwrap());
+    }
+    assert_eq!(book.positions()[2], expected);
+}
+
+#[test]
+fn sequential_cycles() {
+    let book = OpeningBook::parse_lines("f1\nf2\n", parse_ataxx_opening).unwrap();
+    let start = book.sequential();
+    let starts = (0..4).map(|_| start()).collect::<Vec<_>>();
+    assert_eq!(starts[0], starts[2]);
+    assert_eq!(starts[1], starts[3]);
+    assert_ne!(starts[0], starts[1]);
+}
+
+#[test]
+fn generate_distinct() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let book = OpeningBook::generate(&AtaxxBoard::default(), 20, 4, &mut rng, |_| true);
+    assert_eq!(book.len(), 20);
+
+    for (i, a) in book.positions().iter().enumerate() {
+        assert!(!a.is_done());
+        for b in &book.positions()[i + 1..] {
+            assert_ne!(a, b);
+        }
+    }
+}
+
+#[test]
+fn generate_balanced() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let heuristic = AtaxxTileHeuristic::default();
+    let book = OpeningBook::generate_balanced(&AtaxxBoard::default(), 10, 6, &heuristic, 2, 150, &mut rng);
+    assert_eq!(book.len(), 10);
+
+    for board in book.positions() {
+        let value = board_game::ai::minimax::minimax_value(board, &heuristic, 2);
+        assert!(value.abs() <= 150);
+    }
+}
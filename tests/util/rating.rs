@@ -0,0 +1,64 @@
+use board_game::util::rating::{elo_from_wdl, ratings_from_cross_table, RatingMethod};
+use board_game::wdl::WDL;
+
+fn two_player_table(wdl: WDL<u32>) -> Vec<Vec<WDL<u32>>> {
+    vec![
+        vec![WDL::default(), wdl],
+        vec![WDL::new(wdl.loss, wdl.draw, wdl.win), WDL::default()],
+    ]
+}
+
+#[test]
+fn maximum_likelihood_matches_pair() {
+    let wdl = WDL::new(60, 20, 20);
+    let ratings = ratings_from_cross_table(&two_player_table(wdl), RatingMethod::MaximumLikelihood);
+
+    let expected = elo_from_wdl(wdl.cast());
+    let diff = ratings[0].elo - ratings[1].elo;
+    assert!((diff - expected).abs() < 0.5, "expected {}, got {}", expected, diff);
+    assert!((ratings[0].elo + ratings[1].elo).abs() < 1e-3);
+    assert!(ratings[0].margin > 0.0 && ratings[0].margin.is_finite());
+}
+
+#[test]
+fn more_games_smaller_margin() {
+    let few = ratings_from_cross_table(&two_player_table(WDL::new(6, 2, 2)), RatingMethod::MaximumLikelihood);
+    let many = ratings_from_cross_table(&two_player_table(WDL::new(60, 20, 20)), RatingMethod::MaximumLikelihood);
+    assert!(many[0].margin < few[0].margin);
+}
+
+#[test]
+fn bayes_elo_perfect_score_finite() {
+    let ratings = ratings_from_cross_table(&two_player_table(WDL::new(10, 0, 0)), RatingMethod::bayes_elo());
+    assert!(ratings[0].elo > 0.0 && ratings[0].elo.is_finite());
+    assert!(ratings[0].elo < 1000.0);
+    assert!(ratings[0].margin.is_finite());
+}
+
+#[test]
+fn transitive_ranking() {
+    // a beats b, b beats c, a and c never played
+    let empty = WDL::default();
+    let table = vec![
+        vec![empty, WDL::new(15, 0, 5), empty],
+        vec![WDL::new(5, 0, 15), empty, WDL::new(15, 0, 5)],
+        vec![empty, WDL::new(5, 0, 15), empty],
+    ];
+
+    for method in [RatingMethod::MaximumLikelihood, RatingMethod::bayes_elo()] {
+        let ratings = ratings_from_cross_table(&table, method);
+        assert!(
+            ratings[0].elo > ratings[1].elo && ratings[1].elo > ratings[2].elo,
+            "{:?}",
+            ratings
+        );
+        assert!((ratings[0].elo + ratings[2].elo).abs() < 0.5, "{:?}", ratings);
+    }
+}
+
+#[test]
+fn no_games() {
+    let ratings = ratings_from_cross_table(&[vec![WDL::default()]], RatingMethod::bayes_elo());
+    assert_eq!(ratings[0].elo, 0.0);
+    assert!(ratings[0].margin.is_infinite());
+}
@@ -0,0 +1,120 @@
+use rand::rngs::SmallRng;
+use rand::SeedableRng;
+
+use board_game::ai::simple::RandomBot;
+use board_game::ai::solver::SolverBot;
+use board_game::games::ttt::TTTBoard;
+use board_game::util::bot_game::{run_sprt, TimeControl};
+use board_game::util::sprt::{Pentanomial, Sprt, SprtStatus};
+use board_game::wdl::{OutcomeWDL, WDL};
+
+#[test]
+fn bounds() {
+    let sprt = Sprt::new(0.0, 5.0, 0.05, 0.05);
+    let (lower, upper) = sprt.bounds();
+    assert!((lower + 2.944).abs() < 1e-3);
+    assert!((upper - 2.944).abs() < 1e-3);
+
+    assert_eq!(sprt.status(0.0), SprtStatus::Continue);
+    assert_eq!(sprt.status(-3.0), SprtStatus::AcceptH0);
+    assert_eq!(sprt.status(3.0), SprtStatus::AcceptH1);
+}
+
+#[test]
+fn llr_trinomial() {
+    let sprt = Sprt::new(0.0, 5.0, 0.05, 0.05);
+
+    assert_eq!(sprt.llr_trinomial(WDL::default()), 0.0);
+    assert_eq!(sprt.llr_trinomial(WDL::new(10, 0, 0)), 0.0);
+
+    let even = sprt.llr_trinomial(WDL::new(1000, 1000, 1000));
+    let ahead = sprt.llr_trinomial(WDL::new(1100, 1000, 900));
+    let behind = sprt.llr_trinomial(WDL::new(900, 1000, 1100));
+    assert!(even < 0.0);
+    assert!(ahead > 0.0);
+    assert!(behind < even);
+
+    // more games with the same score give more evidence
+    let ahead_more = sprt.llr_trinomial(WDL::new(2200, 2000, 1800));
+    assert!(ahead_more > ahead);
+}
+
+#[test]
+fn llr_pentanomial() {
+    let sprt = Sprt::new(0.0, 5.0, 0.05, 0.05).with_pentanomial(true);
+
+    let mut pentanomial = Pentanomial::default();
+    pentanomial.add_pair(OutcomeWDL::Win, OutcomeWDL::Loss);
+    pentanomial.add_pair(OutcomeWDL::Win, OutcomeWDL::Draw);
+    pentanomial.add_pair(OutcomeWDL::Loss, OutcomeWDL::Loss);
+    pentanomial.add_pair(OutcomeWDL::Win, OutcomeWDL::Win);
+    assert_eq!(pentanomial.counts, [1, 0, 1, 1, 1]);
+    assert_eq!(pentanomial.pair_count(), 4);
+
+    let llr = sprt.llr_pentanomial(Pentanomial {
+        counts: [100, 400, 1000, 500, 100],
+    });
+    assert!(llr > 0.0);
+}
+
+#[test]
+fn stops_early() {
+    let sprt = Sprt::new(0.0, 50.0, 0.05, 0.05);
+    let result = run_sprt(
+        TTTBoard::default,
+        || SolverBot::new(9, SmallRng::seed_from_u64(0)),
+        // with a fixed seed every game would be the same, so the outcomes would have no variance
+        || RandomBot::new(SmallRng::from_entropy()),
+        1000,
+        false,
+        TimeControl::Unlimited,
+        sprt,
+        |_, _, _| {},
+    );
+
+    assert_eq!(result.status, SprtStatus::AcceptH1);
+    assert!(result.llr >= sprt.bounds().1);
+    assert!(result.result.game_count < 1000);
+    assert_eq!(result.result.game_count as usize, result.result.replays.len());
+    assert_eq!(result.pentanomial, None);
+}
+
+#[test]
+fn stops_early_pentanomial() {
+    let sprt = Sprt::new(0.0, 50.0, 0.05, 0.05).with_pentanomial(true);
+    let result = run_sprt(
+        TTTBoard::default,
+        || SolverBot::new(9, SmallRng::seed_from_u64(0)),
+        || RandomBot::new(SmallRng::from_entropy()),
+        1000,
+        true,
+        TimeControl::Unlimited,
+        sprt,
+        |_, _, _| {},
+    );
+
+    assert_eq!(result.status, SprtStatus::AcceptH1);
+    assert!(result.result.game_count < 2000);
+
+    let pentanomial = result.pentanomial.unwrap();
+    assert!(2 * pentanomial.pair_count() <= result.result.game_count);
+    // the solver never loses
+    assert_eq!(pentanomial.counts[0], 0);
+    assert_eq!(pentanomial.counts[1], 0);
+}
+
+#[test]
+#[should_panic]
+fn pentanomial_requires_both_sides() {
+    let sprt = Sprt::new(0.0, 5.0, 0.05, 0.05).with_pentanomial(true);
+    let _ = run_sprt(
+        TTTBoard::default,
+        || RandomBot::new(SmallRng::seed_from_u64(0)),
+        || RandomBot::new(SmallRng::seed_from_u64(1)),
+        10,
+        false,
+        TimeControl::Unlimited,
+        sprt,
+        |_, _, _| {},
+    );
+}
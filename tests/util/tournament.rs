@@ -0,0 +1,67 @@
+use rand::rngs::SmallRng;
+use rand::SeedableRng;
+
+use board_game::ai::simple::RandomBot;
+use board_game::ai::solver::SolverBot;
+use board_game::games::ttt::TTTBoard;
+use board_game::util::bot_game::TimeControl;
+use board_game::util::rating::RatingMethod;
+use board_game::util::tournament::{gauntlet, round_robin, Entrant, MatchSettings};
+use board_game::wdl::WDL;
+
+fn entrants() -> Vec<Entrant<TTTBoard>> {
+    vec![
+        Entrant::new("solver", || SolverBot::new(9, SmallRng::seed_from_u64(0))),
+        Entrant::new("random_a", || RandomBot::new(SmallRng::from_entropy())),
+        Entrant::new("random_b", || RandomBot::new(SmallRng::from_entropy())),
+    ]
+}
+
+fn settings() -> MatchSettings {
+    MatchSettings {
+        games_per_side: 10,
+        both_sides: true,
+        time_control: TimeControl::Unlimited,
+    }
+}
+
+#[test]
+fn round_robin_cross_table() {
+    let mut matches = vec![];
+    let result = round_robin(TTTBoard::default, &entrants(), settings(), |i, j, r| {
+        matches.push((i, j, r.game_count))
+    });
+
+    assert_eq!(matches, vec![(0, 1, 20), (0, 2, 20), (1, 2, 20)]);
+    for i in 0..3 {
+        assert_eq!(result.cross_table[i][i], WDL::default());
+        for j in 0..3 {
+            let (a, b) = (result.cross_table[i][j], result.cross_table[j][i]);
+            assert_eq!((a.win, a.draw, a.loss), (b.loss, b.draw, b.win));
+        }
+    }
+
+    // the solver never loses
+    assert_eq!(result.totals()[0].loss, 0);
+    assert_eq!(result.totals()[0].sum(), 40);
+
+    let ranking = result.ranking(RatingMethod::bayes_elo());
+    assert_eq!(ranking[0].0, 0);
+
+    let table = result.to_string();
+    assert_eq!(table.lines().count(), 4);
+    assert!(table.contains("solver"));
+}
+
+#[test]
+fn gauntlet_only_first() {
+    let result = gauntlet(TTTBoard::default, &entrants(), settings(), |i, _, _| assert_eq!(i, 0));
+
+    assert_eq!(result.cross_table[1][2], WDL::default());
+    assert_eq!(result.cross_table[0][1].sum(), 20);
+    assert_eq!(result.cross_table[0][2].sum(), 20);
+
+    let ratings = result.ratings(RatingMethod::MaximumLikelihood);
+    assert!(ratings[0].elo > ratings[1].elo);
+    assert!(ratings[0].elo > ratings[2].elo);
+}